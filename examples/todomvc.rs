@@ -19,24 +19,23 @@ pub struct TodoItem {
 }
 
 pub fn app(cx: Scope<()>) -> Element {
-    let todos = use_state(&cx, im_rc::HashMap::<u32, TodoItem>::default);
+    let todos = use_map(&cx, Vec::<(u32, TodoItem)>::new);
     let filter = use_state(&cx, || FilterState::All);
     let draft = use_state(&cx, || "".to_string());
     let todo_id = use_state(&cx, || 0);
 
-    // Filter the todos based on the filter state
-    let mut filtered_todos = todos
-        .iter()
-        .filter(|(_, item)| match **filter {
+    // Filter the todos based on the filter state, keeping the order they were added in
+    let filtered_todos = todos
+        .entries()
+        .into_iter()
+        .filter(|todo| match **filter {
             FilterState::All => true,
-            FilterState::Active => !item.checked,
-            FilterState::Completed => item.checked,
+            FilterState::Active => !todo.read().checked,
+            FilterState::Completed => todo.read().checked,
         })
-        .map(|f| *f.0)
         .collect::<Vec<_>>();
-    filtered_todos.sort_unstable();
 
-    let show_clear_completed = todos.values().any(|todo| todo.checked);
+    let show_clear_completed = todos.entries().iter().any(|todo| todo.read().checked);
     let items_left = filtered_todos.len();
     let item_text = match items_left {
         1 => "item",
@@ -57,7 +56,7 @@ pub fn app(cx: Scope<()>) -> Element {
                         oninput: move |evt| draft.set(evt.value.clone()),
                         onkeydown: move |evt| {
                             if evt.key == "Enter" && !draft.is_empty() {
-                                todos.make_mut().insert(
+                                todos.insert(
                                     **todo_id,
                                     TodoItem {
                                         id: **todo_id,
@@ -72,7 +71,10 @@ pub fn app(cx: Scope<()>) -> Element {
                     }
                 }
                 ul { class: "todo-list",
-                    filtered_todos.iter().map(|id| rsx!(todo_entry( key: "{id}", id: *id, todos: todos  )))
+                    filtered_todos.into_iter().map(|todo| {
+                        let id = *todo.key();
+                        rsx!(todo_entry( key: "{id}", todo: todo ))
+                    })
                 }
                 (!todos.is_empty()).then(|| rsx!(
                     footer { class: "footer",
@@ -88,7 +90,7 @@ pub fn app(cx: Scope<()>) -> Element {
                         (show_clear_completed).then(|| rsx!(
                             button {
                                 class: "clear-completed",
-                                onclick: move |_| todos.make_mut().retain(|_, todo| !todo.checked),
+                                onclick: move |_| todos.retain(|_, todo| !todo.checked),
                                 "Clear completed"
                             }
                         ))
@@ -104,21 +106,21 @@ pub fn app(cx: Scope<()>) -> Element {
     })
 }
 
-#[derive(Props)]
-pub struct TodoEntryProps<'a> {
-    todos: &'a UseState<im_rc::HashMap<u32, TodoItem>>,
-    id: u32,
+// Each entry re-renders on its own when its todo changes
+#[derive(Props, PartialEq)]
+pub struct TodoEntryProps {
+    todo: Entry<u32, TodoItem>,
 }
 
-pub fn todo_entry<'a>(cx: Scope<'a, TodoEntryProps<'a>>) -> Element {
+pub fn todo_entry(cx: Scope<TodoEntryProps>) -> Element {
     let is_editing = use_state(&cx, || false);
 
-    let todos = cx.props.todos.get();
-    let todo = &todos[&cx.props.id];
+    let entry = cx.props.todo;
+    let todo = entry.get();
     let completed = if todo.checked { "completed" } else { "" };
     let editing = if **is_editing { "editing" } else { "" };
 
-    cx.render(rsx!{
+    cx.render(rsx! {
         li {
             class: "{completed} {editing}",
             div { class: "view",
@@ -129,7 +131,7 @@ pub fn todo_entry<'a>(cx: Scope<'a, TodoEntryProps<'a>>) -> Element {
                     id: "cbg-{todo.id}",
                     checked: "{todo.checked}",
                     oninput: move |evt| {
-                        cx.props.todos.make_mut()[&cx.props.id].checked = evt.value.parse().unwrap();
+                        entry.write().checked = evt.value.parse().unwrap();
                     }
                 }
 
@@ -144,7 +146,7 @@ pub fn todo_entry<'a>(cx: Scope<'a, TodoEntryProps<'a>>) -> Element {
                 input {
                     class: "edit",
                     value: "{todo.contents}",
-                    oninput: move |evt| cx.props.todos.make_mut()[&cx.props.id].contents = evt.value.clone(),
+                    oninput: move |evt| entry.write().contents = evt.value.clone(),
                     autofocus: "true",
                     onfocusout: move |_| is_editing.set(false),
                     onkeydown: move |evt| {
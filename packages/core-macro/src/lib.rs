@@ -1,3 +1,4 @@
+use dioxus_rsx as rsx;
 use proc_macro::TokenStream;
 use quote::ToTokens;
 use syn::parse_macro_input;
@@ -5,7 +6,6 @@ use syn::parse_macro_input;
 mod ifmt;
 mod inlineprops;
 mod props;
-mod rsx;
 
 #[proc_macro]
 pub fn format_args_f(input: TokenStream) -> TokenStream {
@@ -179,8 +179,16 @@ pub fn derive_typed_builder(input: proc_macro::TokenStream) -> proc_macro::Token
 #[proc_macro_error::proc_macro_error]
 #[proc_macro]
 pub fn rsx(s: TokenStream) -> TokenStream {
+    // with hot reloading, the call needs to know where it is before its tokens are consumed
+    #[cfg(feature = "hot-reload")]
+    let location =
+        rsx::hot_reload::call_location(&s.clone().into(), proc_macro2::Span::call_site());
+
     match syn::parse::<rsx::CallBody>(s) {
         Err(err) => err.to_compile_error().into(),
+        #[cfg(feature = "hot-reload")]
+        Ok(stream) => stream.to_hot_reload_tokens(location).into(),
+        #[cfg(not(feature = "hot-reload"))]
         Ok(stream) => stream.to_token_stream().into(),
     }
 }
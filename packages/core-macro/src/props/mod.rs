@@ -251,6 +251,7 @@ mod field_info {
         pub default: Option<syn::Expr>,
         pub doc: Option<syn::Expr>,
         pub skip: bool,
+        pub skip_compare: bool,
         pub auto_into: bool,
         pub strip_option: bool,
         pub ignore_option: bool,
@@ -378,6 +379,7 @@ mod field_info {
                             }
                             handle_fields!(
                                 "skip", skip, "skipped";
+                                "skip_compare", skip_compare, "skipped during comparison";
                                 "into", auto_into, "calling into() on the argument";
                                 "strip_option", strip_option, "putting the argument in Some(...)";
                             )
@@ -406,6 +408,10 @@ mod field_info {
                                 self.skip = false;
                                 Ok(())
                             }
+                            "skip_compare" => {
+                                self.skip_compare = false;
+                                Ok(())
+                            }
                             "auto_into" => {
                                 self.auto_into = false;
                                 Ok(())
@@ -614,9 +620,53 @@ Finally, call `.build()` to create the instance of `{name}`.
                     .extend(predicates.predicates.clone());
             }
 
-            let can_memoize = match are_there_generics {
-                true => quote! { false  },
-                false => quote! { self == other },
+            let skipped_fields = self.fields.iter().filter(|f| f.builder_attr.skip_compare);
+            if let Some(field) = skipped_fields.clone().next() {
+                if are_there_generics {
+                    return Err(Error::new_spanned(
+                        field.name,
+                        "#[props(skip_compare)] can't be used on props with generic parameters, since props that might borrow are never memoized",
+                    ));
+                }
+                if self.builder_attr.memo.is_some() || self.builder_attr.no_memo {
+                    return Err(Error::new_spanned(
+                        field.name,
+                        "#[props(skip_compare)] has no effect when the props use #[props(memo)] or #[props(no_memo)]",
+                    ));
+                }
+            }
+            if let Some(memo) = &self.builder_attr.memo {
+                if are_there_generics {
+                    return Err(Error::new_spanned(
+                        memo,
+                        "#[props(memo)] can't be used on props with generic parameters, since props that might borrow are never memoized",
+                    ));
+                }
+                if self.builder_attr.no_memo {
+                    return Err(Error::new_spanned(
+                        memo,
+                        "#[props(memo)] and #[props(no_memo)] can't be used together",
+                    ));
+                }
+            }
+
+            let can_memoize = if are_there_generics || self.builder_attr.no_memo {
+                quote! { false }
+            } else if let Some(memo) = &self.builder_attr.memo {
+                quote! { (#memo)(self, other) }
+            } else if skipped_fields.clone().next().is_some() {
+                let compared = self
+                    .fields
+                    .iter()
+                    .filter(|f| !f.builder_attr.skip_compare)
+                    .map(|f| f.name)
+                    .collect::<Vec<_>>();
+                match compared.is_empty() {
+                    true => quote! { true },
+                    false => quote! { #( self.#compared == other.#compared )&&* },
+                }
+            } else {
+                quote! { self == other }
             };
 
             let is_static = match are_there_generics {
@@ -1075,6 +1125,12 @@ Finally, call `.build()` to create the instance of `{name}`.
         /// specify `doc` instead and a default value will be filled in here.
         pub build_method_doc: Option<syn::Expr>,
 
+        /// A `fn(&Self, &Self) -> bool` used to compare the props instead of `PartialEq`.
+        pub memo: Option<syn::Expr>,
+
+        /// Never memoize the props, re-rendering the component whenever its parent renders.
+        pub no_memo: bool,
+
         pub field_defaults: FieldBuilderAttr,
     }
 
@@ -1082,7 +1138,7 @@ Finally, call `.build()` to create the instance of `{name}`.
         pub fn new(attrs: &[syn::Attribute]) -> Result<TypeBuilderAttr, Error> {
             let mut result = TypeBuilderAttr::default();
             for attr in attrs {
-                if path_to_single_string(&attr.path).as_deref() != Some("builder") {
+                if path_to_single_string(&attr.path).as_deref() != Some("props") {
                     continue;
                 }
 
@@ -1129,6 +1185,10 @@ Finally, call `.build()` to create the instance of `{name}`.
                             self.doc = true;
                             Ok(())
                         }
+                        "memo" => {
+                            self.memo = Some(*assign.right);
+                            Ok(())
+                        }
                         _ => Err(Error::new_spanned(
                             &assign,
                             format!("Unknown parameter {:?}", name),
@@ -1143,6 +1203,10 @@ Finally, call `.build()` to create the instance of `{name}`.
                             self.doc = true;
                             Ok(())
                         }
+                        "no_memo" => {
+                            self.no_memo = true;
+                            Ok(())
+                        }
                         _ => Err(Error::new_spanned(
                             &path,
                             format!("Unknown parameter {:?}", name),
@@ -1,4 +1,8 @@
-use std::fmt::Formatter;
+use std::{
+    any::{Any, TypeId},
+    fmt::Formatter,
+    sync::{Arc, RwLock},
+};
 
 /// Possible values for an attribute
 // trying to keep values at 3 bytes
@@ -66,17 +70,61 @@ impl<'a> std::fmt::Display for AttributeValue<'a> {
             AttributeValue::Vec4Int(_, _, _, _) => todo!(),
             AttributeValue::Vec4Uint(_, _, _, _) => todo!(),
             AttributeValue::Bytes(_) => todo!(),
-            AttributeValue::Any(_) => todo!(),
+            // values that can't be serialized have no text form
+            AttributeValue::Any(a) => match a.to_custom() {
+                Some(custom) => write!(f, "{}", custom.payload),
+                None => Ok(()),
+            },
         }
     }
 }
 
+/// An attribute value of any type, for renderers that read the VirtualDom directly.
+///
+/// If the type is registered with [`register_attribute_value`], the value can also be sent to renderers that don't,
+/// as a [`CustomValue`].
 #[derive(Clone, Copy)]
 pub struct ArbitraryAttributeValue<'a> {
+    /// The value itself
     pub value: &'a dyn std::any::Any,
+
+    /// Compares two values of the same type while diffing
     pub cmp: fn(&'a dyn std::any::Any, &'a dyn std::any::Any) -> bool,
 }
 
+impl<'a> ArbitraryAttributeValue<'a> {
+    /// Wrap a value, comparing it with its `PartialEq` implementation
+    pub fn new<T: Any + PartialEq>(value: &'a T) -> Self {
+        Self {
+            value,
+            cmp: |a, b| match (a.downcast_ref::<T>(), b.downcast_ref::<T>()) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            },
+        }
+    }
+
+    /// Get the value if it's a `T`
+    pub fn downcast<T: Any>(&self) -> Option<&'a T> {
+        self.value.downcast_ref()
+    }
+
+    /// Serialize the value, if its type is registered with [`register_attribute_value`].
+    ///
+    /// Values that are already serialized are returned as they are.
+    pub fn to_custom(&self) -> Option<CustomValue> {
+        if let Some(custom) = self.downcast::<CustomValue>() {
+            return Some(custom.clone());
+        }
+
+        let ty = CustomValueType::find(|ty| ty.type_id == self.value.type_id())?;
+        Some(CustomValue {
+            name: Arc::from(ty.name),
+            payload: Arc::from((ty.serialize)(self.value)?),
+        })
+    }
+}
+
 impl PartialEq for ArbitraryAttributeValue<'_> {
     fn eq(&self, other: &Self) -> bool {
         (self.cmp)(self.value, other.value)
@@ -91,11 +139,16 @@ impl std::fmt::Debug for ArbitraryAttributeValue<'_> {
 
 #[cfg(feature = "serialize")]
 impl<'a> serde::Serialize for ArbitraryAttributeValue<'a> {
-    fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        panic!("ArbitraryAttributeValue should not be serialized")
+        match self.to_custom() {
+            Some(custom) => custom.serialize(serializer),
+            None => Err(serde::ser::Error::custom(
+                "the attribute value's type isn't registered with `register_attribute_value`",
+            )),
+        }
     }
 }
 #[cfg(feature = "serialize")]
@@ -117,6 +170,107 @@ impl<'de, 'a> serde::Deserialize<'de> for ArbitraryAttributeValue<'a> {
     }
 }
 
+/// A type that can be used as an [`AttributeValue::Any`] and still be sent to renderers that don't read the
+/// VirtualDom directly.
+///
+/// Values are sent as a [`CustomValue`]: the type's [`NAME`](CustomAttributeValue::NAME) and a string payload.
+/// Renderers look the name up to turn the payload back into a typed value - with [`CustomValue::deserialize`] in Rust,
+/// or a decoder registered with `registerAttributeType` in `interpreter.js`. Structured values should use JSON
+/// payloads so the interpreter can parse them.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// #[derive(PartialEq, Serialize, Deserialize)]
+/// struct ClassList(Vec<String>);
+///
+/// impl CustomAttributeValue for ClassList {
+///     const NAME: &'static str = "class-list";
+///
+///     fn to_payload(&self) -> Option<String> {
+///         serde_json::to_string(self).ok()
+///     }
+///
+///     fn from_payload(payload: &str) -> Option<Self> {
+///         serde_json::from_str(payload).ok()
+///     }
+/// }
+///
+/// register_attribute_value::<ClassList>();
+/// ```
+pub trait CustomAttributeValue: Any + PartialEq + Sized {
+    /// The name renderers look the type up by
+    const NAME: &'static str;
+
+    /// Turn the value into its payload
+    fn to_payload(&self) -> Option<String>;
+
+    /// Turn a payload back into a value
+    fn from_payload(payload: &str) -> Option<Self>;
+}
+
+/// A registered [`CustomAttributeValue`], with its functions erased so values can be serialized through `dyn Any`.
+#[derive(Clone, Copy)]
+struct CustomValueType {
+    name: &'static str,
+    type_id: TypeId,
+    serialize: fn(&dyn Any) -> Option<String>,
+    deserialize: fn(&str) -> Option<Box<dyn Any>>,
+}
+
+static CUSTOM_VALUE_TYPES: RwLock<Vec<CustomValueType>> = RwLock::new(Vec::new());
+
+impl CustomValueType {
+    fn find(f: impl Fn(&CustomValueType) -> bool) -> Option<CustomValueType> {
+        let types = CUSTOM_VALUE_TYPES.read().unwrap();
+        types.iter().find(|ty| f(ty)).copied()
+    }
+}
+
+/// Register a [`CustomAttributeValue`], so [`AttributeValue::Any`]s holding it can be serialized, encoded, and
+/// converted into their owned form, and [`CustomValue`]s with its name can be deserialized.
+///
+/// Registering a type again replaces the previous registration of its name.
+pub fn register_attribute_value<T: CustomAttributeValue>() {
+    let ty = CustomValueType {
+        name: T::NAME,
+        type_id: TypeId::of::<T>(),
+        serialize: |value| value.downcast_ref::<T>()?.to_payload(),
+        deserialize: |payload| Some(Box::new(T::from_payload(payload)?)),
+    };
+
+    let mut types = CUSTOM_VALUE_TYPES.write().unwrap();
+    types.retain(|other| other.name != ty.name && other.type_id != ty.type_id);
+    types.push(ty);
+}
+
+/// A serialized [`CustomAttributeValue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomValue {
+    /// The [`NAME`](CustomAttributeValue::NAME) of the value's type
+    pub name: Arc<str>,
+
+    /// The serialized value
+    pub payload: Arc<str>,
+}
+
+impl CustomValue {
+    /// Turn the payload back into a `T`, if the value is one
+    pub fn decode<T: CustomAttributeValue>(&self) -> Option<T> {
+        match *self.name == *T::NAME {
+            true => T::from_payload(&self.payload),
+            false => None,
+        }
+    }
+
+    /// Turn the payload back into a value of whichever registered type has the value's name
+    pub fn deserialize(&self) -> Option<Box<dyn Any>> {
+        let ty = CustomValueType::find(|ty| *ty.name == *self.name)?;
+        (ty.deserialize)(&self.payload)
+    }
+}
+
 // todo
 #[allow(missing_docs)]
 impl<'a> AttributeValue<'a> {
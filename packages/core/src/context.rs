@@ -0,0 +1,382 @@
+//! Contexts share values with the components beneath the one that provides them, without drilling props.
+//!
+//! A context is identified by its type and, optionally, a [`ContextKey`], so one component can provide several values
+//! of the same type. Lookups start at the consuming component and walk up its parents until they find a component that
+//! provides a matching context. That means a component can shadow a context provided by one of its ancestors: the new
+//! value is seen by the component and everything beneath it, while the rest of the tree keeps seeing the old one.
+//!
+//! Consuming a context from another component subscribes the consumer to it. When the provider replaces the value with
+//! [`ScopeState::update_context`], every subscriber is re-rendered. Subscriptions only last until the next update, so
+//! components that want to see new values should consume the context while rendering instead of once in a hook.
+
+use crate::innerlude::*;
+use std::{
+    any::{Any, TypeId},
+    collections::HashSet,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+/// A name for a context, so a component can provide several values of the same type.
+///
+/// Keys are compared by their name and type: two keys with the same name and type refer to the same context.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// const PRIMARY: ContextKey<Color> = ContextKey::new("primary");
+/// const SECONDARY: ContextKey<Color> = ContextKey::new("secondary");
+///
+/// fn App(cx: Scope) -> Element {
+///     cx.use_hook(|_| {
+///         PRIMARY.provide(&cx, Color::Red);
+///         SECONDARY.provide(&cx, Color::Blue);
+///     });
+///     cx.render(rsx!(Button {}))
+/// }
+///
+/// fn Button(cx: Scope) -> Element {
+///     let color = PRIMARY.consume(&cx)?;
+///     cx.render(rsx!(button { color: "{color}", "click me" }))
+/// }
+/// ```
+pub struct ContextKey<T> {
+    name: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ContextKey<T> {
+    /// Create a key for a context of type `T`
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The name of the key
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T: 'static + Clone> ContextKey<T> {
+    /// Provide a value for this key to the scope and its descendants.
+    ///
+    /// See [`ScopeState::provide_context`].
+    pub fn provide(&self, cx: &ScopeState, value: T) -> T {
+        cx.provide_context_with_id(self.id(), value)
+    }
+
+    /// Replace the value the scope provides for this key, re-rendering every component that consumed it.
+    ///
+    /// See [`ScopeState::update_context`].
+    pub fn update(&self, cx: &ScopeState, value: T) {
+        cx.update_context_with_id(self.id(), value)
+    }
+
+    /// Get the value for this key from the closest scope that provides it.
+    ///
+    /// See [`ScopeState::consume_context`].
+    pub fn consume(&self, cx: &ScopeState) -> Option<T> {
+        cx.consume_context_with_id(self.id())
+    }
+
+    /// Get the value for this key, or an error that can be thrown to an [`ErrorBoundary`] if no scope provides it.
+    pub fn try_consume(&self, cx: &ScopeState) -> Result<T, MissingContext> {
+        self.consume(cx).ok_or(MissingContext {
+            type_name: std::any::type_name::<T>(),
+            key: Some(self.name),
+        })
+    }
+
+    /// Get the value for this key, or the default value of `T` if no scope provides it.
+    pub fn consume_or_default(&self, cx: &ScopeState) -> T
+    where
+        T: Default,
+    {
+        self.consume(cx).unwrap_or_default()
+    }
+
+    fn id(&self) -> ContextId {
+        ContextId::of::<T>(Some(self.name))
+    }
+}
+
+impl<T> Clone for ContextKey<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ContextKey<T> {}
+
+impl<T> Debug for ContextKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextKey")
+            .field("name", &self.name)
+            .field("type", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+/// The error returned when no scope provides a context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingContext {
+    /// The name of the type of the context
+    pub type_name: &'static str,
+
+    /// The name of the context's key, if it has one
+    pub key: Option<&'static str>,
+}
+
+impl Display for MissingContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.key {
+            Some(key) => write!(
+                f,
+                "no context of type {} was provided for the key {:?}",
+                self.type_name, key
+            ),
+            None => write!(f, "no context of type {} was provided", self.type_name),
+        }
+    }
+}
+
+impl std::error::Error for MissingContext {}
+
+/// Identifies a context in the scope that provides it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct ContextId {
+    type_id: TypeId,
+    name: Option<&'static str>,
+}
+
+impl ContextId {
+    pub(crate) fn of<T: 'static>(name: Option<&'static str>) -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name,
+        }
+    }
+}
+
+/// A value provided by a scope, and the scopes that consumed it since it was last updated
+pub(crate) struct ProvidedContext {
+    value: Box<dyn Any>,
+    subscribers: HashSet<ScopeId>,
+}
+
+impl ProvidedContext {
+    fn get<T: 'static + Clone>(&self) -> T {
+        self.value.downcast_ref::<T>().unwrap().clone()
+    }
+}
+
+impl ScopeState {
+    /// This method enables the ability to expose state to children further down the VirtualDOM Tree.
+    ///
+    /// This is a "fundamental" operation and should only be called during initialization of a hook.
+    ///
+    /// For a hook that provides the same functionality, use `use_provide_context` and `use_consume_context` instead.
+    ///
+    /// The context shadows any context of the same type provided by this scope's ancestors, for this scope and its
+    /// descendants. Providing a context again replaces it without re-rendering the scopes that consumed it - use
+    /// [`ScopeState::update_context`] for that. To provide several values of the same type, use a [`ContextKey`].
+    ///
+    /// When the component is dropped, so is the context. Be aware of this behavior when consuming
+    /// the context via Rc/Weak.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// struct SharedState(&'static str);
+    ///
+    /// static App: Component = |cx| {
+    ///     cx.use_hook(|_| cx.provide_context(SharedState("world")));
+    ///     rsx!(cx, Child {})
+    /// }
+    ///
+    /// static Child: Component = |cx| {
+    ///     let state = cx.consume_state::<SharedState>();
+    ///     rsx!(cx, div { "hello {state.0}" })
+    /// }
+    /// ```
+    pub fn provide_context<T: 'static + Clone>(&self, value: T) -> T {
+        self.provide_context_with_id(ContextId::of::<T>(None), value)
+    }
+
+    /// Provide a context for the root component from anywhere in your app.
+    ///
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// struct SharedState(&'static str);
+    ///
+    /// static App: Component = |cx| {
+    ///     cx.use_hook(|_| cx.provide_root_context(SharedState("world")));
+    ///     rsx!(cx, Child {})
+    /// }
+    ///
+    /// static Child: Component = |cx| {
+    ///     let state = cx.consume_state::<SharedState>();
+    ///     rsx!(cx, div { "hello {state.0}" })
+    /// }
+    /// ```
+    pub fn provide_root_context<T: 'static + Clone>(&self, value: T) -> T {
+        // if we *are* the root component, then we can just provide the context directly
+        if self.scope_id() == ScopeId(0) {
+            return self.provide_context(value);
+        }
+
+        let mut search_parent = self.parent_scope;
+
+        while let Some(parent) = search_parent.take() {
+            // safety: scopes live in the arena's bump allocator, and a parent is only removed after its children, so
+            // every ancestor of this scope is still alive
+            let parent = unsafe { &*parent };
+
+            if parent.scope_id() == ScopeId(0) {
+                let exists = parent
+                    .shared_contexts
+                    .borrow()
+                    .contains_key(&ContextId::of::<T>(None));
+
+                if exists {
+                    log::warn!("Context already provided to parent scope - replacing it");
+                }
+                return parent.provide_context(value);
+            }
+
+            search_parent = parent.parent_scope;
+        }
+
+        unreachable!("all apps have a root scope")
+    }
+
+    /// Replace a context this scope provides and re-render every scope that consumed it.
+    ///
+    /// Provides the context if the scope didn't already. Unlike [`ScopeState::provide_context`], this is meant to be
+    /// called after the context was provided, ie from an event handler.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// fn App(cx: Scope) -> Element {
+    ///     cx.use_hook(|_| cx.provide_context(Theme::Light));
+    ///
+    ///     cx.render(rsx!(
+    ///         button { onclick: move |_| cx.update_context(Theme::Dark), "dark mode" }
+    ///         Page {}
+    ///     ))
+    /// }
+    /// ```
+    pub fn update_context<T: 'static + Clone>(&self, value: T) {
+        self.update_context_with_id(ContextId::of::<T>(None), value)
+    }
+
+    /// Try to retrieve a SharedState with type T from this scope or any parent Scope.
+    ///
+    /// The closest scope that provides the context wins. Consuming a context provided by another scope subscribes
+    /// this scope to its updates, see [`ScopeState::update_context`].
+    pub fn consume_context<T: 'static + Clone>(&self) -> Option<T> {
+        self.consume_context_with_id(ContextId::of::<T>(None))
+    }
+
+    /// Retrieve a context, or an error that can be thrown to an [`ErrorBoundary`] if no scope provides it.
+    ///
+    /// ```rust, ignore
+    /// fn Child(cx: Scope) -> Element {
+    ///     let theme = cx.try_consume_context::<Theme>().throw(&cx)?;
+    ///     // ...
+    /// }
+    /// ```
+    pub fn try_consume_context<T: 'static + Clone>(&self) -> Result<T, MissingContext> {
+        self.consume_context().ok_or(MissingContext {
+            type_name: std::any::type_name::<T>(),
+            key: None,
+        })
+    }
+
+    /// Retrieve a context, or the default value of `T` if no scope provides it.
+    pub fn consume_context_or_default<T: 'static + Clone + Default>(&self) -> T {
+        self.consume_context().unwrap_or_default()
+    }
+
+    /// Get a context this scope provides itself, without subscribing to it
+    pub(crate) fn own_context<T: 'static + Clone>(&self) -> Option<T> {
+        self.shared_contexts
+            .borrow()
+            .get(&ContextId::of::<T>(None))
+            .map(ProvidedContext::get)
+    }
+
+    /// Get a context from the closest ancestor of this scope that provides it, without subscribing to it
+    pub(crate) fn parent_context<T: 'static + Clone>(&self) -> Option<T> {
+        let id = ContextId::of::<T>(None);
+        let mut search_parent = self.parent_scope;
+
+        while let Some(parent_ptr) = search_parent {
+            // safety: all parent pointers are valid thanks to the bump arena
+            let parent = unsafe { &*parent_ptr };
+            if let Some(provided) = parent.shared_contexts.borrow().get(&id) {
+                return Some(provided.get());
+            }
+            search_parent = parent.parent_scope;
+        }
+
+        None
+    }
+
+    pub(crate) fn provide_context_with_id<T: 'static + Clone>(&self, id: ContextId, value: T) -> T {
+        let mut contexts = self.shared_contexts.borrow_mut();
+        match contexts.get_mut(&id) {
+            // keep the subscribers of the value being replaced
+            Some(provided) => provided.value = Box::new(value.clone()),
+            None => {
+                contexts.insert(
+                    id,
+                    ProvidedContext {
+                        value: Box::new(value.clone()),
+                        subscribers: HashSet::new(),
+                    },
+                );
+            }
+        }
+        value
+    }
+
+    pub(crate) fn update_context_with_id<T: 'static + Clone>(&self, id: ContextId, value: T) {
+        self.provide_context_with_id(id, value);
+
+        let subscribers = match self.shared_contexts.borrow_mut().get_mut(&id) {
+            Some(provided) => std::mem::take(&mut provided.subscribers),
+            None => return,
+        };
+
+        for subscriber in subscribers {
+            self.needs_update_any(subscriber);
+        }
+    }
+
+    pub(crate) fn consume_context_with_id<T: 'static + Clone>(&self, id: ContextId) -> Option<T> {
+        if let Some(provided) = self.shared_contexts.borrow().get(&id) {
+            return Some(provided.get());
+        }
+
+        let mut search_parent = self.parent_scope;
+
+        while let Some(parent_ptr) = search_parent {
+            // safety: all parent pointers are valid thanks to the bump arena
+            let parent = unsafe { &*parent_ptr };
+            if let Some(provided) = parent.shared_contexts.borrow_mut().get_mut(&id) {
+                provided.subscribers.insert(self.scope_id());
+                return Some(provided.get());
+            }
+            search_parent = parent.parent_scope;
+        }
+
+        None
+    }
+}
@@ -92,21 +92,56 @@
 //!  - <https://hacks.mozilla.org/2019/03/fast-bump-allocated-virtual-doms-with-rust-and-wasm/>
 
 use crate::innerlude::{
-    AnyProps, ElementId, Mutations, ScopeArena, ScopeId, VComponent, VElement, VFragment, VNode,
-    VPlaceholder, VText,
+    AnyProps, ComponentPtr, ElementId, Mutations, ScopeArena, ScopeId, ScopeState,
+    SuspenseBoundary, SuspenseStatus, VComponent, VComponentProps, VElement, VFragment, VNode,
+    VPlaceholder, VPortal, VTemplate, VText,
 };
 use fxhash::{FxHashMap, FxHashSet};
 use smallvec::{smallvec, SmallVec};
 
-pub(crate) struct DiffState<'bump> {
+pub(crate) struct DiffState<'bump, 'deadline> {
     pub(crate) scopes: &'bump ScopeArena,
     pub(crate) mutations: Mutations<'bump>,
     pub(crate) force_diff: bool,
     pub(crate) element_stack: SmallVec<[ElementId; 10]>,
     pub(crate) scope_stack: SmallVec<[ScopeId; 5]>,
+    pub(crate) saved_templates: Vec<usize>,
+
+    /// The deadline of the current call to `work_with_deadline` (if any).
+    pub(crate) deadline: Option<&'deadline mut dyn FnMut() -> bool>,
+    pub(crate) out_of_time: bool,
+
+    /// Scopes to render next: child components whose props changed but that were left unrendered because the deadline
+    /// passed, and error boundaries that caught an error from a scope that was rendered.
+    pub(crate) deferred: Vec<ScopeId>,
+
+    /// Siblings left undiffed because the deadline passed partway through a scope. The scope's edits are held back
+    /// until all of them have been diffed.
+    frames: Vec<DiffFrame<'bump>>,
+
+    /// While above zero, the diff can't stop partway: the edits that follow depend on every new node being mounted.
+    pinned: usize,
+
+    /// The pairs of siblings diffed so far. The diff only stops once there's at least one, so every call makes progress.
+    diffed_siblings: usize,
+}
+
+/// Siblings that are still waiting to be diffed, along with the element and scope they belong to.
+struct DiffFrame<'b> {
+    old: &'b [VNode<'b>],
+    new: &'b [VNode<'b>],
+    element_stack: SmallVec<[ElementId; 10]>,
+    scope_stack: SmallVec<[ScopeId; 5]>,
 }
 
-impl<'b> DiffState<'b> {
+/// The diff of a scope that ran out of time, with the edits it has made so far and the siblings it has left.
+pub(crate) struct PausedDiff<'b> {
+    pub(crate) scope: ScopeId,
+    mutations: Mutations<'b>,
+    frames: Vec<DiffFrame<'b>>,
+}
+
+impl<'b> DiffState<'b, '_> {
     pub fn new(scopes: &'b ScopeArena) -> Self {
         Self {
             scopes,
@@ -114,9 +149,103 @@ impl<'b> DiffState<'b> {
             force_diff: false,
             element_stack: smallvec![],
             scope_stack: smallvec![],
+            saved_templates: Vec::new(),
+            deadline: None,
+            out_of_time: false,
+            deferred: Vec::new(),
+            frames: Vec::new(),
+            pinned: 0,
+            diffed_siblings: 0,
         }
     }
 
+    /// Check if the deadline has been reached. Once it has, it stays reached for the rest of the diff.
+    fn out_of_time(&mut self) -> bool {
+        if !self.out_of_time {
+            if let Some(deadline) = self.deadline.as_mut() {
+                self.out_of_time = deadline();
+            }
+        }
+        self.out_of_time
+    }
+
+    /// Check if the deadline has passed and the siblings from this pair on should be left for the next call.
+    ///
+    /// Child components with static props are still diffed, since they're put off as their own unit of work.
+    fn should_pause(&mut self, old: &'b VNode<'b>, new: &'b VNode<'b>) -> bool {
+        let deferrable = match (old, new) {
+            (VNode::Component(old), VNode::Component(new)) => {
+                old.user_fc == new.user_fc && old.can_memoize && !self.force_diff
+            }
+            _ => false,
+        };
+
+        self.pinned == 0 && self.diffed_siblings > 0 && !deferrable && self.out_of_time()
+    }
+
+    /// Diff pairs of siblings, saving the ones that are left if the deadline passes.
+    fn diff_siblings(&mut self, old: &'b [VNode<'b>], new: &'b [VNode<'b>]) {
+        for (idx, (old_node, new_node)) in old.iter().zip(new.iter()).enumerate() {
+            if self.should_pause(old_node, new_node) {
+                self.save_frame(&old[idx..], &new[idx..]);
+                return;
+            }
+            self.diff_node(old_node, new_node);
+            self.diffed_siblings += 1;
+        }
+    }
+
+    fn save_frame(&mut self, old: &'b [VNode<'b>], new: &'b [VNode<'b>]) {
+        self.frames.push(DiffFrame {
+            old,
+            new,
+            element_stack: self.element_stack.clone(),
+            scope_stack: self.scope_stack.clone(),
+        });
+    }
+
+    /// Check if the diff stopped partway through a scope and has siblings left to diff.
+    pub(crate) fn is_paused(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// Hold onto the edits of a scope that ran out of time, along with the siblings it has left.
+    pub(crate) fn pause(self, scope: ScopeId) -> PausedDiff<'b> {
+        PausedDiff {
+            scope,
+            mutations: self.mutations,
+            frames: self.frames,
+        }
+    }
+
+    /// Pick a paused diff back up, diffing the siblings it has left until they're done or the deadline passes again.
+    pub(crate) fn resume(&mut self, paused: PausedDiff<'b>) {
+        let PausedDiff {
+            scope,
+            mutations,
+            mut frames,
+        } = paused;
+        self.mutations = mutations;
+
+        self.profile_diff(scope, |state| {
+            while let Some(frame) = frames.pop() {
+                state.element_stack = frame.element_stack;
+                state.scope_stack = frame.scope_stack;
+                state.diff_siblings(frame.old, frame.new);
+
+                if state.is_paused() {
+                    // the siblings saved just now are the first to be picked up next time
+                    frames.append(&mut state.frames);
+                    state.frames = frames;
+                    break;
+                }
+            }
+        });
+
+        self.element_stack.clear();
+        self.scope_stack.clear();
+    }
+
     pub fn diff_scope(&mut self, scopeid: ScopeId) {
         let (old, new) = (self.scopes.wip_head(scopeid), self.scopes.fin_head(scopeid));
         let scope = self.scopes.get_scope(scopeid).unwrap();
@@ -124,7 +253,7 @@ impl<'b> DiffState<'b> {
         self.scope_stack.push(scopeid);
         self.element_stack.push(scope.container);
         {
-            self.diff_node(old, new);
+            self.profile_diff(scopeid, |state| state.diff_node(old, new));
         }
         self.element_stack.pop();
         self.scope_stack.pop();
@@ -133,7 +262,7 @@ impl<'b> DiffState<'b> {
     }
 
     pub fn diff_node(&mut self, old_node: &'b VNode<'b>, new_node: &'b VNode<'b>) {
-        use VNode::{Component, Element, Fragment, Placeholder, Text};
+        use VNode::{Component, Element, Fragment, Placeholder, Portal, Template, Text};
         match (old_node, new_node) {
             (Text(old), Text(new)) => {
                 self.diff_text_nodes(old, new, old_node, new_node);
@@ -155,9 +284,19 @@ impl<'b> DiffState<'b> {
                 self.diff_fragment_nodes(old, new);
             }
 
+            (Template(old), Template(new)) => {
+                self.diff_template_nodes(old, new, old_node, new_node);
+            }
+
+            (Portal(old), Portal(new)) => {
+                self.diff_portal_nodes(old, new, old_node, new_node);
+            }
+
             (
-                Component(_) | Fragment(_) | Text(_) | Element(_) | Placeholder(_),
-                Component(_) | Fragment(_) | Text(_) | Element(_) | Placeholder(_),
+                Component(_) | Fragment(_) | Text(_) | Element(_) | Placeholder(_) | Template(_)
+                | Portal(_),
+                Component(_) | Fragment(_) | Text(_) | Element(_) | Placeholder(_) | Template(_)
+                | Portal(_),
             ) => self.replace_node(old_node, new_node),
         }
     }
@@ -169,6 +308,8 @@ impl<'b> DiffState<'b> {
             VNode::Element(element) => self.create_element_node(element, node),
             VNode::Fragment(frag) => self.create_fragment_node(frag),
             VNode::Component(component) => self.create_component_node(*component),
+            VNode::Template(template) => self.create_template_node(template, node),
+            VNode::Portal(portal) => self.create_portal_node(portal, node),
         }
     }
 
@@ -204,6 +345,10 @@ impl<'b> DiffState<'b> {
 
         dom_id.set(Some(real_id));
 
+        if let Some(node_ref) = element.node_ref {
+            self.mutations.node_ref(node_ref, real_id);
+        }
+
         self.element_stack.push(real_id);
         {
             self.mutations.create_element(tag_name, *namespace, real_id);
@@ -228,6 +373,61 @@ impl<'b> DiffState<'b> {
         1
     }
 
+    fn create_template_node(&mut self, template: &'b VTemplate, node: &'b VNode<'b>) -> usize {
+        template.parent.set(self.element_stack.last().copied());
+
+        let real_id = self.scopes.reserve_node(node);
+        template.id.set(Some(real_id));
+        self.clone_template(template, real_id);
+
+        1
+    }
+
+    /// Clone a template into the renderer, sending the template first if the renderer doesn't have it yet.
+    fn clone_template(&mut self, template: &'b VTemplate, root: ElementId) {
+        let scope = self.scopes.get_scope(self.current_scope()).unwrap();
+        let (id, tree, saved) = self.scopes.templates.get(template, scope);
+
+        if !saved {
+            // the definition is built with throwaway IDs that are released as soon as it's saved
+            self.create_node(tree);
+            self.mutations.save_template(id);
+            self.remove_nodes([tree], false);
+
+            self.scopes.templates.set_saved(template.key(), true);
+            self.saved_templates.push(template.key());
+        }
+
+        self.mutations.clone_template(id, root);
+    }
+
+    /// Forget about templates saved after the given point because the edits that saved them were thrown away.
+    fn forget_saved_templates(&mut self, since: usize) {
+        for key in self.saved_templates.drain(since..) {
+            self.scopes.templates.set_saved(key, false);
+        }
+    }
+
+    /// Portals leave a placeholder in place and append their children to the named root.
+    fn create_portal_node(&mut self, portal: &'b VPortal<'b>, node: &'b VNode<'b>) -> usize {
+        portal.parent.set(self.element_stack.last().copied());
+
+        let real_id = self.scopes.reserve_node(node);
+        portal.id.set(Some(real_id));
+        self.mutations.create_placeholder(real_id);
+
+        // the children bubble their events up through the portal, not the named root
+        self.element_stack.push(real_id);
+        {
+            self.mutations.push_named_root(portal.target);
+            self.create_and_append_children(portal.children);
+            self.mutations.pop_root();
+        }
+        self.element_stack.pop();
+
+        1
+    }
+
     fn create_fragment_node(&mut self, frag: &'b VFragment<'b>) -> usize {
         self.create_children(frag.children)
     }
@@ -271,6 +471,12 @@ impl<'b> DiffState<'b> {
 
         self.enter_scope(new_idx);
 
+        #[cfg(feature = "profile")]
+        self.scopes
+            .profiler
+            .borrow_mut()
+            .mark_mounted(new_idx, Some(vcomponent.fn_name));
+
         let created = {
             // Run the scope for one iteration to initialize it
             self.scopes.run_scope(new_idx);
@@ -278,7 +484,19 @@ impl<'b> DiffState<'b> {
 
             // Take the node that was just generated from running the component
             let nextnode = self.scopes.fin_head(new_idx);
-            self.create_node(nextnode)
+
+            self.profile_diff(new_idx, |state| {
+                match state
+                    .scopes
+                    .get_scope(new_idx)
+                    .and_then(ScopeState::own_suspense_boundary)
+                {
+                    Some(boundary) if !boundary.is_resolved() => {
+                        state.create_suspense_boundary(&boundary, nextnode)
+                    }
+                    _ => state.create_node(nextnode),
+                }
+            })
         };
 
         self.leave_scope();
@@ -286,6 +504,230 @@ impl<'b> DiffState<'b> {
         created
     }
 
+    /// Create the subtree of a suspense boundary, holding its edits back if it spawned any tasks.
+    pub(crate) fn create_suspense_boundary(
+        &mut self,
+        boundary: &SuspenseBoundary,
+        node: &'b VNode<'b>,
+    ) -> usize {
+        // Build the subtree into a side buffer - any tasks it spawns are collected by the boundary
+        let edits = std::mem::take(&mut self.mutations.edits);
+        let (refs, saved_templates) = (self.mutations.refs.len(), self.saved_templates.len());
+        let created = self.create_node(node);
+        let subtree_edits = std::mem::replace(&mut self.mutations.edits, edits);
+
+        if boundary.pending_tasks() == 0 {
+            boundary.status.set(SuspenseStatus::Resolved);
+            self.mutations.edits.extend(subtree_edits);
+            return created;
+        }
+
+        boundary.status.set(SuspenseStatus::Suspended);
+        self.mutations.refs.truncate(refs);
+        self.forget_saved_templates(saved_templates);
+
+        let fallback = self.scopes.new_with_key(
+            boundary.fallback as ComponentPtr,
+            Box::new(VComponentProps {
+                props: (),
+                memo: |_a, _b| unreachable!("fallbacks are never memoized"),
+                render_fn: boundary.fallback,
+            }),
+            Some(boundary.scope_id()),
+            self.element_stack.last().copied().unwrap(),
+            0,
+        );
+        boundary.fallback_scope.set(Some(fallback));
+
+        #[cfg(feature = "profile")]
+        self.scopes
+            .profiler
+            .borrow_mut()
+            .mark_mounted(fallback, None);
+
+        self.enter_scope(fallback);
+        self.scopes.run_scope(fallback);
+        self.mutations.mark_dirty_scope(fallback);
+        let created = self.profile_diff(fallback, |state| {
+            state.create_node(state.scopes.fin_head(fallback))
+        });
+        self.leave_scope();
+
+        created
+    }
+
+    /// Swap a boundary's fallback out for the subtree it was holding back.
+    pub(crate) fn resolve_suspense(&mut self, boundary: &SuspenseBoundary) {
+        let scope_id = boundary.scope_id();
+        let fallback = boundary
+            .fallback_scope
+            .take()
+            .expect("suspended boundaries should have a fallback");
+
+        boundary.status.set(SuspenseStatus::Resolved);
+
+        // a boundary nested in another suspended boundary only updates its own bookkeeping
+        let outer_edits = self
+            .scopes
+            .is_suspended(scope_id)
+            .then(|| std::mem::take(&mut self.mutations.edits));
+        let (refs, saved_templates) = (self.mutations.refs.len(), self.saved_templates.len());
+
+        let scope = self.scopes.get_scope(scope_id).unwrap();
+        self.scope_stack.push(scope_id);
+        self.element_stack.push(scope.container);
+        {
+            let created = self.recreate_node(self.scopes.root_node(scope_id));
+
+            let fallback_root = self.scopes.root_node(fallback);
+            let anchor = self.find_first_element(fallback_root).unwrap();
+            self.mutations.insert_before(anchor, created as u32);
+
+            self.enter_scope(fallback);
+            self.remove_nodes([fallback_root], true);
+            self.leave_scope();
+            self.scopes.try_remove(fallback).unwrap();
+        }
+        self.element_stack.pop();
+        self.scope_stack.pop();
+
+        if let Some(edits) = outer_edits {
+            self.mutations.edits = edits;
+            self.mutations.refs.truncate(refs);
+            self.forget_saved_templates(saved_templates);
+        }
+    }
+
+    /// Emit the edits to create a tree that has already been mounted, reusing its existing IDs.
+    fn recreate_node(&mut self, node: &'b VNode<'b>) -> usize {
+        match node {
+            VNode::Text(t) => {
+                self.mutations.create_text_node(t.text, node.mounted_id());
+                1
+            }
+            VNode::Placeholder(_) => {
+                self.mutations.create_placeholder(node.mounted_id());
+                1
+            }
+            VNode::Template(t) => {
+                self.clone_template(t, node.mounted_id());
+                1
+            }
+            VNode::Element(el) => {
+                let id = node.mounted_id();
+                self.mutations.create_element(el.tag, el.namespace, id);
+
+                // the renderer creates a new element, so any handle it provided before is stale
+                if let Some(node_ref) = el.node_ref {
+                    self.mutations.node_ref(node_ref, id);
+                }
+
+                let cur_scope_id = self.current_scope();
+                for listener in el.listeners {
+                    self.mutations.new_event_listener(listener, cur_scope_id);
+                }
+
+                for attr in el.attributes {
+                    self.mutations.set_attribute(attr, id.as_u64());
+                }
+
+                if !el.children.is_empty() {
+                    let created = el
+                        .children
+                        .iter()
+                        .map(|child| self.recreate_node(child))
+                        .sum::<usize>();
+                    self.mutations.append_children(created as u32);
+                }
+
+                1
+            }
+            VNode::Fragment(frag) => frag
+                .children
+                .iter()
+                .map(|child| self.recreate_node(child))
+                .sum(),
+            VNode::Portal(portal) => {
+                self.mutations.create_placeholder(node.mounted_id());
+
+                self.mutations.push_named_root(portal.target);
+                let created = portal
+                    .children
+                    .iter()
+                    .map(|child| self.recreate_node(child))
+                    .sum::<usize>();
+                self.mutations.append_children(created as u32);
+                self.mutations.pop_root();
+
+                1
+            }
+            VNode::Component(c) => {
+                let scope_id = c.scope.get().unwrap();
+                let mounted = self
+                    .scopes
+                    .get_scope(scope_id)
+                    .and_then(ScopeState::suspense_fallback)
+                    .unwrap_or(scope_id);
+
+                self.enter_scope(mounted);
+                let created = self.recreate_node(self.scopes.root_node(mounted));
+                self.leave_scope();
+                created
+            }
+        }
+    }
+
+    /// Run the diff of a scope's output, recording how long it took and how many edits it made when profiling.
+    #[cfg_attr(not(feature = "profile"), allow(unused_variables))]
+    pub(crate) fn profile_diff<R>(&mut self, id: ScopeId, f: impl FnOnce(&mut Self) -> R) -> R {
+        #[cfg(feature = "profile")]
+        let (start, edits) = (std::time::Instant::now(), self.mutations.edits.len());
+
+        let out = f(self);
+
+        #[cfg(feature = "profile")]
+        self.scopes.profiler.borrow_mut().record_diff(
+            id,
+            start,
+            self.mutations.edits.len().saturating_sub(edits),
+        );
+
+        out
+    }
+
+    /// Run a diffing operation without sending any of its edits to the renderer.
+    pub(crate) fn muted<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let edits = std::mem::take(&mut self.mutations.edits);
+        let (refs, saved_templates) = (self.mutations.refs.len(), self.saved_templates.len());
+
+        // the edits are thrown away, so the diff can't stop partway through them
+        self.pinned += 1;
+        let out = f(self);
+        self.pinned -= 1;
+
+        self.mutations.edits = edits;
+        self.mutations.refs.truncate(refs);
+        self.forget_saved_templates(saved_templates);
+        out
+    }
+
+    /// Remove the fallback a suspended boundary is showing, if any, and report if it was removed.
+    fn remove_suspense_fallback(&mut self, scope_id: ScopeId, gen_muts: bool) -> bool {
+        let fallback = self
+            .scopes
+            .get_scope(scope_id)
+            .and_then(ScopeState::suspense_fallback);
+
+        if let Some(fallback) = fallback {
+            self.enter_scope(fallback);
+            self.remove_nodes([self.scopes.root_node(fallback)], gen_muts);
+            self.leave_scope();
+            self.scopes.try_remove(fallback).unwrap();
+        }
+
+        fallback.is_some()
+    }
+
     pub(crate) fn diff_text_nodes(
         &mut self,
         old: &'b VText<'b>,
@@ -333,6 +775,56 @@ impl<'b> DiffState<'b> {
         new.id.set(Some(root));
     }
 
+    fn diff_template_nodes(
+        &mut self,
+        old: &'b VTemplate,
+        new: &'b VTemplate,
+        old_node: &'b VNode<'b>,
+        new_node: &'b VNode<'b>,
+    ) {
+        if std::ptr::eq(old, new) {
+            return;
+        }
+
+        // templates are static, so only a different template needs new edits
+        if old.key() != new.key() {
+            self.replace_node(old_node, new_node);
+            return;
+        }
+
+        let root = old.id.get().unwrap();
+        new.id.set(Some(root));
+        new.parent.set(old.parent.get());
+        self.scopes.update_node(new_node, root);
+    }
+
+    fn diff_portal_nodes(
+        &mut self,
+        old: &'b VPortal<'b>,
+        new: &'b VPortal<'b>,
+        old_node: &'b VNode<'b>,
+        new_node: &'b VNode<'b>,
+    ) {
+        if std::ptr::eq(old, new) {
+            return;
+        }
+
+        // the children can't be moved between mount points, so a new target needs a new portal
+        if old.target != new.target {
+            self.replace_node(old_node, new_node);
+            return;
+        }
+
+        let root = old.id.get().unwrap();
+        new.id.set(Some(root));
+        new.parent.set(old.parent.get());
+        self.scopes.update_node(new_node, root);
+
+        self.element_stack.push(root);
+        self.diff_children(old.children, new.children);
+        self.element_stack.pop();
+    }
+
     fn diff_element_nodes(
         &mut self,
         old: &'b VElement<'b>,
@@ -364,6 +856,19 @@ impl<'b> DiffState<'b> {
         new.id.set(Some(root));
         new.parent.set(old.parent.get());
 
+        // a ref that stays attached keeps the handle it already has
+        match (old.node_ref, new.node_ref) {
+            (Some(old_ref), Some(new_ref)) if old_ref.ptr_eq(new_ref) => {}
+            (old_ref, new_ref) => {
+                if let Some(old_ref) = old_ref {
+                    old_ref.unmount(root);
+                }
+                if let Some(new_ref) = new_ref {
+                    self.mutations.node_ref(new_ref, root);
+                }
+            }
+        }
+
         // todo: attributes currently rely on the element on top of the stack, but in theory, we only need the id of the
         // element to modify its attributes.
         // it would result in fewer instructions if we just set the id directly.
@@ -487,17 +992,54 @@ impl<'b> DiffState<'b> {
                         .props
                         .replace(unsafe { std::mem::transmute(Some(new_props)) });
 
-                    // this should auto drop the previous props
-                    self.scopes.run_scope(scope_addr);
-                    self.mutations.mark_dirty_scope(scope_addr);
+                    #[cfg(feature = "profile")]
+                    if let Some(parent) = scope.parent() {
+                        self.scopes
+                            .profiler
+                            .borrow_mut()
+                            .mark_dirty(scope_addr, crate::DirtyReason::ParentRender(parent));
+                    }
 
-                    self.diff_node(
-                        self.scopes.wip_head(scope_addr),
-                        self.scopes.fin_head(scope_addr),
-                    );
+                    // Out of time: the child's current nodes stay mounted and it's rendered as its own unit of
+                    // work on the next call to `work_with_deadline`. Only children with static props can wait,
+                    // since borrowed props don't outlive this render of the parent.
+                    if old.can_memoize && !self.force_diff && self.out_of_time() {
+                        self.deferred.push(scope_addr);
+                    } else {
+                        // this should auto drop the previous props
+                        let rendered = self.scopes.run_scope(scope_addr);
+                        self.mutations.mark_dirty_scope(scope_addr);
+
+                        if rendered {
+                            let (old, new) = (
+                                self.scopes.wip_head(scope_addr),
+                                self.scopes.fin_head(scope_addr),
+                            );
+
+                            // suspended subtrees are recreated from scratch when they resolve
+                            self.profile_diff(scope_addr, |state| {
+                                match scope.own_suspense_boundary() {
+                                    Some(boundary) if !boundary.is_resolved() => {
+                                        state.muted(|state| state.diff_node(old, new));
+                                    }
+                                    _ => state.diff_node(old, new),
+                                }
+                            });
+                        } else {
+                            // a failed child keeps its nodes until the boundary that caught the error replaces them
+                            let boundary = scope.parent_error_boundary();
+                            self.deferred.extend(boundary.map(|b| b.scope_id()));
+                        }
+                    }
                 } else {
                     // memoization has taken place
                     drop(new_props);
+
+                    #[cfg(feature = "profile")]
+                    self.scopes
+                        .profiler
+                        .borrow_mut()
+                        .record_memoized(scope_addr);
                 };
             }
             self.leave_scope();
@@ -594,9 +1136,7 @@ impl<'b> DiffState<'b> {
             Ordering::Equal => {}
         }
 
-        for (new, old) in new.iter().zip(old.iter()) {
-            self.diff_node(old, new);
-        }
+        self.diff_siblings(old, new);
     }
 
     // Diffing "keyed" children.
@@ -680,7 +1220,10 @@ impl<'b> DiffState<'b> {
                 self.create_and_insert_after(new_middle, foothold);
             }
         } else {
+            // moving the nodes in the middle around needs all of them mounted
+            self.pinned += 1;
             self.diff_keyed_middle(old_middle, new_middle);
+            self.pinned -= 1;
         }
     }
 
@@ -694,16 +1237,15 @@ impl<'b> DiffState<'b> {
         old: &'b [VNode<'b>],
         new: &'b [VNode<'b>],
     ) -> Option<(usize, usize)> {
-        let mut left_offset = 0;
+        // abort early if we finally run into nodes with different keys
+        let left_offset = old
+            .iter()
+            .zip(new.iter())
+            .take_while(|(old, new)| old.key() == new.key())
+            .count();
 
-        for (old, new) in old.iter().zip(new.iter()) {
-            // abort early if we finally run into nodes with different keys
-            if old.key() != new.key() {
-                break;
-            }
-            self.diff_node(old, new);
-            left_offset += 1;
-        }
+        // the ends are never looked at again, so whatever's left of them can wait for the next call
+        self.diff_siblings(&old[..left_offset], &new[..left_offset]);
 
         // If that was all of the old children, then create and append the remaining
         // new children and we're finished.
@@ -720,14 +1262,22 @@ impl<'b> DiffState<'b> {
         }
 
         // if the shared prefix is less than either length, then we need to walk backwards
-        let mut right_offset = 0;
-        for (old, new) in old.iter().rev().zip(new.iter().rev()) {
-            // abort early if we finally run into nodes with different keys
-            if old.key() != new.key() {
+        let right_offset = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take_while(|(old, new)| old.key() == new.key())
+            .count();
+
+        let (old_end, new_end) = (old.len() - right_offset, new.len() - right_offset);
+        for idx in (0..right_offset).rev() {
+            let (old_node, new_node) = (&old[old_end + idx], &new[new_end + idx]);
+            if self.should_pause(old_node, new_node) {
+                self.save_frame(&old[old_end..=old_end + idx], &new[new_end..=new_end + idx]);
                 break;
             }
-            self.diff_node(old, new);
-            right_offset += 1;
+            self.diff_node(old_node, new_node);
+            self.diffed_siblings += 1;
         }
 
         Some((left_offset, right_offset))
@@ -932,12 +1482,16 @@ impl<'b> DiffState<'b> {
                     .try_mounted_id()
                     .unwrap_or_else(|| panic!("broke on {:?}", old));
 
+                if let Some(node_ref) = el.node_ref {
+                    node_ref.unmount(id);
+                }
+
                 self.mutations.replace_with(id, nodes_created as u32);
                 self.remove_nodes(el.children, false);
                 self.scopes.collect_garbage(id);
             }
 
-            VNode::Text(_) | VNode::Placeholder(_) => {
+            VNode::Text(_) | VNode::Placeholder(_) | VNode::Template(_) => {
                 let id = old
                     .try_mounted_id()
                     .unwrap_or_else(|| panic!("broke on {:?}", old));
@@ -951,6 +1505,16 @@ impl<'b> DiffState<'b> {
                 self.remove_nodes(f.children.iter().skip(1), true);
             }
 
+            VNode::Portal(p) => {
+                let id = p.id.get().unwrap();
+                self.mutations.replace_with(id, nodes_created as u32);
+                self.scopes.collect_garbage(id);
+                p.id.set(None);
+
+                // the children live under the named root, so removing the placeholder doesn't remove them
+                self.remove_nodes(p.children, true);
+            }
+
             VNode::Component(c) => {
                 log::trace!("Replacing component {:?}", old);
                 let scope_id = c.scope.get().unwrap();
@@ -958,7 +1522,19 @@ impl<'b> DiffState<'b> {
 
                 self.enter_scope(scope_id);
                 {
-                    self.replace_inner(node, nodes_created);
+                    if let Some(fallback) = self
+                        .scopes
+                        .get_scope(scope_id)
+                        .and_then(ScopeState::suspense_fallback)
+                    {
+                        self.enter_scope(fallback);
+                        self.replace_inner(self.scopes.root_node(fallback), nodes_created);
+                        self.leave_scope();
+                        self.scopes.try_remove(fallback).unwrap();
+                        self.remove_nodes([node], false);
+                    } else {
+                        self.replace_inner(node, nodes_created);
+                    }
 
                     log::trace!("Replacing component x2 {:?}", old);
 
@@ -996,6 +1572,15 @@ impl<'b> DiffState<'b> {
                         self.mutations.remove(id.as_u64());
                     }
                 }
+                VNode::Template(t) => {
+                    let id = t.id.get().unwrap();
+                    self.scopes.collect_garbage(id);
+                    t.id.set(None);
+
+                    if gen_muts {
+                        self.mutations.remove(id.as_u64());
+                    }
+                }
                 VNode::Element(e) => {
                     let id = e.id.get().unwrap();
 
@@ -1006,6 +1591,10 @@ impl<'b> DiffState<'b> {
                     self.scopes.collect_garbage(id);
                     e.id.set(None);
 
+                    if let Some(node_ref) = e.node_ref {
+                        node_ref.unmount(id);
+                    }
+
                     self.remove_nodes(e.children, false);
                 }
 
@@ -1013,12 +1602,28 @@ impl<'b> DiffState<'b> {
                     self.remove_nodes(f.children, gen_muts);
                 }
 
+                VNode::Portal(p) => {
+                    let id = p.id.get().unwrap();
+                    self.scopes.collect_garbage(id);
+                    p.id.set(None);
+
+                    if gen_muts {
+                        self.mutations.remove(id.as_u64());
+                    }
+
+                    // the children live under the named root, so removing an ancestor doesn't remove them
+                    self.remove_nodes(p.children, true);
+                }
+
                 VNode::Component(c) => {
                     self.enter_scope(c.scope.get().unwrap());
                     {
                         let scope_id = c.scope.get().unwrap();
                         let root = self.scopes.root_node(scope_id);
-                        self.remove_nodes([root], gen_muts);
+
+                        // only the fallback of a suspended boundary was ever sent to the renderer
+                        let suspended = self.remove_suspense_fallback(scope_id, gen_muts);
+                        self.remove_nodes([root], gen_muts && !suspended);
 
                         let scope = self.scopes.get_scope(scope_id).unwrap();
                         c.scope.set(None);
@@ -1077,10 +1682,12 @@ impl<'b> DiffState<'b> {
                 VNode::Text(t) => break t.id.get(),
                 VNode::Element(t) => break t.id.get(),
                 VNode::Placeholder(t) => break t.id.get(),
+                VNode::Template(t) => break t.id.get(),
+                VNode::Portal(t) => break t.id.get(),
                 VNode::Fragment(frag) => search_node = frag.children.last(),
                 VNode::Component(el) => {
                     let scope_id = el.scope.get().unwrap();
-                    search_node = Some(self.scopes.root_node(scope_id));
+                    search_node = Some(self.scopes.mounted_root(scope_id));
                 }
             }
         }
@@ -1093,10 +1700,12 @@ impl<'b> DiffState<'b> {
                 VNode::Text(t) => break t.id.get(),
                 VNode::Element(t) => break t.id.get(),
                 VNode::Placeholder(t) => break t.id.get(),
+                VNode::Template(t) => break t.id.get(),
+                VNode::Portal(t) => break t.id.get(),
                 VNode::Fragment(frag) => search_node = Some(&frag.children[0]),
                 VNode::Component(el) => {
                     let scope = el.scope.get().expect("element to have a scope assigned");
-                    search_node = Some(self.scopes.root_node(scope));
+                    search_node = Some(self.scopes.mounted_root(scope));
                 }
             }
         }
@@ -1105,7 +1714,11 @@ impl<'b> DiffState<'b> {
     // recursively push all the nodes of a tree onto the stack and return how many are there
     fn push_all_real_nodes(&mut self, node: &'b VNode<'b>) -> usize {
         match node {
-            VNode::Text(_) | VNode::Placeholder(_) | VNode::Element(_) => {
+            VNode::Text(_)
+            | VNode::Placeholder(_)
+            | VNode::Element(_)
+            | VNode::Template(_)
+            | VNode::Portal(_) => {
                 self.mutations.push_root(node.mounted_id());
                 1
             }
@@ -1120,7 +1733,7 @@ impl<'b> DiffState<'b> {
 
             VNode::Component(c) => {
                 let scope_id = c.scope.get().unwrap();
-                let root = self.scopes.root_node(scope_id);
+                let root = self.scopes.mounted_root(scope_id);
                 self.push_all_real_nodes(root)
             }
         }
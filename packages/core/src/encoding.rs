@@ -0,0 +1,426 @@
+//! A compact binary encoding for [`DomEdit`] streams.
+//!
+//! JSON spends most of its bytes on field names and repeated tag names. This encoding replaces them with opcode
+//! bytes, LEB128 varints, and a string table that interns tags, attribute names, namespaces, and event names. The
+//! table lives as long as the [`EditEncoder`], so once a name has been sent it only costs a byte or two in every
+//! following batch.
+//!
+//! The matching decoder is `EditDecoder` in `dioxus-interpreter-js`'s `interpreter.js`. It produces the same objects
+//! as parsing the serde JSON representation, so the edits can be handed straight to `Interpreter::handleEdits`.
+//!
+//! ## Format
+//!
+//! Each batch is laid out as:
+//!
+//! ```text
+//! version: u8
+//! new strings: varint count, then (varint len, utf8 bytes) for each string appended to the table
+//! edits: varint count, then (opcode: u8, operands...) for each edit
+//! ```
+//!
+//! Ids, counts, and unsigned numbers are varints, signed numbers are zigzag varints, and floats are little-endian.
+//! Interned strings are written as their index in the table, and optional ones as `index + 1`, with `0` for `None`.
+//! Text content and text attribute values are written inline as (varint len, utf8 bytes) since they rarely repeat.
+//! [`CustomValue`]s are written as their name and payload, both inline.
+
+use crate::innerlude::*;
+use fxhash::FxHashMap;
+use std::fmt::{Display, Formatter};
+
+/// The version byte written at the start of every batch
+pub const ENCODING_VERSION: u8 = 1;
+
+mod op {
+    pub const PUSH_ROOT: u8 = 0;
+    pub const APPEND_CHILDREN: u8 = 1;
+    pub const REPLACE_WITH: u8 = 2;
+    pub const INSERT_AFTER: u8 = 3;
+    pub const INSERT_BEFORE: u8 = 4;
+    pub const REMOVE: u8 = 5;
+    pub const CREATE_TEXT_NODE: u8 = 6;
+    pub const CREATE_ELEMENT: u8 = 7;
+    pub const CREATE_ELEMENT_NS: u8 = 8;
+    pub const CREATE_PLACEHOLDER: u8 = 9;
+    pub const NEW_EVENT_LISTENER: u8 = 10;
+    pub const REMOVE_EVENT_LISTENER: u8 = 11;
+    pub const SET_TEXT: u8 = 12;
+    pub const SET_ATTRIBUTE: u8 = 13;
+    pub const REMOVE_ATTRIBUTE: u8 = 14;
+    pub const POP_ROOT: u8 = 15;
+    pub const SAVE_TEMPLATE: u8 = 16;
+    pub const CLONE_TEMPLATE: u8 = 17;
+    pub const PUSH_NAMED_ROOT: u8 = 18;
+}
+
+mod value {
+    pub const TEXT: u8 = 0;
+    pub const FLOAT32: u8 = 1;
+    pub const FLOAT64: u8 = 2;
+    pub const INT32: u8 = 3;
+    pub const INT64: u8 = 4;
+    pub const UINT32: u8 = 5;
+    pub const UINT64: u8 = 6;
+    pub const BOOL: u8 = 7;
+    pub const VEC3_FLOAT: u8 = 8;
+    pub const VEC3_INT: u8 = 9;
+    pub const VEC3_UINT: u8 = 10;
+    pub const VEC4_FLOAT: u8 = 11;
+    pub const VEC4_INT: u8 = 12;
+    pub const VEC4_UINT: u8 = 13;
+    pub const BYTES: u8 = 14;
+    pub const CUSTOM: u8 = 15;
+}
+
+/// An error encountered while encoding a batch of edits.
+///
+/// Nothing is written to the string table for a batch that fails, so the encoder can keep being used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncodeError {
+    /// An [`AttributeValue::Any`] holds a type that isn't registered with [`register_attribute_value`]
+    UnsupportedValue {
+        /// The node the attribute was set on
+        root: u64,
+
+        /// The name of the attribute
+        field: &'static str,
+    },
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::UnsupportedValue { root, field } => write!(
+                f,
+                "attribute \"{}\" on node {} holds an arbitrary value, which can't be encoded",
+                field, root
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+/// Encode a single batch of edits with a fresh string table.
+///
+/// Use an [`EditEncoder`] to share the string table across batches.
+pub fn encode_edits(edits: &[DomEdit]) -> Result<Vec<u8>, EncodeError> {
+    EditEncoder::new().encode(edits)
+}
+
+/// Encodes batches of edits into the binary format, remembering every string it has interned.
+///
+/// The decoder on the other end must see every batch, in order, to keep its string table in sync.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let mut dom = VirtualDom::new(App);
+/// let mut encoder = EditEncoder::new();
+///
+/// socket.send(encoder.encode(&dom.rebuild().edits)?);
+///
+/// loop {
+///     dom.wait_for_work().await;
+///     for mutations in dom.work_with_deadline(|| false) {
+///         socket.send(encoder.encode(&mutations.edits)?);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct EditEncoder {
+    strings: FxHashMap<String, u64>,
+}
+
+impl EditEncoder {
+    /// Create an encoder with an empty string table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget every interned string. The decoder must be reset at the same time.
+    pub fn reset(&mut self) {
+        self.strings.clear();
+    }
+
+    /// Encode a batch of edits.
+    pub fn encode(&mut self, edits: &[DomEdit]) -> Result<Vec<u8>, EncodeError> {
+        let mut batch = Batch {
+            table: &self.strings,
+            new_strings: Vec::new(),
+            new_lookup: FxHashMap::default(),
+            buf: Vec::with_capacity(edits.len() * 4),
+        };
+
+        for edit in edits {
+            batch.edit(edit)?;
+        }
+
+        let mut out = Vec::with_capacity(batch.buf.len() + 8);
+        out.push(ENCODING_VERSION);
+
+        write_varint(&mut out, batch.new_strings.len() as u64);
+        for string in &batch.new_strings {
+            write_str(&mut out, string);
+        }
+
+        write_varint(&mut out, edits.len() as u64);
+        out.extend_from_slice(&batch.buf);
+
+        // only commit the new strings once the whole batch has been encoded
+        let first = self.strings.len() as u64;
+        for (idx, string) in batch.new_strings.into_iter().enumerate() {
+            self.strings.insert(string.to_string(), first + idx as u64);
+        }
+
+        Ok(out)
+    }
+}
+
+struct Batch<'e, 'a> {
+    table: &'e FxHashMap<String, u64>,
+    new_strings: Vec<&'a str>,
+    new_lookup: FxHashMap<&'a str, u64>,
+    buf: Vec<u8>,
+}
+
+impl<'e, 'a> Batch<'e, 'a> {
+    fn edit(&mut self, edit: &DomEdit<'a>) -> Result<(), EncodeError> {
+        match *edit {
+            DomEdit::PushRoot { root } => {
+                self.buf.push(op::PUSH_ROOT);
+                write_varint(&mut self.buf, root);
+            }
+            DomEdit::AppendChildren { many } => {
+                self.buf.push(op::APPEND_CHILDREN);
+                write_varint(&mut self.buf, many as u64);
+            }
+            DomEdit::ReplaceWith { root, m } => {
+                self.buf.push(op::REPLACE_WITH);
+                write_varint(&mut self.buf, root);
+                write_varint(&mut self.buf, m as u64);
+            }
+            DomEdit::InsertAfter { root, n } => {
+                self.buf.push(op::INSERT_AFTER);
+                write_varint(&mut self.buf, root);
+                write_varint(&mut self.buf, n as u64);
+            }
+            DomEdit::InsertBefore { root, n } => {
+                self.buf.push(op::INSERT_BEFORE);
+                write_varint(&mut self.buf, root);
+                write_varint(&mut self.buf, n as u64);
+            }
+            DomEdit::Remove { root } => {
+                self.buf.push(op::REMOVE);
+                write_varint(&mut self.buf, root);
+            }
+            DomEdit::CreateTextNode { root, text } => {
+                self.buf.push(op::CREATE_TEXT_NODE);
+                write_varint(&mut self.buf, root);
+                write_str(&mut self.buf, text);
+            }
+            DomEdit::CreateElement { root, tag } => {
+                self.buf.push(op::CREATE_ELEMENT);
+                write_varint(&mut self.buf, root);
+                self.interned(tag);
+            }
+            DomEdit::CreateElementNs { root, tag, ns } => {
+                self.buf.push(op::CREATE_ELEMENT_NS);
+                write_varint(&mut self.buf, root);
+                self.interned(tag);
+                self.interned(ns);
+            }
+            DomEdit::CreatePlaceholder { root } => {
+                self.buf.push(op::CREATE_PLACEHOLDER);
+                write_varint(&mut self.buf, root);
+            }
+            DomEdit::NewEventListener {
+                event_name,
+                scope,
+                root,
+            } => {
+                self.buf.push(op::NEW_EVENT_LISTENER);
+                self.interned(event_name);
+                write_varint(&mut self.buf, scope.0 as u64);
+                write_varint(&mut self.buf, root);
+            }
+            DomEdit::RemoveEventListener { root, event } => {
+                self.buf.push(op::REMOVE_EVENT_LISTENER);
+                write_varint(&mut self.buf, root);
+                self.interned(event);
+            }
+            DomEdit::SetText { root, text } => {
+                self.buf.push(op::SET_TEXT);
+                write_varint(&mut self.buf, root);
+                write_str(&mut self.buf, text);
+            }
+            DomEdit::SetAttribute {
+                root,
+                field,
+                ref value,
+                ns,
+            } => {
+                self.buf.push(op::SET_ATTRIBUTE);
+                write_varint(&mut self.buf, root);
+                self.interned(field);
+                self.value(value)
+                    .map_err(|_| EncodeError::UnsupportedValue { root, field })?;
+                self.optional_interned(ns);
+            }
+            DomEdit::RemoveAttribute { root, name, ns } => {
+                self.buf.push(op::REMOVE_ATTRIBUTE);
+                write_varint(&mut self.buf, root);
+                self.interned(name);
+                self.optional_interned(ns);
+            }
+            DomEdit::PopRoot {} => self.buf.push(op::POP_ROOT),
+            DomEdit::PushNamedRoot { name } => {
+                self.buf.push(op::PUSH_NAMED_ROOT);
+                self.interned(name);
+            }
+            DomEdit::SaveTemplate { id } => {
+                self.buf.push(op::SAVE_TEMPLATE);
+                write_varint(&mut self.buf, id);
+            }
+            DomEdit::CloneTemplate { id, root } => {
+                self.buf.push(op::CLONE_TEMPLATE);
+                write_varint(&mut self.buf, id);
+                write_varint(&mut self.buf, root);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn value(&mut self, value: &AttributeValue<'a>) -> Result<(), ()> {
+        let buf = &mut self.buf;
+        match *value {
+            AttributeValue::Text(text) => {
+                buf.push(value::TEXT);
+                write_str(buf, text);
+            }
+            AttributeValue::Float32(a) => {
+                buf.push(value::FLOAT32);
+                buf.extend_from_slice(&a.to_le_bytes());
+            }
+            AttributeValue::Float64(a) => {
+                buf.push(value::FLOAT64);
+                buf.extend_from_slice(&a.to_le_bytes());
+            }
+            AttributeValue::Int32(a) => {
+                buf.push(value::INT32);
+                write_signed(buf, a as i64);
+            }
+            AttributeValue::Int64(a) => {
+                buf.push(value::INT64);
+                write_signed(buf, a);
+            }
+            AttributeValue::Uint32(a) => {
+                buf.push(value::UINT32);
+                write_varint(buf, a as u64);
+            }
+            AttributeValue::Uint64(a) => {
+                buf.push(value::UINT64);
+                write_varint(buf, a);
+            }
+            AttributeValue::Bool(a) => {
+                buf.push(value::BOOL);
+                buf.push(a as u8);
+            }
+            AttributeValue::Vec3Float(a, b, c) => {
+                buf.push(value::VEC3_FLOAT);
+                for f in [a, b, c] {
+                    buf.extend_from_slice(&f.to_le_bytes());
+                }
+            }
+            AttributeValue::Vec3Int(a, b, c) => {
+                buf.push(value::VEC3_INT);
+                for i in [a, b, c] {
+                    write_signed(buf, i as i64);
+                }
+            }
+            AttributeValue::Vec3Uint(a, b, c) => {
+                buf.push(value::VEC3_UINT);
+                for u in [a, b, c] {
+                    write_varint(buf, u as u64);
+                }
+            }
+            AttributeValue::Vec4Float(a, b, c, d) => {
+                buf.push(value::VEC4_FLOAT);
+                for f in [a, b, c, d] {
+                    buf.extend_from_slice(&f.to_le_bytes());
+                }
+            }
+            AttributeValue::Vec4Int(a, b, c, d) => {
+                buf.push(value::VEC4_INT);
+                for i in [a, b, c, d] {
+                    write_signed(buf, i as i64);
+                }
+            }
+            AttributeValue::Vec4Uint(a, b, c, d) => {
+                buf.push(value::VEC4_UINT);
+                for u in [a, b, c, d] {
+                    write_varint(buf, u as u64);
+                }
+            }
+            AttributeValue::Bytes(bytes) => {
+                buf.push(value::BYTES);
+                write_varint(buf, bytes.len() as u64);
+                buf.extend_from_slice(bytes);
+            }
+            AttributeValue::Any(a) => {
+                let custom = a.to_custom().ok_or(())?;
+                buf.push(value::CUSTOM);
+                write_str(buf, &custom.name);
+                write_str(buf, &custom.payload);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn intern(&mut self, string: &'a str) -> u64 {
+        if let Some(idx) = self.table.get(string) {
+            return *idx;
+        }
+
+        if let Some(idx) = self.new_lookup.get(string) {
+            return *idx;
+        }
+
+        let idx = (self.table.len() + self.new_strings.len()) as u64;
+        self.new_strings.push(string);
+        self.new_lookup.insert(string, idx);
+        idx
+    }
+
+    fn interned(&mut self, string: &'a str) {
+        let idx = self.intern(string);
+        write_varint(&mut self.buf, idx);
+    }
+
+    fn optional_interned(&mut self, string: Option<&'a str>) {
+        let idx = string.map_or(0, |string| self.intern(string) + 1);
+        write_varint(&mut self.buf, idx);
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_signed(buf: &mut Vec<u8>, value: i64) {
+    write_varint(buf, ((value << 1) ^ (value >> 63)) as u64);
+}
+
+fn write_str(buf: &mut Vec<u8>, string: &str) {
+    write_varint(buf, string.len() as u64);
+    buf.extend_from_slice(string.as_bytes());
+}
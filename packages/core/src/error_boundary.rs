@@ -0,0 +1,288 @@
+//! Error boundaries catch errors thrown by components further down the tree.
+//!
+//! Without a boundary, a component that panics takes down the whole VirtualDom, and a component
+//! that returns `None` because some `?` failed silently disappears from the tree. Boundaries give
+//! those errors somewhere to go: the nearest boundary above the failing component captures the
+//! error and re-renders, letting it swap its children for a fallback.
+
+use crate::innerlude::*;
+use std::{
+    any::Any,
+    cell::{Ref, RefCell},
+    fmt::{Debug, Display},
+    rc::Rc,
+    sync::Arc,
+};
+
+/// A boundary that captures errors thrown by the components beneath it.
+///
+/// Boundaries are created with [`ScopeState::provide_error_boundary`]. Errors are delivered with
+/// [`ScopeState::throw`], the [`Throw`] trait, or by panicking while rendering.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn Boundary<'a>(cx: Scope<'a, BoundaryProps<'a>>) -> Element {
+///     let boundary = cx.provide_error_boundary();
+///
+///     match boundary.error() {
+///         Some(error) => cx.render(rsx!(
+///             "Something went wrong: {error}"
+///             button { onclick: move |_| boundary.retry(), "retry" }
+///         )),
+///         None => cx.render(rsx!(&cx.props.children)),
+///     }
+/// }
+/// ```
+pub struct ErrorBoundary {
+    error: RefCell<Option<CapturedError>>,
+    id: ScopeId,
+    update_any: Arc<dyn Fn(ScopeId) + Send + Sync>,
+}
+
+impl ErrorBoundary {
+    pub(crate) fn new(id: ScopeId, update_any: Arc<dyn Fn(ScopeId) + Send + Sync>) -> Self {
+        Self {
+            error: RefCell::new(None),
+            id,
+            update_any,
+        }
+    }
+
+    /// The scope this boundary belongs to
+    pub fn scope_id(&self) -> ScopeId {
+        self.id
+    }
+
+    /// Check if the boundary has captured an error
+    pub fn has_error(&self) -> bool {
+        self.error.borrow().is_some()
+    }
+
+    /// Get the error this boundary has captured, if any
+    pub fn error(&self) -> Option<Ref<'_, CapturedError>> {
+        Ref::filter_map(self.error.borrow(), |error| error.as_ref()).ok()
+    }
+
+    /// Take the captured error out of the boundary without scheduling a re-render
+    pub fn take_error(&self) -> Option<CapturedError> {
+        self.error.borrow_mut().take()
+    }
+
+    /// Clear the captured error and re-render the boundary so it can try its children again.
+    pub fn retry(&self) {
+        self.error.borrow_mut().take();
+        (self.update_any)(self.id);
+    }
+
+    /// Capture an error and schedule the boundary to re-render.
+    ///
+    /// The first error wins - subsequent errors are logged and dropped until the boundary retries.
+    pub(crate) fn insert_error(&self, error: CapturedError) {
+        let mut slot = self.error.borrow_mut();
+
+        if slot.is_some() {
+            log::error!(
+                "Error boundary already holds an error, dropping {:?}",
+                error
+            );
+            return;
+        }
+
+        *slot = Some(error);
+        (self.update_any)(self.id);
+    }
+}
+
+/// An error captured by an [`ErrorBoundary`].
+pub struct CapturedError {
+    error: Box<dyn AnyDebug>,
+    scope: ScopeId,
+}
+
+impl CapturedError {
+    pub(crate) fn new(error: impl Debug + 'static, scope: ScopeId) -> Self {
+        Self {
+            error: Box::new(error),
+            scope,
+        }
+    }
+
+    /// The scope that threw the error
+    pub fn scope_id(&self) -> ScopeId {
+        self.scope
+    }
+
+    /// Try to downcast the error to a concrete type.
+    ///
+    /// Panics while rendering are captured as a [`RenderPanic`].
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        (*self.error).as_any().downcast_ref::<T>()
+    }
+}
+
+impl Debug for CapturedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CapturedError")
+            .field("error", &self.error)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+impl Display for CapturedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.error)
+    }
+}
+
+/// The error captured when a component panics while rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderPanic {
+    /// The message the component panicked with
+    pub message: String,
+}
+
+impl RenderPanic {
+    pub(crate) fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(msg) = payload.downcast_ref::<&'static str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+
+        Self { message }
+    }
+}
+
+/// The error thrown when [`Throw`] is called on a [`None`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MissingValue {
+    /// The name of the type that was expected
+    pub type_name: &'static str,
+}
+
+/// Throw the error in a `Result` or `Option` to the nearest [`ErrorBoundary`].
+///
+/// This is useful with the `?` operator, making a failure visible to the boundary instead of
+/// silently rendering nothing.
+///
+/// ```rust, ignore
+/// fn Component(cx: Scope) -> Element {
+///     let theme = cx.consume_context::<Theme>().throw(&cx)?;
+///     let count: i32 = "123".parse().throw(&cx)?;
+///     // ...
+/// }
+/// ```
+pub trait Throw {
+    /// The value left over when there is no error
+    type Out;
+
+    /// Throw any error to the nearest error boundary, returning `None` so the component can bail
+    /// with `?`.
+    fn throw(self, cx: &ScopeState) -> Option<Self::Out>;
+}
+
+impl<T, E: Debug + 'static> Throw for Result<T, E> {
+    type Out = T;
+
+    fn throw(self, cx: &ScopeState) -> Option<T> {
+        match self {
+            Ok(value) => Some(value),
+            Err(error) => {
+                cx.throw(error);
+                None
+            }
+        }
+    }
+}
+
+impl<T> Throw for Option<T> {
+    type Out = T;
+
+    fn throw(self, cx: &ScopeState) -> Option<T> {
+        match self {
+            Some(value) => Some(value),
+            None => {
+                cx.throw(MissingValue {
+                    type_name: std::any::type_name::<T>(),
+                });
+                None
+            }
+        }
+    }
+}
+
+impl ScopeState {
+    /// Make this scope an [`ErrorBoundary`] for its descendants.
+    ///
+    /// Calling this more than once returns the same boundary, so it's fine to call it every render.
+    pub fn provide_error_boundary(&self) -> Rc<ErrorBoundary> {
+        if let Some(boundary) = self.own_context::<Rc<ErrorBoundary>>() {
+            return boundary;
+        }
+
+        let boundary = Rc::new(ErrorBoundary::new(
+            self.scope_id(),
+            self.schedule_update_any(),
+        ));
+        self.provide_context(boundary)
+    }
+
+    /// Throw an error to the nearest [`ErrorBoundary`] above this scope.
+    ///
+    /// A boundary never catches its own errors - those go to the next boundary up the tree.
+    ///
+    /// Always returns `None` so the component can bail out with `?`. If no boundary exists, the
+    /// error is logged and the component renders nothing, like any other `None`.
+    pub fn throw(&self, error: impl Debug + 'static) -> Option<()> {
+        let error = CapturedError::new(error, self.scope_id());
+
+        match self.parent_error_boundary() {
+            Some(boundary) => {
+                // only errors thrown while rendering fail the render - not ones thrown from handlers or tasks
+                if current_scope_id() == Some(self.scope_id()) {
+                    self.render_failed.set(true);
+                }
+                boundary.insert_error(error)
+            }
+            None => log::error!("Uncaught error in {:?}: {:?}", self.scope_id(), error),
+        }
+
+        None
+    }
+
+    /// Deliver a panic from this scope's render to the nearest boundary.
+    ///
+    /// Returns the payload back if no boundary is around to catch it.
+    pub(crate) fn throw_panic(
+        &self,
+        payload: Box<dyn Any + Send>,
+    ) -> Result<(), Box<dyn Any + Send>> {
+        match self.parent_error_boundary() {
+            Some(boundary) => {
+                let panic = RenderPanic::from_payload(payload.as_ref());
+                boundary.insert_error(CapturedError::new(panic, self.scope_id()));
+                self.render_failed.set(true);
+                Ok(())
+            }
+            None => Err(payload),
+        }
+    }
+
+    pub(crate) fn parent_error_boundary(&self) -> Option<Rc<ErrorBoundary>> {
+        self.parent_context::<Rc<ErrorBoundary>>()
+    }
+}
+
+trait AnyDebug: Any + Debug {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any + Debug> AnyDebug for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
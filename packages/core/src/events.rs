@@ -88,6 +88,7 @@ pub struct UserEvent {
 /// We still have a concept of discrete vs continuous though - discrete events won't be batched, but continuous events will.
 /// This means that multiple "scroll" events will be processed in a single frame, but multiple "click" events will be
 /// flushed before proceeding. Multiple discrete events is highly unlikely, though.
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
 pub enum EventPriority {
     /// Work that must be completed during the EventHandler phase.
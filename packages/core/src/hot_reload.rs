@@ -0,0 +1,503 @@
+//! Hot reloading of the static parts of `rsx!` calls.
+//!
+//! With the `hot-reload` feature enabled on the macro, every `rsx!` call records where it is in the source code and
+//! captures the values of its dynamic parts - components, expressions, event listeners, and the arguments of
+//! formatted strings - each identified by its source code. A file watcher re-parses edited calls and sends their new
+//! structure to the app as an [`RsxTemplate`].
+//!
+//! [`VirtualDom::replace_template`] stores the template and re-renders every scope that rendered the call. From then
+//! on, the call builds its nodes from the template instead of its compiled code, slotting in the captured values
+//! wherever the template refers to them. Dynamic parts keep running their compiled code, so a template can rearrange
+//! them but can't add new ones - templates that refer to code the app wasn't compiled with are ignored.
+//!
+//! Hot reloading is disabled in release builds.
+
+use crate::innerlude::*;
+use fxhash::{FxHashMap, FxHashSet};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+/// Where an `rsx!` call is in the source code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct CodeLocation {
+    /// The file the call is in.
+    ///
+    /// Compiled calls use the path from `file!()`, which may be relative to the workspace.
+    pub file: Cow<'static, str>,
+
+    /// The line of the start of the call, starting from 1.
+    pub line: u32,
+
+    /// The column of the start of the call, starting from 0.
+    pub column: u32,
+}
+
+impl CodeLocation {
+    /// Create the location of a compiled `rsx!` call
+    pub const fn new(file: &'static str, line: u32, column: u32) -> Self {
+        Self {
+            file: Cow::Borrowed(file),
+            line,
+            column,
+        }
+    }
+
+    /// Check if two locations point to the same call.
+    ///
+    /// The paths may be relative to different directories, so one only has to end with the other.
+    pub fn matches(&self, other: &CodeLocation) -> bool {
+        if self.line != other.line || self.column != other.column {
+            return false;
+        }
+
+        let (a, b) = (self.file.replace('\\', "/"), other.file.replace('\\', "/"));
+        let (long, short) = match a.len() >= b.len() {
+            true => (a, b),
+            false => (b, a),
+        };
+
+        long == short || long.ends_with(&format!("/{}", short.trim_start_matches("./")))
+    }
+}
+
+/// The new structure of an `rsx!` call.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct RsxTemplate {
+    /// The location of the call as it was compiled.
+    pub location: CodeLocation,
+
+    /// The nodes at the root of the call.
+    pub roots: Vec<TemplateNode>,
+}
+
+/// A node in an [`RsxTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemplateNode {
+    /// An element like `div { class: "a", "hello" }`.
+    Element(TemplateElement),
+
+    /// Text, which can use the arguments of formatted strings in the compiled call like `"hello {name}"`.
+    Text(String),
+
+    /// A component or an expression, identified by its source code.
+    Dynamic(String),
+}
+
+/// An element in an [`RsxTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemplateElement {
+    /// The tag as it's written in `rsx!`.
+    pub tag: String,
+
+    /// The key of the element, formatted like text.
+    pub key: Option<String>,
+
+    /// The source code of the element's `node_ref`.
+    pub node_ref: Option<String>,
+
+    /// The attributes of the element.
+    pub attributes: Vec<TemplateAttribute>,
+
+    /// The source code of the element's event listeners, like `onclick: move |_| count += 1`.
+    pub listeners: Vec<String>,
+
+    /// The children of the element.
+    pub children: Vec<TemplateNode>,
+}
+
+/// An attribute in an [`RsxTemplate`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemplateAttribute {
+    /// An attribute set to text, like `class: "a {b}"`.
+    Text {
+        /// The name as it's written in `rsx!`.
+        name: String,
+
+        /// Whether the name was written as a string, like `"data-id": "a"`.
+        custom: bool,
+
+        /// The value, formatted like text.
+        value: String,
+    },
+
+    /// An attribute set to an expression, identified by its source code like `value: name`.
+    Dynamic(String),
+}
+
+/// What a compiled `rsx!` call can fill a template in with, generated by the macro.
+#[doc(hidden)]
+pub struct CompiledTemplate {
+    pub location: CodeLocation,
+    pub nodes: &'static [&'static str],
+    pub attributes: &'static [&'static str],
+    pub listeners: &'static [&'static str],
+    pub node_refs: &'static [&'static str],
+    pub formatted: &'static [&'static str],
+}
+
+/// The values of the dynamic parts of a compiled `rsx!` call, generated by the macro.
+#[doc(hidden)]
+pub struct CapturedContext<'a> {
+    /// The tag names and namespaces of the elements in the call.
+    pub elements: Vec<(&'static str, &'static str, Option<&'static str>)>,
+
+    /// The attributes used on each element of the call, to look up their names and namespaces.
+    pub attribute_names: Vec<(&'static str, &'static str, Attribute<'a>)>,
+
+    /// The arguments of the formatted strings in the call.
+    pub formatted: Vec<(&'static str, &'a str)>,
+
+    pub nodes: Vec<(&'static str, VNode<'a>)>,
+    pub attributes: Vec<(&'static str, Attribute<'a>)>,
+    pub listeners: Vec<(&'static str, Listener<'a>)>,
+    pub node_refs: Vec<(&'static str, &'a NodeRef)>,
+}
+
+impl RsxTemplate {
+    /// Build the nodes of the template, filling them in with the values the compiled call captured.
+    #[doc(hidden)]
+    pub fn render<'a>(&self, cx: NodeFactory<'a>, mut captured: CapturedContext<'a>) -> VNode<'a> {
+        let mut nodes = bumpalo::collections::Vec::new_in(cx.bump());
+        for root in &self.roots {
+            nodes.push(render_node(cx, &mut captured, root, None));
+        }
+
+        match nodes.len() {
+            1 => nodes.pop().unwrap(),
+            _ => cx.fragment_root(nodes),
+        }
+    }
+
+    /// Check that the compiled call can fill in every dynamic part of the template.
+    ///
+    /// Dynamic parts are moved into the nodes they're used in, so each one can only be used as many times as it
+    /// appears in the compiled call.
+    fn fits(&self, compiled: &CompiledTemplate) -> bool {
+        #[derive(Default)]
+        struct Used<'t> {
+            nodes: Vec<&'t str>,
+            attributes: Vec<&'t str>,
+            listeners: Vec<&'t str>,
+            node_refs: Vec<&'t str>,
+            texts: Vec<&'t str>,
+        }
+
+        fn visit<'t>(node: &'t TemplateNode, used: &mut Used<'t>) {
+            match node {
+                TemplateNode::Text(text) => used.texts.push(text),
+                TemplateNode::Dynamic(key) => used.nodes.push(key),
+                TemplateNode::Element(el) => {
+                    used.texts.extend(el.key.as_deref());
+                    used.node_refs.extend(el.node_ref.as_deref());
+                    used.listeners
+                        .extend(el.listeners.iter().map(String::as_str));
+                    for attr in &el.attributes {
+                        match attr {
+                            TemplateAttribute::Text { value, .. } => used.texts.push(value),
+                            TemplateAttribute::Dynamic(key) => used.attributes.push(key),
+                        }
+                    }
+                    for child in &el.children {
+                        visit(child, used);
+                    }
+                }
+            }
+        }
+
+        fn available(used: &[&str], compiled: &[&str]) -> bool {
+            let mut remaining = compiled.to_vec();
+            used.iter()
+                .all(|key| match remaining.iter().position(|k| k == key) {
+                    Some(idx) => {
+                        remaining.swap_remove(idx);
+                        true
+                    }
+                    None => false,
+                })
+        }
+
+        let mut used = Used::default();
+        for root in &self.roots {
+            visit(root, &mut used);
+        }
+
+        available(&used.nodes, compiled.nodes)
+            && available(&used.attributes, compiled.attributes)
+            && available(&used.listeners, compiled.listeners)
+            && available(&used.node_refs, compiled.node_refs)
+            && used.texts.iter().all(|text| {
+                format_segments(text)
+                    .map(|segments| {
+                        segments.iter().all(|segment| match segment {
+                            Segment::Literal(_) => true,
+                            Segment::Formatted(arg) => compiled.formatted.contains(arg),
+                        })
+                    })
+                    .unwrap_or(false)
+            })
+    }
+}
+
+fn render_node<'a>(
+    cx: NodeFactory<'a>,
+    captured: &mut CapturedContext<'a>,
+    node: &TemplateNode,
+    parent_namespace: Option<&'static str>,
+) -> VNode<'a> {
+    match node {
+        TemplateNode::Text(text) => {
+            let text = format_text(cx, captured, text);
+            cx.text(format_args!("{}", text))
+        }
+        TemplateNode::Dynamic(key) => match take(&mut captured.nodes, key) {
+            Some(node) => node,
+            None => cx.fragment_from_iter(None::<VNode>),
+        },
+        TemplateNode::Element(el) => {
+            let (tag, namespace) = match captured.elements.iter().find(|(name, ..)| *name == el.tag)
+            {
+                Some((_, tag, namespace)) => (*tag, *namespace),
+                // Elements that aren't in the compiled call are most likely in the same namespace as their parent
+                None => (intern(cx, &el.tag), parent_namespace),
+            };
+
+            let mut attributes = bumpalo::collections::Vec::new_in(cx.bump());
+            for attr in &el.attributes {
+                match attr {
+                    TemplateAttribute::Text {
+                        name,
+                        custom,
+                        value,
+                    } => {
+                        let resolved = captured
+                            .attribute_names
+                            .iter()
+                            .filter(|_| !custom)
+                            .find(|(tag, attr, _)| *tag == el.tag && *attr == name)
+                            .or_else(|| {
+                                captured
+                                    .attribute_names
+                                    .iter()
+                                    .filter(|_| !custom)
+                                    .find(|(_, attr, _)| *attr == name)
+                            })
+                            .map(|(_, _, attr)| (attr.name, attr.namespace, attr.is_volatile));
+                        let (name, namespace, is_volatile) =
+                            resolved.unwrap_or_else(|| (intern(cx, name), None, false));
+
+                        attributes.push(Attribute {
+                            name,
+                            value: AttributeValue::Text(format_text(cx, captured, value)),
+                            is_static: false,
+                            is_volatile,
+                            namespace,
+                        });
+                    }
+                    TemplateAttribute::Dynamic(key) => {
+                        attributes.extend(take(&mut captured.attributes, key));
+                    }
+                }
+            }
+
+            let mut listeners = bumpalo::collections::Vec::new_in(cx.bump());
+            for key in &el.listeners {
+                listeners.extend(take(&mut captured.listeners, key));
+            }
+
+            let mut children = bumpalo::collections::Vec::new_in(cx.bump());
+            for child in &el.children {
+                children.push(render_node(cx, captured, child, namespace));
+            }
+
+            let key = el.key.as_ref().map(|key| format_text(cx, captured, key));
+            let node_ref = el
+                .node_ref
+                .as_ref()
+                .and_then(|key| take(&mut captured.node_refs, key));
+
+            let (listeners, attributes, children) = (
+                listeners.into_bump_slice(),
+                attributes.into_bump_slice(),
+                children.into_bump_slice(),
+            );
+            match key {
+                Some(key) => cx.new_element(
+                    tag,
+                    namespace,
+                    listeners,
+                    attributes,
+                    children,
+                    Some(format_args!("{}", key)),
+                    node_ref,
+                ),
+                None => cx.new_element(
+                    tag, namespace, listeners, attributes, children, None, node_ref,
+                ),
+            }
+        }
+    }
+}
+
+fn take<T>(items: &mut Vec<(&'static str, T)>, key: &str) -> Option<T> {
+    let idx = items.iter().position(|(k, _)| *k == key)?;
+    Some(items.remove(idx).1)
+}
+
+/// Format text from a template with the arguments the compiled call captured
+fn format_text<'a>(cx: NodeFactory<'a>, captured: &CapturedContext<'a>, text: &str) -> &'a str {
+    let mut out = bumpalo::collections::String::new_in(cx.bump());
+
+    for segment in format_segments(text).unwrap_or_default() {
+        match segment {
+            Segment::Literal(literal) => out.push_str(&literal),
+            Segment::Formatted(arg) => {
+                if let Some((_, value)) = captured.formatted.iter().find(|(a, _)| *a == arg) {
+                    out.push_str(value);
+                }
+            }
+        }
+    }
+
+    out.into_bump_str()
+}
+
+enum Segment<'t> {
+    Literal(String),
+    Formatted(&'t str),
+}
+
+/// Split a format string into its literals and its arguments, like `{name}` or `{count:?}`.
+fn format_segments(text: &str) -> Option<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' if matches!(chars.peek(), Some((_, '{'))) => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if matches!(chars.peek(), Some((_, '}'))) => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let (end, _) = chars.find(|(_, c)| *c == '}')?;
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(Segment::Formatted(text[idx + 1..end].trim()));
+            }
+            '}' => return None,
+            c => literal.push(c),
+        }
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    Some(segments)
+}
+
+/// Tags and attribute names are `&'static str`, so the names that only appear in templates are leaked. There are only
+/// as many as there are distinct names in the templates of a session.
+fn intern(cx: NodeFactory, name: &str) -> &'static str {
+    let mut names = cx.scope.hot_reload.names.borrow_mut();
+    match names.get(name) {
+        Some(name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.insert(name);
+            name
+        }
+    }
+}
+
+/// The templates that replaced compiled `rsx!` calls, shared between the arena and its scopes.
+#[derive(Default)]
+pub(crate) struct HotReloadRegistry {
+    templates: RefCell<Vec<ReplacedTemplate>>,
+
+    /// The scopes that have rendered each compiled call.
+    rendered: RefCell<FxHashMap<CodeLocation, FxHashSet<ScopeId>>>,
+
+    names: RefCell<FxHashSet<&'static str>>,
+}
+
+struct ReplacedTemplate {
+    template: Rc<RsxTemplate>,
+
+    /// Whether the compiled call can fill in the template, checked the first time it's rendered.
+    fits: Cell<Option<bool>>,
+}
+
+impl HotReloadRegistry {
+    /// Store a template, returning the scopes that rendered the call it replaces.
+    pub fn replace(&self, template: RsxTemplate) -> Vec<ScopeId> {
+        let location = template.location.clone();
+
+        let mut templates = self.templates.borrow_mut();
+        templates.retain(|replaced| !replaced.template.location.matches(&location));
+        templates.push(ReplacedTemplate {
+            template: Rc::new(template),
+            fits: Cell::new(None),
+        });
+
+        self.rendered
+            .borrow()
+            .iter()
+            .filter(|(compiled, _)| compiled.matches(&location))
+            .flat_map(|(_, scopes)| scopes.iter().copied())
+            .collect()
+    }
+
+    /// Get the template that replaces a compiled call, if the call can fill it in.
+    pub fn get(&self, compiled: &CompiledTemplate, scope: ScopeId) -> Option<Rc<RsxTemplate>> {
+        self.rendered
+            .borrow_mut()
+            .entry(compiled.location.clone())
+            .or_default()
+            .insert(scope);
+
+        let templates = self.templates.borrow();
+        let replaced = templates
+            .iter()
+            .find(|replaced| replaced.template.location.matches(&compiled.location))?;
+
+        let fits = match replaced.fits.get() {
+            Some(fits) => fits,
+            None => {
+                let fits = replaced.template.fits(compiled);
+                if !fits {
+                    log::warn!(
+                        "The rsx! call at {}:{}:{} uses code the app wasn't compiled with. Rebuild the app to see the changes.",
+                        compiled.location.file,
+                        compiled.location.line,
+                        compiled.location.column
+                    );
+                }
+                replaced.fits.set(Some(fits));
+                fits
+            }
+        };
+
+        fits.then(|| replaced.template.clone())
+    }
+
+    /// Forget the scopes that were removed since the last template was applied
+    pub fn retain_scopes(&self, exists: impl Fn(ScopeId) -> bool) {
+        for scopes in self.rendered.borrow_mut().values_mut() {
+            scopes.retain(|id| exists(*id));
+        }
+    }
+}
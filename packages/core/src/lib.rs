@@ -3,24 +3,48 @@
 #![deny(missing_docs)]
 
 pub(crate) mod arbitrary_value;
+pub(crate) mod context;
 pub(crate) mod diff;
+#[cfg(feature = "serialize")]
+pub(crate) mod encoding;
+pub(crate) mod error_boundary;
 pub(crate) mod events;
+#[cfg(feature = "hot-reload")]
+pub(crate) mod hot_reload;
 pub(crate) mod lazynodes;
 pub(crate) mod mutations;
+pub(crate) mod node_ref;
 pub(crate) mod nodes;
+pub(crate) mod owned;
+#[cfg(feature = "profile")]
+pub(crate) mod profiler;
 pub(crate) mod properties;
 pub(crate) mod scopes;
+pub(crate) mod suspense;
+pub(crate) mod template;
 pub(crate) mod util;
 pub(crate) mod virtual_dom;
 
 pub(crate) mod innerlude {
     pub use crate::arbitrary_value::*;
+    pub use crate::context::*;
+    #[cfg(feature = "serialize")]
+    pub use crate::encoding::*;
+    pub use crate::error_boundary::*;
     pub use crate::events::*;
+    #[cfg(feature = "hot-reload")]
+    pub use crate::hot_reload::*;
     pub use crate::lazynodes::*;
     pub use crate::mutations::*;
+    pub use crate::node_ref::*;
     pub use crate::nodes::*;
+    pub use crate::owned::*;
+    #[cfg(feature = "profile")]
+    pub use crate::profiler::*;
     pub use crate::properties::*;
     pub use crate::scopes::*;
+    pub use crate::suspense::*;
+    pub use crate::template::*;
     pub use crate::util::*;
     pub use crate::virtual_dom::*;
 
@@ -73,10 +97,28 @@ pub(crate) mod innerlude {
 }
 
 pub use crate::innerlude::{
-    AnyEvent, Attribute, AttributeValue, Component, DioxusElement, DomEdit, Element, ElementId,
-    ElementIdIterator, EventHandler, EventPriority, IntoVNode, LazyNodes, Listener, Mutations,
-    NodeFactory, Properties, SchedulerMsg, Scope, ScopeId, ScopeState, TaskId, UiEvent, UserEvent,
-    VComponent, VElement, VFragment, VNode, VPlaceholder, VText, VirtualDom,
+    current_scope_id, register_attribute_value, AnyEvent, ArbitraryAttributeValue, Attribute,
+    AttributeValue, AttributeValueOwned, CapturedError, Component, ContextKey,
+    CustomAttributeValue, CustomValue, DioxusElement, DomEdit, DomEditOwned, EditInterner, Element,
+    ElementId, ElementIdIterator, ErrorBoundary, EventHandler, EventPriority, IntoVNode, LazyNodes,
+    Listener, Memo, MissingContext, MissingValue, Mutations, MutationsOwned, NodeFactory, NodeRef,
+    NodeRefMutation, Properties, RenderPanic, SchedulerMsg, Scope, ScopeId, ScopeState,
+    SuspenseBoundary, TaskHandle, TaskId, TemplateBuilder, Throw, ToOwnedError, UiEvent, UserEvent,
+    VComponent, VElement, VFragment, VNode, VPlaceholder, VPortal, VTemplate, VText, VirtualDom,
+};
+
+#[cfg(feature = "serialize")]
+pub use crate::innerlude::{encode_edits, EditEncoder, EncodeError, ENCODING_VERSION};
+
+#[cfg(feature = "hot-reload")]
+pub use crate::innerlude::{
+    CapturedContext, CodeLocation, CompiledTemplate, RsxTemplate, TemplateAttribute,
+    TemplateElement, TemplateNode,
+};
+
+#[cfg(feature = "profile")]
+pub use crate::innerlude::{
+    CycleKind, DirtyReason, ProfileCycle, ProfileReport, RenderRecord, Timing,
 };
 
 /// The purpose of this module is to alleviate imports of many common types
@@ -84,9 +126,13 @@ pub use crate::innerlude::{
 /// This includes types like [`Scope`], [`Element`], and [`Component`].
 pub mod prelude {
     pub use crate::innerlude::{
-        fc_to_builder, Attributes, Component, DioxusElement, Element, EventHandler, Fragment,
-        LazyNodes, NodeFactory, Properties, Scope, ScopeId, ScopeState, VNode, VirtualDom,
+        fc_to_builder, Attributes, Component, ContextKey, DioxusElement, Element, EventHandler,
+        Fragment, LazyNodes, Memo, NodeFactory, NodeRef, Portal, Properties, Scope, ScopeId,
+        ScopeState, Throw, VNode, VirtualDom,
     };
+
+    #[cfg(feature = "hot-reload")]
+    pub use crate::innerlude::{CapturedContext, CodeLocation, CompiledTemplate};
 }
 
 pub mod exports {
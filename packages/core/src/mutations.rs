@@ -6,7 +6,7 @@
 //! interpreters for these types of DomEdits.
 
 use crate::innerlude::*;
-use std::{any::Any, fmt::Debug};
+use std::fmt::Debug;
 
 /// ## Mutations
 ///
@@ -22,7 +22,7 @@ pub struct Mutations<'a> {
     /// The list of Scopes that were diffed, created, and removed during the Diff process.
     pub dirty_scopes: FxHashSet<ScopeId>,
 
-    /// The list of nodes to connect to the RealDOM.
+    /// The refs attached to elements that were created, which renderers need to provide handles for.
     pub refs: Vec<NodeRefMutation<'a>>,
 }
 
@@ -189,6 +189,32 @@ pub enum DomEdit<'bump> {
 
     /// Manually pop a root node from the stack.
     PopRoot {},
+
+    /// Push the renderer's mount point with the given name onto our stack.
+    ///
+    /// Portals use this to append their children outside of the main root. Renderers should fall back to creating
+    /// the mount point if it doesn't exist yet. Balanced by a [`DomEdit::PopRoot`].
+    PushNamedRoot {
+        /// The name of the mount point, ie the `id` of an element on the web.
+        name: &'bump str,
+    },
+
+    /// Pop the top node off the stack and save it as a template that can be cloned later.
+    ///
+    /// Only sent once [`VirtualDom::enable_templates`] has been called.
+    SaveTemplate {
+        /// The ID of the template, unique for the lifetime of the VirtualDom.
+        id: u64,
+    },
+
+    /// Deep-clone a saved template and push the clone onto the stack.
+    CloneTemplate {
+        /// The ID of the template to clone.
+        id: u64,
+
+        /// The ID to assign to the root of the clone.
+        root: u64,
+    },
 }
 
 use fxhash::FxHashSet;
@@ -214,6 +240,10 @@ impl<'a> Mutations<'a> {
         self.edits.push(PopRoot {});
     }
 
+    pub(crate) fn push_named_root(&mut self, name: &'a str) {
+        self.edits.push(PushNamedRoot { name });
+    }
+
     pub(crate) fn replace_with(&mut self, root: ElementId, m: u32) {
         let root = root.as_u64();
         self.edits.push(ReplaceWith { m, root });
@@ -262,6 +292,16 @@ impl<'a> Mutations<'a> {
         self.edits.push(CreatePlaceholder { root: id });
     }
 
+    // templates
+    pub(crate) fn save_template(&mut self, id: u64) {
+        self.edits.push(SaveTemplate { id });
+    }
+
+    pub(crate) fn clone_template(&mut self, id: u64, root: ElementId) {
+        let root = root.as_u64();
+        self.edits.push(CloneTemplate { id, root });
+    }
+
     // events
     pub(crate) fn new_event_listener(&mut self, listener: &Listener, scope: ScopeId) {
         let Listener {
@@ -315,14 +355,27 @@ impl<'a> Mutations<'a> {
         });
     }
 
+    pub(crate) fn node_ref(&mut self, node_ref: &'a NodeRef, element_id: ElementId) {
+        node_ref.mount(element_id);
+        self.refs.push(NodeRefMutation {
+            node_ref,
+            element_id,
+        });
+    }
+
     pub(crate) fn mark_dirty_scope(&mut self, scope: ScopeId) {
         self.dirty_scopes.insert(scope);
     }
 }
 
-// refs are only assigned once
+/// A [`NodeRef`] that was attached to a newly created element.
+///
+/// Once the edits have been applied, renderers should provide a handle to the element with [`NodeRef::set`].
 pub struct NodeRefMutation<'a> {
-    pub element: &'a mut Option<once_cell::sync::OnceCell<Box<dyn Any>>>,
+    /// The ref to provide the handle to
+    pub node_ref: &'a NodeRef,
+
+    /// The element the ref is attached to
     pub element_id: ElementId,
 }
 
@@ -333,18 +386,3 @@ impl<'a> std::fmt::Debug for NodeRefMutation<'a> {
             .finish()
     }
 }
-
-impl<'a> NodeRefMutation<'a> {
-    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
-        self.element
-            .as_ref()
-            .and_then(|f| f.get())
-            .and_then(|f| f.downcast_ref::<T>())
-    }
-    pub fn downcast_mut<T: 'static>(&mut self) -> Option<&mut T> {
-        self.element
-            .as_mut()
-            .and_then(|f| f.get_mut())
-            .and_then(|f| f.downcast_mut::<T>())
-    }
-}
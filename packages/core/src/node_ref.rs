@@ -0,0 +1,100 @@
+//! Handles to the real elements VNodes are mounted as.
+//!
+//! A [`NodeRef`] is attached to an element with the `node_ref` attribute in `rsx!`. Once the element is created, the
+//! VirtualDom assigns its [`ElementId`] and lists the ref in [`Mutations::refs`]. After applying the edits, the
+//! renderer fills in a handle to the element it created - a `web_sys::Element` on the web, for example.
+//!
+//! ```rust, ignore
+//! fn app(cx: Scope) -> Element {
+//!     let input = use_node_ref(&cx);
+//!
+//!     cx.render(rsx!(
+//!         input { node_ref: input }
+//!         button {
+//!             onclick: move |_| {
+//!                 if let Some(el) = input.get::<web_sys::HtmlElement>() {
+//!                     el.focus().unwrap();
+//!                 }
+//!             },
+//!             "focus"
+//!         }
+//!     ))
+//! }
+//! ```
+
+use crate::innerlude::*;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt::{Debug, Formatter},
+    rc::Rc,
+};
+
+/// A shared handle to the real element that a VNode was mounted as.
+///
+/// Cloning a `NodeRef` is cheap, and all clones point to the same element.
+#[derive(Clone, Default)]
+pub struct NodeRef {
+    inner: Rc<NodeRefInner>,
+}
+
+#[derive(Default)]
+struct NodeRefInner {
+    id: Cell<Option<ElementId>>,
+    handle: RefCell<Option<Rc<dyn Any>>>,
+}
+
+impl NodeRef {
+    /// Create a new `NodeRef` that isn't attached to any element
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The [`ElementId`] of the element the ref is attached to, if it's mounted.
+    pub fn id(&self) -> Option<ElementId> {
+        self.inner.id.get()
+    }
+
+    /// Get the handle the renderer provided for the element.
+    ///
+    /// Returns `None` if the element isn't mounted, the renderer hasn't provided a handle yet, or the handle isn't a
+    /// `T`.
+    pub fn get<T: 'static>(&self) -> Option<Rc<T>> {
+        let handle = self.inner.handle.borrow().clone()?;
+        handle.downcast().ok()
+    }
+
+    /// Provide the handle for the element the ref is attached to.
+    ///
+    /// This is called by renderers for each of the [`Mutations::refs`] once they've applied the edits.
+    pub fn set<T: 'static>(&self, handle: T) {
+        *self.inner.handle.borrow_mut() = Some(Rc::new(handle));
+    }
+
+    /// Check if two `NodeRef`s point to the same element
+    pub fn ptr_eq(&self, other: &NodeRef) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    pub(crate) fn mount(&self, id: ElementId) {
+        self.inner.id.set(Some(id));
+        self.inner.handle.borrow_mut().take();
+    }
+
+    /// Detach the ref from an element, unless it has already been attached to a different one.
+    pub(crate) fn unmount(&self, id: ElementId) {
+        if self.inner.id.get() == Some(id) {
+            self.inner.id.set(None);
+            self.inner.handle.borrow_mut().take();
+        }
+    }
+}
+
+impl Debug for NodeRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeRef")
+            .field("id", &self.inner.id.get())
+            .field("has_handle", &self.inner.handle.borrow().is_some())
+            .finish()
+    }
+}
@@ -3,8 +3,13 @@
 //! VNodes represent lazily-constructed VDom trees that support diffing and event handlers. These VNodes should be *very*
 //! cheap and *very* fast to construct - building a full tree should be quick.
 
+#[cfg(feature = "hot-reload")]
+use crate::innerlude::{CompiledTemplate, RsxTemplate};
 use crate::{
-    innerlude::{AttributeValue, ComponentPtr, Element, Properties, Scope, ScopeId, ScopeState},
+    innerlude::{
+        AttributeValue, ComponentPtr, Element, NodeRef, Properties, Scope, ScopeId, ScopeState,
+        TemplateBuilder, VTemplate,
+    },
     lazynodes::LazyNodes,
     AnyEvent, Component,
 };
@@ -113,6 +118,42 @@ pub enum VNode<'src> {
     /// }
     /// ```
     Placeholder(&'src VPlaceholder),
+
+    /// Template nodes are static subtrees that `rsx!` has hoisted out of the component.
+    ///
+    /// They are only generated once templates have been enabled on the VirtualDom - otherwise the subtree is built
+    /// in place as regular elements.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut vdom = VirtualDom::new();
+    /// vdom.enable_templates();
+    ///
+    /// let node = vdom.render_vnode(rsx!( div { class: "header", "hello" } ));
+    ///
+    /// if let VNode::Template(template) = node {
+    ///     assert_eq!(template.id.get(), None);
+    /// }
+    /// ```
+    Template(&'src VTemplate),
+
+    /// Portal nodes render their children into a different mount point of the renderer.
+    ///
+    /// The children are still diffed as part of the component that rendered them, and events from inside the portal
+    /// bubble up through the portal's position in the VirtualDom, not through the mount point. A placeholder is left
+    /// where the portal is rendered.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// rsx!{
+    ///     Portal { target: "modals",
+    ///         div { "rendered into the modals root" }
+    ///     }
+    /// }
+    /// ```
+    Portal(&'src VPortal<'src>),
 }
 
 impl<'src> VNode<'src> {
@@ -124,6 +165,8 @@ impl<'src> VNode<'src> {
             VNode::Fragment(f) => f.key,
             VNode::Text(_t) => None,
             VNode::Placeholder(_f) => None,
+            VNode::Template(_t) => None,
+            VNode::Portal(p) => p.key,
         }
     }
 
@@ -142,6 +185,8 @@ impl<'src> VNode<'src> {
             VNode::Text(el) => el.id.get(),
             VNode::Element(el) => el.id.get(),
             VNode::Placeholder(el) => el.id.get(),
+            VNode::Template(el) => el.id.get(),
+            VNode::Portal(el) => el.id.get(),
             VNode::Fragment(_) => None,
             VNode::Component(_) => None,
         }
@@ -154,7 +199,9 @@ impl<'src> VNode<'src> {
             VNode::Element(e) => VNode::Element(e),
             VNode::Component(c) => VNode::Component(c),
             VNode::Placeholder(a) => VNode::Placeholder(a),
+            VNode::Template(t) => VNode::Template(t),
             VNode::Fragment(f) => VNode::Fragment(f),
+            VNode::Portal(p) => VNode::Portal(p),
         }
     }
 }
@@ -179,6 +226,11 @@ impl Debug for VNode<'_> {
                 .debug_struct("VNode::Placholder")
                 .field("id", &t.id)
                 .finish(),
+            VNode::Template(t) => s
+                .debug_struct("VNode::Template")
+                .field("build", &(t.build as *const ()))
+                .field("id", &t.id)
+                .finish(),
             VNode::Fragment(frag) => s
                 .debug_struct("VNode::Fragment")
                 .field("children", &frag.children)
@@ -190,6 +242,13 @@ impl Debug for VNode<'_> {
                 .field("key", &comp.key)
                 .field("scope", &comp.scope)
                 .finish(),
+            VNode::Portal(portal) => s
+                .debug_struct("VNode::Portal")
+                .field("target", &portal.target)
+                .field("key", &portal.key)
+                .field("children", &portal.children)
+                .field("id", &portal.id)
+                .finish(),
         }
     }
 }
@@ -198,6 +257,7 @@ impl Debug for VNode<'_> {
 ///
 /// `ElementId` is a `usize` that is unique across the entire VirtualDOM - but not unique across time. If a component is
 /// unmounted, then the `ElementId` will be reused for a new component.
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct ElementId(pub usize);
 impl std::fmt::Display for ElementId {
@@ -247,6 +307,26 @@ pub struct VFragment<'src> {
     pub children: &'src [VNode<'src>],
 }
 
+/// A list of VNodes rendered into a different mount point of the renderer.
+pub struct VPortal<'src> {
+    /// The [`ElementId`] of the placeholder left where the portal is rendered.
+    pub id: Cell<Option<ElementId>>,
+
+    /// The key of the portal to be used during keyed diffing.
+    pub key: Option<&'src str>,
+
+    /// The name of the mount point the children are rendered into.
+    pub target: &'src str,
+
+    /// The parent of the portal (if any).
+    ///
+    /// Events from inside the portal bubble up to it.
+    pub parent: Cell<Option<ElementId>>,
+
+    /// Portals can never have zero children. Enforced by NodeFactory.
+    pub children: &'src [VNode<'src>],
+}
+
 /// An element like a "div" with children, listeners, and attributes.
 pub struct VElement<'a> {
     /// The [`ElementId`] of the VText.
@@ -278,6 +358,9 @@ pub struct VElement<'a> {
 
     /// The children of the VElement.
     pub children: &'a [VNode<'a>],
+
+    /// The ref to provide a handle to the real element to (if any).
+    pub node_ref: Option<&'a NodeRef>,
 }
 
 impl Debug for VElement<'_> {
@@ -291,6 +374,7 @@ impl Debug for VElement<'_> {
             .field("listeners", &self.listeners.len())
             .field("attributes", &self.attributes)
             .field("children", &self.children)
+            .field("node_ref", &self.node_ref)
             .finish()
     }
 }
@@ -569,6 +653,27 @@ impl<'a> NodeFactory<'a> {
         )
     }
 
+    /// Create a new [`VNode::VElement`] that provides a handle to the real element to `node_ref` once it's mounted
+    pub fn element_with_ref(
+        &self,
+        el: impl DioxusElement,
+        listeners: &'a [Listener<'a>],
+        attributes: &'a [Attribute<'a>],
+        children: &'a [VNode<'a>],
+        key: Option<Arguments>,
+        node_ref: &'a NodeRef,
+    ) -> VNode<'a> {
+        self.new_element(
+            el.tag_name(),
+            el.namespace(),
+            listeners,
+            attributes,
+            children,
+            key,
+            Some(node_ref),
+        )
+    }
+
     /// Create a new [`VNode::VElement`] without the trait bound
     ///
     /// IE pass in "div" instead of `div`
@@ -580,6 +685,22 @@ impl<'a> NodeFactory<'a> {
         attributes: &'a [Attribute<'a>],
         children: &'a [VNode<'a>],
         key: Option<Arguments>,
+    ) -> VNode<'a> {
+        self.new_element(
+            tag_name, namespace, listeners, attributes, children, key, None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new_element(
+        &self,
+        tag_name: &'static str,
+        namespace: Option<&'static str>,
+        listeners: &'a [Listener<'a>],
+        attributes: &'a [Attribute<'a>],
+        children: &'a [VNode<'a>],
+        key: Option<Arguments>,
+        node_ref: Option<&'a NodeRef>,
     ) -> VNode<'a> {
         let key = key.map(|f| self.raw_text(f).0);
 
@@ -598,9 +719,40 @@ impl<'a> NodeFactory<'a> {
             children,
             id: empty_cell(),
             parent: empty_cell(),
+            node_ref,
+        }))
+    }
+
+    /// Create a static subtree that's built once and cloned by the renderer.
+    ///
+    /// `build` must always return the same tree - templates have no dynamic parts, and two instances with the same
+    /// `build` are never diffed beyond their root. If templates haven't been enabled on the VirtualDom, the tree is
+    /// built in place instead.
+    pub fn template(&self, build: TemplateBuilder) -> VNode<'a> {
+        if !self.scope.templates.enabled.get() {
+            return build(*self);
+        }
+
+        VNode::Template(self.bump.alloc(VTemplate {
+            build,
+            id: empty_cell(),
+            parent: empty_cell(),
         }))
     }
 
+    /// Get the template that replaces a compiled `rsx!` call, if hot reloading has sent one.
+    ///
+    /// Always returns `None` in release builds.
+    #[cfg(feature = "hot-reload")]
+    #[doc(hidden)]
+    pub fn hot_template(&self, compiled: &CompiledTemplate) -> Option<std::rc::Rc<RsxTemplate>> {
+        if !cfg!(debug_assertions) {
+            return None;
+        }
+
+        self.scope.hot_reload.get(compiled, self.scope.scope_id())
+    }
+
     /// Create a new [`Attribute`]
     pub fn attr(
         &self,
@@ -745,6 +897,35 @@ impl<'a> NodeFactory<'a> {
         }
     }
 
+    /// Create a new [`VNode::Portal`] that renders its children into the mount point named `target`
+    pub fn portal(
+        self,
+        target: &'a str,
+        key: Option<Arguments>,
+        node_iter: impl IntoIterator<Item = impl IntoVNode<'a>>,
+    ) -> VNode<'a> {
+        let mut nodes = bumpalo::collections::Vec::new_in(self.bump);
+
+        for node in node_iter {
+            nodes.push(node.into_vnode(self));
+        }
+
+        // the children need at least one real node so the portal can be diffed like a fragment
+        if nodes.is_empty() {
+            nodes.push(VNode::Placeholder(
+                self.bump.alloc(VPlaceholder { id: empty_cell() }),
+            ));
+        }
+
+        VNode::Portal(self.bump.alloc(VPortal {
+            id: empty_cell(),
+            key: key.map(|f| self.raw_text(f).0),
+            target,
+            parent: empty_cell(),
+            children: nodes.into_bump_slice(),
+        }))
+    }
+
     /// Create a new [`VNode`] from any iterator of children
     pub fn create_children(
         self,
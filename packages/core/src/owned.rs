@@ -0,0 +1,453 @@
+//! Owned, `'static` copies of [`Mutations`].
+//!
+//! [`Mutations`] borrow their strings from the bump arenas of the components that produced them, so they have to be
+//! applied before the VirtualDom does any more work. Converting them into [`MutationsOwned`] lets a renderer send
+//! batches to another thread, queue them, or keep them around for later.
+//!
+//! Tags, attribute names, namespaces, and event names are interned by an [`EditInterner`], so converting a batch only
+//! allocates for text content and text attribute values. Keep the same interner around to share strings across
+//! batches.
+
+use crate::innerlude::*;
+use fxhash::FxHashSet;
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+/// An owned copy of [`Mutations`] that can be sent across threads.
+///
+/// NodeRefs point into the VirtualDom, so they aren't carried over.
+///
+/// A serialized batch keeps the type of every attribute value next to its edits, so batches can be stored and read
+/// back exactly. A lone [`DomEditOwned`] serializes its value the way the interpreters expect, which loses the type.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "StoredMutations", into = "StoredMutations")
+)]
+pub struct MutationsOwned {
+    /// The list of edits that need to be applied for the RealDOM to match the VirtualDOM.
+    pub edits: Vec<DomEditOwned>,
+
+    /// The list of Scopes that were diffed, created, and removed during the Diff process.
+    pub dirty_scopes: FxHashSet<ScopeId>,
+}
+
+/// An owned copy of a [`DomEdit`].
+///
+/// See [`DomEdit`] for what each edit does.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serialize",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type")
+)]
+#[allow(missing_docs)]
+pub enum DomEditOwned {
+    PushRoot {
+        root: u64,
+    },
+    AppendChildren {
+        many: u32,
+    },
+    ReplaceWith {
+        root: u64,
+        m: u32,
+    },
+    InsertAfter {
+        root: u64,
+        n: u32,
+    },
+    InsertBefore {
+        root: u64,
+        n: u32,
+    },
+    Remove {
+        root: u64,
+    },
+    CreateTextNode {
+        root: u64,
+        text: Arc<str>,
+    },
+    CreateElement {
+        root: u64,
+        tag: Arc<str>,
+    },
+    CreateElementNs {
+        root: u64,
+        tag: Arc<str>,
+        ns: Arc<str>,
+    },
+    CreatePlaceholder {
+        root: u64,
+    },
+    NewEventListener {
+        event_name: Arc<str>,
+        scope: ScopeId,
+        root: u64,
+    },
+    RemoveEventListener {
+        root: u64,
+        event: Arc<str>,
+    },
+    SetText {
+        root: u64,
+        text: Arc<str>,
+    },
+    SetAttribute {
+        root: u64,
+        field: Arc<str>,
+        value: AttributeValueOwned,
+        ns: Option<Arc<str>>,
+    },
+    RemoveAttribute {
+        root: u64,
+        name: Arc<str>,
+        ns: Option<Arc<str>>,
+    },
+    PopRoot {},
+    PushNamedRoot {
+        name: Arc<str>,
+    },
+    SaveTemplate {
+        id: u64,
+    },
+    CloneTemplate {
+        id: u64,
+        root: u64,
+    },
+}
+
+/// An owned copy of an [`AttributeValue`].
+///
+/// [`AttributeValue::Any`] only borrows its value, so it's owned in its serialized form. That only works for types
+/// registered with [`register_attribute_value`].
+///
+/// Values serialize untagged, like [`AttributeValue`], so deserializing one on its own picks the first variant that
+/// fits - a `Uint64` can come back as a `Float32`, and three `Bytes` as a `Vec3Float`. Serialize whole
+/// [`MutationsOwned`] batches to keep the types.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serialize", serde(untagged))]
+#[allow(missing_docs)]
+pub enum AttributeValueOwned {
+    Text(Arc<str>),
+    Float32(f32),
+    Float64(f64),
+    Int32(i32),
+    Int64(i64),
+    Uint32(u32),
+    Uint64(u64),
+    Bool(bool),
+
+    Vec3Float(f32, f32, f32),
+    Vec3Int(i32, i32, i32),
+    Vec3Uint(u32, u32, u32),
+
+    Vec4Float(f32, f32, f32, f32),
+    Vec4Int(i32, i32, i32, i32),
+    Vec4Uint(u32, u32, u32, u32),
+
+    Bytes(Arc<[u8]>),
+    Custom(CustomValue),
+}
+
+/// The serialized form of [`MutationsOwned`]: the edits as the interpreters read them, plus the value of every
+/// `SetAttribute` tagged with its type.
+#[cfg(feature = "serialize")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct StoredMutations {
+    edits: Vec<DomEditOwned>,
+    dirty_scopes: FxHashSet<ScopeId>,
+    #[serde(default)]
+    values: Vec<TaggedValue>,
+}
+
+#[cfg(feature = "serialize")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct TaggedValue(#[serde(with = "TaggedValueDef")] AttributeValueOwned);
+
+#[cfg(feature = "serialize")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(remote = "AttributeValueOwned")]
+enum TaggedValueDef {
+    Text(Arc<str>),
+    Float32(f32),
+    Float64(f64),
+    Int32(i32),
+    Int64(i64),
+    Uint32(u32),
+    Uint64(u64),
+    Bool(bool),
+    Vec3Float(f32, f32, f32),
+    Vec3Int(i32, i32, i32),
+    Vec3Uint(u32, u32, u32),
+    Vec4Float(f32, f32, f32, f32),
+    Vec4Int(i32, i32, i32, i32),
+    Vec4Uint(u32, u32, u32, u32),
+    Bytes(Arc<[u8]>),
+    Custom(CustomValue),
+}
+
+#[cfg(feature = "serialize")]
+impl From<MutationsOwned> for StoredMutations {
+    fn from(mutations: MutationsOwned) -> Self {
+        let values = mutations
+            .edits
+            .iter()
+            .filter_map(|edit| match edit {
+                DomEditOwned::SetAttribute { value, .. } => Some(TaggedValue(value.clone())),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            edits: mutations.edits,
+            dirty_scopes: mutations.dirty_scopes,
+            values,
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl From<StoredMutations> for MutationsOwned {
+    fn from(stored: StoredMutations) -> Self {
+        let mut edits = stored.edits;
+
+        // batches stored without their tagged values keep whatever the untagged values decoded to
+        let attributes = edits.iter_mut().filter_map(|edit| match edit {
+            DomEditOwned::SetAttribute { value, .. } => Some(value),
+            _ => None,
+        });
+        for (value, TaggedValue(tagged)) in attributes.zip(stored.values) {
+            *value = tagged;
+        }
+
+        Self {
+            edits,
+            dirty_scopes: stored.dirty_scopes,
+        }
+    }
+}
+
+impl AttributeValueOwned {
+    /// Borrow the value as an [`AttributeValue`] to reuse code written against borrowed edits.
+    pub fn as_borrowed(&self) -> AttributeValue<'_> {
+        match self {
+            AttributeValueOwned::Text(a) => AttributeValue::Text(a),
+            AttributeValueOwned::Float32(a) => AttributeValue::Float32(*a),
+            AttributeValueOwned::Float64(a) => AttributeValue::Float64(*a),
+            AttributeValueOwned::Int32(a) => AttributeValue::Int32(*a),
+            AttributeValueOwned::Int64(a) => AttributeValue::Int64(*a),
+            AttributeValueOwned::Uint32(a) => AttributeValue::Uint32(*a),
+            AttributeValueOwned::Uint64(a) => AttributeValue::Uint64(*a),
+            AttributeValueOwned::Bool(a) => AttributeValue::Bool(*a),
+            AttributeValueOwned::Vec3Float(x, y, z) => AttributeValue::Vec3Float(*x, *y, *z),
+            AttributeValueOwned::Vec3Int(x, y, z) => AttributeValue::Vec3Int(*x, *y, *z),
+            AttributeValueOwned::Vec3Uint(x, y, z) => AttributeValue::Vec3Uint(*x, *y, *z),
+            AttributeValueOwned::Vec4Float(x, y, z, w) => AttributeValue::Vec4Float(*x, *y, *z, *w),
+            AttributeValueOwned::Vec4Int(x, y, z, w) => AttributeValue::Vec4Int(*x, *y, *z, *w),
+            AttributeValueOwned::Vec4Uint(x, y, z, w) => AttributeValue::Vec4Uint(*x, *y, *z, *w),
+            AttributeValueOwned::Bytes(a) => AttributeValue::Bytes(a),
+            AttributeValueOwned::Custom(a) => AttributeValue::Any(ArbitraryAttributeValue::new(a)),
+        }
+    }
+}
+
+/// An error encountered while converting edits into their owned form.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToOwnedError {
+    /// An [`AttributeValue::Any`] holds a type that isn't registered with [`register_attribute_value`]
+    UnsupportedValue {
+        /// The node the attribute was set on
+        root: u64,
+
+        /// The name of the attribute
+        field: &'static str,
+    },
+}
+
+impl Display for ToOwnedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToOwnedError::UnsupportedValue { root, field } => write!(
+                f,
+                "attribute \"{}\" on node {} holds an arbitrary value, which can't be owned",
+                field, root
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ToOwnedError {}
+
+impl Mutations<'_> {
+    /// Convert the mutations into an owned form with a fresh [`EditInterner`].
+    ///
+    /// Fails if any attribute holds an [`AttributeValue::Any`] that isn't registered with
+    /// [`register_attribute_value`].
+    pub fn try_to_owned(&self) -> Result<MutationsOwned, ToOwnedError> {
+        EditInterner::new().mutations(self)
+    }
+}
+
+/// Converts edits into their owned form, remembering every name it has interned.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let mut dom = VirtualDom::new(App);
+/// let mut interner = EditInterner::new();
+/// let (tx, rx) = std::sync::mpsc::channel();
+///
+/// std::thread::spawn(move || {
+///     for mutations in rx {
+///         apply(mutations);
+///     }
+/// });
+///
+/// tx.send(interner.mutations(&dom.rebuild())?);
+///
+/// loop {
+///     dom.wait_for_work().await;
+///     for mutations in dom.work_with_deadline(|| false) {
+///         tx.send(interner.mutations(&mutations)?);
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct EditInterner {
+    strings: FxHashSet<Arc<str>>,
+}
+
+impl EditInterner {
+    /// Create an interner with no strings in it
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget every interned string.
+    pub fn reset(&mut self) {
+        self.strings.clear();
+    }
+
+    /// Get a shared copy of the string, allocating it the first time it's seen
+    pub fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(string);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    /// Convert a batch of mutations into its owned form.
+    pub fn mutations(&mut self, mutations: &Mutations) -> Result<MutationsOwned, ToOwnedError> {
+        let edits = mutations
+            .edits
+            .iter()
+            .map(|edit| self.edit(edit))
+            .collect::<Result<_, _>>()?;
+
+        Ok(MutationsOwned {
+            edits,
+            dirty_scopes: mutations.dirty_scopes.clone(),
+        })
+    }
+
+    /// Convert a single edit into its owned form.
+    pub fn edit(&mut self, edit: &DomEdit) -> Result<DomEditOwned, ToOwnedError> {
+        Ok(match *edit {
+            DomEdit::PushRoot { root } => DomEditOwned::PushRoot { root },
+            DomEdit::AppendChildren { many } => DomEditOwned::AppendChildren { many },
+            DomEdit::ReplaceWith { root, m } => DomEditOwned::ReplaceWith { root, m },
+            DomEdit::InsertAfter { root, n } => DomEditOwned::InsertAfter { root, n },
+            DomEdit::InsertBefore { root, n } => DomEditOwned::InsertBefore { root, n },
+            DomEdit::Remove { root } => DomEditOwned::Remove { root },
+            DomEdit::CreateTextNode { root, text } => DomEditOwned::CreateTextNode {
+                root,
+                text: Arc::from(text),
+            },
+            DomEdit::CreateElement { root, tag } => DomEditOwned::CreateElement {
+                root,
+                tag: self.intern(tag),
+            },
+            DomEdit::CreateElementNs { root, tag, ns } => DomEditOwned::CreateElementNs {
+                root,
+                tag: self.intern(tag),
+                ns: self.intern(ns),
+            },
+            DomEdit::CreatePlaceholder { root } => DomEditOwned::CreatePlaceholder { root },
+            DomEdit::NewEventListener {
+                event_name,
+                scope,
+                root,
+            } => DomEditOwned::NewEventListener {
+                event_name: self.intern(event_name),
+                scope,
+                root,
+            },
+            DomEdit::RemoveEventListener { root, event } => DomEditOwned::RemoveEventListener {
+                root,
+                event: self.intern(event),
+            },
+            DomEdit::SetText { root, text } => DomEditOwned::SetText {
+                root,
+                text: Arc::from(text),
+            },
+            DomEdit::SetAttribute {
+                root,
+                field,
+                ref value,
+                ns,
+            } => DomEditOwned::SetAttribute {
+                root,
+                field: self.intern(field),
+                value: owned_value(value, root, field)?,
+                ns: ns.map(|ns| self.intern(ns)),
+            },
+            DomEdit::RemoveAttribute { root, name, ns } => DomEditOwned::RemoveAttribute {
+                root,
+                name: self.intern(name),
+                ns: ns.map(|ns| self.intern(ns)),
+            },
+            DomEdit::PopRoot {} => DomEditOwned::PopRoot {},
+            DomEdit::PushNamedRoot { name } => DomEditOwned::PushNamedRoot {
+                name: self.intern(name),
+            },
+            DomEdit::SaveTemplate { id } => DomEditOwned::SaveTemplate { id },
+            DomEdit::CloneTemplate { id, root } => DomEditOwned::CloneTemplate { id, root },
+        })
+    }
+}
+
+fn owned_value(
+    value: &AttributeValue,
+    root: u64,
+    field: &'static str,
+) -> Result<AttributeValueOwned, ToOwnedError> {
+    Ok(match *value {
+        AttributeValue::Text(a) => AttributeValueOwned::Text(Arc::from(a)),
+        AttributeValue::Float32(a) => AttributeValueOwned::Float32(a),
+        AttributeValue::Float64(a) => AttributeValueOwned::Float64(a),
+        AttributeValue::Int32(a) => AttributeValueOwned::Int32(a),
+        AttributeValue::Int64(a) => AttributeValueOwned::Int64(a),
+        AttributeValue::Uint32(a) => AttributeValueOwned::Uint32(a),
+        AttributeValue::Uint64(a) => AttributeValueOwned::Uint64(a),
+        AttributeValue::Bool(a) => AttributeValueOwned::Bool(a),
+        AttributeValue::Vec3Float(x, y, z) => AttributeValueOwned::Vec3Float(x, y, z),
+        AttributeValue::Vec3Int(x, y, z) => AttributeValueOwned::Vec3Int(x, y, z),
+        AttributeValue::Vec3Uint(x, y, z) => AttributeValueOwned::Vec3Uint(x, y, z),
+        AttributeValue::Vec4Float(x, y, z, w) => AttributeValueOwned::Vec4Float(x, y, z, w),
+        AttributeValue::Vec4Int(x, y, z, w) => AttributeValueOwned::Vec4Int(x, y, z, w),
+        AttributeValue::Vec4Uint(x, y, z, w) => AttributeValueOwned::Vec4Uint(x, y, z, w),
+        AttributeValue::Bytes(a) => AttributeValueOwned::Bytes(Arc::from(a)),
+        AttributeValue::Any(a) => match a.to_custom() {
+            Some(custom) => AttributeValueOwned::Custom(custom),
+            None => return Err(ToOwnedError::UnsupportedValue { root, field }),
+        },
+    })
+}
@@ -0,0 +1,334 @@
+//! Per-component render profiling, enabled with the `profile` feature.
+//!
+//! While profiling, every call to [`VirtualDom::rebuild`] and [`VirtualDom::work_with_deadline`] is recorded as a
+//! [`ProfileCycle`]. Each cycle lists the scopes that rendered, why they were dirty, how long their render and diff
+//! took, and how many edits they produced. The resulting [`ProfileReport`] can be queried directly or dumped as a
+//! Chrome trace-event file and opened in `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+//!
+//! Timings use [`std::time::Instant`], so this feature is not available on `wasm32-unknown-unknown`.
+
+use crate::innerlude::*;
+use fxhash::FxHashMap;
+use std::{
+    fmt::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Why a scope was rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirtyReason {
+    /// The scope scheduled an update for itself with [`SchedulerMsg::Immediate`], usually through a hook
+    Immediate,
+
+    /// The scope scheduled an update while an event listener was running, IE "onclick"
+    Event(&'static str),
+
+    /// The parent scope re-rendered and passed down props that weren't memoized
+    ParentRender(ScopeId),
+
+    /// The scope was rendered for the first time
+    Mount,
+}
+
+/// When something started and how long it took.
+///
+/// Start times are relative to the creation of the [`VirtualDom`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timing {
+    /// When the work started
+    pub start: Duration,
+
+    /// How long the work took
+    pub duration: Duration,
+}
+
+/// A single render of a scope and the diff of its output.
+#[derive(Clone, Debug)]
+pub struct RenderRecord {
+    /// The scope that rendered
+    pub scope: ScopeId,
+
+    /// The name of the component, if known
+    pub name: Option<&'static str>,
+
+    /// Why the scope was rendered
+    pub reason: DirtyReason,
+
+    /// The call to the component function
+    pub render: Timing,
+
+    /// The diff of the component's output, including the renders and diffs of any children it updated
+    pub diff: Timing,
+
+    /// The number of edits produced by the diff, including those produced by its children
+    pub edits: usize,
+}
+
+/// The kind of work a [`ProfileCycle`] covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CycleKind {
+    /// A call to [`VirtualDom::rebuild`]
+    Rebuild,
+
+    /// A call to [`VirtualDom::work_with_deadline`]
+    Work,
+}
+
+/// Everything that happened during a single call to [`VirtualDom::rebuild`] or [`VirtualDom::work_with_deadline`].
+#[derive(Clone, Debug)]
+pub struct ProfileCycle {
+    /// The call that was profiled
+    pub kind: CycleKind,
+
+    /// The whole call, from start to finish
+    pub timing: Timing,
+
+    /// Every render that happened during the cycle, in the order the renders started
+    pub renders: Vec<RenderRecord>,
+
+    /// Every scope whose parent re-rendered but whose props were memoized, skipping its render
+    pub memoized: Vec<ScopeId>,
+
+    /// The number of edits returned to the renderer
+    pub edits: usize,
+}
+
+/// The profile recorded by a [`VirtualDom`].
+///
+/// Cycles that didn't render anything are not recorded.
+///
+/// # Example
+///
+/// ```rust, ignore
+/// let mut dom = VirtualDom::new(App);
+/// dom.rebuild();
+///
+/// let report = dom.take_profile();
+/// for render in report.slowest_renders(5) {
+///     println!("{:?} took {:?} because of {:?}", render.name, render.render.duration, render.reason);
+/// }
+///
+/// report.write_chrome_trace("trace.json")?;
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ProfileReport {
+    /// Every cycle that was recorded, oldest first
+    pub cycles: Vec<ProfileCycle>,
+}
+
+impl ProfileReport {
+    /// Iterate over every render in the report
+    pub fn renders(&self) -> impl Iterator<Item = &RenderRecord> {
+        self.cycles.iter().flat_map(|cycle| cycle.renders.iter())
+    }
+
+    /// Iterate over every render of a single scope
+    pub fn renders_of(&self, scope: ScopeId) -> impl Iterator<Item = &RenderRecord> {
+        self.renders().filter(move |render| render.scope == scope)
+    }
+
+    /// Get the `n` renders that spent the longest time in the component function, slowest first
+    pub fn slowest_renders(&self, n: usize) -> Vec<&RenderRecord> {
+        let mut renders: Vec<_> = self.renders().collect();
+        renders.sort_by_key(|render| std::cmp::Reverse(render.render.duration));
+        renders.truncate(n);
+        renders
+    }
+
+    /// The number of renders that were skipped because the component's props were memoized
+    pub fn skipped_renders(&self) -> usize {
+        self.cycles.iter().map(|cycle| cycle.memoized.len()).sum()
+    }
+
+    /// The total number of edits returned to the renderer across every cycle
+    pub fn total_edits(&self) -> usize {
+        self.cycles.iter().map(|cycle| cycle.edits).sum()
+    }
+
+    /// Format the report as Chrome trace-event JSON.
+    ///
+    /// Cycles, renders, and diffs are written as complete ("X") events, so nested renders show up beneath the diff
+    /// of the parent that triggered them.
+    pub fn to_chrome_trace(&self) -> String {
+        let mut events = Vec::new();
+
+        for cycle in &self.cycles {
+            let name = match cycle.kind {
+                CycleKind::Rebuild => "rebuild",
+                CycleKind::Work => "work_with_deadline",
+            };
+            let args = format!(
+                r#"{{"renders":{},"memoized":{},"edits":{}}}"#,
+                cycle.renders.len(),
+                cycle.memoized.len(),
+                cycle.edits
+            );
+            events.push(trace_event(name, "cycle", cycle.timing, &args));
+
+            for render in &cycle.renders {
+                let label = match render.name {
+                    Some(name) => format!("{} ({:?})", name, render.scope),
+                    None => format!("{:?}", render.scope),
+                };
+                let args = format!(
+                    r#"{{"scope":{},"reason":"{}","edits":{}}}"#,
+                    render.scope.0,
+                    escape(&format!("{:?}", render.reason)),
+                    render.edits
+                );
+
+                events.push(trace_event(
+                    &format!("render {}", label),
+                    "render",
+                    render.render,
+                    &args,
+                ));
+                events.push(trace_event(
+                    &format!("diff {}", label),
+                    "diff",
+                    render.diff,
+                    &args,
+                ));
+            }
+        }
+
+        format!(r#"{{"traceEvents":[{}]}}"#, events.join(","))
+    }
+
+    /// Write the report to a file as Chrome trace-event JSON.
+    ///
+    /// See [`ProfileReport::to_chrome_trace`].
+    pub fn write_chrome_trace(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_chrome_trace())
+    }
+}
+
+fn trace_event(name: &str, category: &str, timing: Timing, args: &str) -> String {
+    format!(
+        r#"{{"name":"{}","cat":"{}","ph":"X","ts":{:.3},"dur":{:.3},"pid":1,"tid":1,"args":{}}}"#,
+        escape(name),
+        category,
+        timing.start.as_secs_f64() * 1_000_000.0,
+        timing.duration.as_secs_f64() * 1_000_000.0,
+        args
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Collects render and diff timings as the VirtualDom works.
+pub(crate) struct Profiler {
+    epoch: Instant,
+    cycle: Option<ProfileCycle>,
+    reasons: FxHashMap<ScopeId, DirtyReason>,
+    names: FxHashMap<ScopeId, &'static str>,
+    pub report: ProfileReport,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            cycle: None,
+            reasons: FxHashMap::default(),
+            names: FxHashMap::default(),
+            report: ProfileReport::default(),
+        }
+    }
+
+    fn timing(&self, start: Instant) -> Timing {
+        Timing {
+            start: start.duration_since(self.epoch),
+            duration: start.elapsed(),
+        }
+    }
+
+    pub fn begin_cycle(&mut self, kind: CycleKind) {
+        self.cycle = Some(ProfileCycle {
+            kind,
+            timing: Timing {
+                start: self.epoch.elapsed(),
+                duration: Duration::ZERO,
+            },
+            renders: Vec::new(),
+            memoized: Vec::new(),
+            edits: 0,
+        });
+    }
+
+    pub fn end_cycle(&mut self, edits: usize) {
+        if let Some(mut cycle) = self.cycle.take() {
+            if cycle.renders.is_empty() && edits == 0 {
+                return;
+            }
+
+            cycle.timing.duration = self.epoch.elapsed() - cycle.timing.start;
+            cycle.edits = edits;
+            self.report.cycles.push(cycle);
+        }
+    }
+
+    /// Note why a scope is dirty. The first reason wins until the scope renders.
+    pub fn mark_dirty(&mut self, id: ScopeId, reason: DirtyReason) {
+        self.reasons.entry(id).or_insert(reason);
+    }
+
+    /// Note that a scope is being created - scope IDs are reused, so this replaces anything left over
+    pub fn mark_mounted(&mut self, id: ScopeId, name: Option<&'static str>) {
+        self.reasons.insert(id, DirtyReason::Mount);
+
+        match name {
+            Some(name) => self.names.insert(id, name),
+            None => self.names.remove(&id),
+        };
+    }
+
+    pub fn record_render(&mut self, id: ScopeId, start: Instant) {
+        let reason = self.reasons.remove(&id).unwrap_or(DirtyReason::Immediate);
+        let render = self.timing(start);
+        let name = self.names.get(&id).copied();
+
+        if let Some(cycle) = self.cycle.as_mut() {
+            cycle.renders.push(RenderRecord {
+                scope: id,
+                name,
+                reason,
+                render,
+                diff: Timing::default(),
+                edits: 0,
+            });
+        }
+    }
+
+    pub fn record_memoized(&mut self, id: ScopeId) {
+        if let Some(cycle) = self.cycle.as_mut() {
+            cycle.memoized.push(id);
+        }
+    }
+
+    pub fn record_diff(&mut self, id: ScopeId, start: Instant, edits: usize) {
+        let diff = self.timing(start);
+
+        let record = self
+            .cycle
+            .as_mut()
+            .and_then(|cycle| cycle.renders.iter_mut().rev().find(|r| r.scope == id));
+
+        if let Some(record) = record {
+            record.diff = diff;
+            record.edits = edits;
+        }
+    }
+}
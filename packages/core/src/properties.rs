@@ -1,4 +1,5 @@
 use crate::innerlude::*;
+use std::{fmt::Debug, ops::Deref, rc::Rc};
 
 pub struct FragmentProps<'a>(Element<'a>);
 pub struct FragmentBuilder<'a, const BUILT: bool>(Element<'a>);
@@ -95,6 +96,69 @@ pub fn Fragment<'a>(cx: Scope<'a, FragmentProps<'a>>) -> Element {
     cx.render(LazyNodes::new(|f| f.fragment_from_iter(i)))
 }
 
+pub struct PortalProps<'a> {
+    target: &'a str,
+    children: Element<'a>,
+}
+pub struct PortalBuilder<'a, const TARGET: bool>(PortalProps<'a>);
+impl<'a, const TARGET: bool> PortalBuilder<'a, TARGET> {
+    pub fn target(self, target: &'a str) -> PortalBuilder<'a, true> {
+        PortalBuilder(PortalProps { target, ..self.0 })
+    }
+    pub fn children(self, children: Element<'a>) -> Self {
+        PortalBuilder(PortalProps { children, ..self.0 })
+    }
+}
+impl<'a> PortalBuilder<'a, true> {
+    pub fn build(self) -> PortalProps<'a> {
+        self.0
+    }
+}
+
+impl<'a> Properties for PortalProps<'a> {
+    type Builder = PortalBuilder<'a, false>;
+    const IS_STATIC: bool = false;
+    fn builder() -> Self::Builder {
+        PortalBuilder(PortalProps {
+            target: "",
+            children: None,
+        })
+    }
+    unsafe fn memoize(&self, _other: &Self) -> bool {
+        false
+    }
+}
+
+/// Render children into a different mount point of the renderer.
+///
+/// ## Details
+///
+/// Portals are useful for modals, tooltips, and toasts that need to escape the layout or overflow of the component
+/// that renders them. The children are still diffed as part of that component, and events from inside the portal
+/// bubble up through the component tree, not through the mount point.
+///
+/// The `target` names the mount point. On the web and desktop, it's the `id` of an element, which is created at the end
+/// of the body if it doesn't exist. A hidden placeholder is left where the portal is rendered.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// rsx!{
+///     div { onclick: move |_| show.set(false),
+///         Portal { target: "modals",
+///             div { class: "modal", "Click anywhere to close" }
+///         }
+///     }
+/// }
+/// ```
+#[allow(non_upper_case_globals, non_snake_case)]
+pub fn Portal<'a>(cx: Scope<'a, PortalProps<'a>>) -> Element<'a> {
+    let children = cx.props.children.as_ref().map(|f| f.decouple());
+    cx.render(LazyNodes::new(|f| {
+        f.portal(cx.props.target, None, children)
+    }))
+}
+
 /// Every "Props" used for a component must implement the `Properties` trait. This trait gives some hints to Dioxus
 /// on how to memoize the props and some additional optimizations that can be made. We strongly encourage using the
 /// derive macro to implement the `Properties` trait automatically as guarantee that your memoization strategy is safe.
@@ -127,6 +191,34 @@ pub fn Fragment<'a>(cx: Scope<'a, FragmentProps<'a>>) -> Element {
 ///     data: &'a str
 /// }
 /// ```
+///
+/// ## Tuning memoization
+///
+/// The derive macro accepts a few attributes to change how 'static props are compared:
+///
+/// - `#[props(no_memo)]` on the struct always re-renders the component when its parent does
+/// - `#[props(memo = path::to::fn)]` on the struct compares the props with a `fn(&Self, &Self) -> bool`
+/// - `#[props(skip_compare)]` on a field leaves it out of the comparison, so `PartialEq` is only required for the
+///   remaining fields
+///
+/// Closures can't be compared, so wrap them in a [`Memo`] to compare them by identity instead.
+///
+/// ```rust, ignore
+/// #[derive(Props)]
+/// #[props(memo = same_title)]
+/// struct CardProps {
+///     title: String,
+///     subtitle: String,
+///     select: Memo<dyn Fn(String)>,
+/// }
+///
+/// fn same_title(old: &CardProps, new: &CardProps) -> bool {
+///     old.title == new.title
+/// }
+/// ```
+///
+/// Props that borrow from their parent can't use any of these attributes. The borrowed data is released when the
+/// parent re-renders, so the child has to re-render with it.
 pub trait Properties: Sized {
     /// The type of the builder for this component.
     /// Used to create "in-progress" versions of the props.
@@ -168,3 +260,88 @@ impl EmptyBuilder {
 pub fn fc_to_builder<'a, T: Properties + 'a>(_: fn(Scope<'a, T>) -> Element) -> T::Builder {
     T::builder()
 }
+
+/// A value that is compared by identity instead of by value.
+///
+/// Props that hold closures can't implement `PartialEq`, so components that accept callbacks usually re-render every
+/// time their parent does. Wrapping the closure in a `Memo` makes two props equal when they hold the same allocation.
+/// To keep the identity stable across renders, create the `Memo` once - in a hook - and clone it into the props.
+///
+/// `rsx!` turns the value of every component field starting with `on` into an [`EventHandler`], so `Memo` fields need
+/// a different name.
+///
+/// ```rust, ignore
+/// fn App(cx: Scope) -> Element {
+///     let count = use_state(&cx, || 0);
+///     let select = cx.use_hook(|_| Memo::callback(|item: String| log::info!("selected {}", item)));
+///
+///     cx.render(rsx!(
+///         "{count}"
+///         List { select: select.clone() }
+///     ))
+/// }
+///
+/// #[derive(Props, PartialEq)]
+/// struct ListProps {
+///     select: Memo<dyn Fn(String)>,
+/// }
+/// ```
+pub struct Memo<T: ?Sized>(Rc<T>);
+
+impl<T> Memo<T> {
+    /// Wrap a value in a new allocation with its own identity
+    pub fn new(value: T) -> Self {
+        Self(Rc::new(value))
+    }
+}
+
+impl<A> Memo<dyn Fn(A)> {
+    /// Wrap a closure in a new allocation with its own identity
+    pub fn callback(f: impl Fn(A) + 'static) -> Self {
+        Self(Rc::new(f))
+    }
+
+    /// Call the wrapped closure
+    pub fn call(&self, arg: A) {
+        (self.0)(arg)
+    }
+}
+
+impl<T: ?Sized> Memo<T> {
+    /// Check if two `Memo`s share the same allocation
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl<T: ?Sized> From<Rc<T>> for Memo<T> {
+    fn from(value: Rc<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: ?Sized> Clone for Memo<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Deref for Memo<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> PartialEq for Memo<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Memo::ptr_eq(self, other)
+    }
+}
+
+impl<T: ?Sized> Debug for Memo<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Memo").field(&Rc::as_ptr(&self.0)).finish()
+    }
+}
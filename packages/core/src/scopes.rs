@@ -4,11 +4,12 @@ use futures_channel::mpsc::UnboundedSender;
 use fxhash::FxHashMap;
 use slab::Slab;
 use std::{
-    any::{Any, TypeId},
+    any::Any,
     borrow::Borrow,
     cell::{Cell, RefCell},
     collections::{HashMap, HashSet},
     future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
     pin::Pin,
     rc::Rc,
     sync::Arc,
@@ -35,6 +36,12 @@ pub(crate) struct ScopeArena {
     pub free_scopes: RefCell<Vec<*mut ScopeState>>,
     pub nodes: RefCell<Slab<*const VNode<'static>>>,
     pub tasks: Rc<TaskQueue>,
+    pub templates: Rc<TemplateRegistry>,
+    pub effects: Rc<EffectQueue>,
+    #[cfg(feature = "hot-reload")]
+    pub hot_reload: Rc<HotReloadRegistry>,
+    #[cfg(feature = "profile")]
+    pub profiler: RefCell<Profiler>,
 }
 
 impl ScopeArena {
@@ -53,6 +60,7 @@ impl ScopeArena {
             listeners: &[],
             attributes: &[],
             children: &[],
+            node_ref: None,
         });
 
         let node = bump.alloc(VNode::Element(el));
@@ -71,9 +79,18 @@ impl ScopeArena {
             tasks: Rc::new(TaskQueue {
                 tasks: RefCell::new(FxHashMap::default()),
                 task_map: RefCell::new(FxHashMap::default()),
+                suspended: RefCell::new(FxHashMap::default()),
+                spawned: RefCell::new(Vec::new()),
+                removed: RefCell::new(Vec::new()),
                 gen: Cell::new(0),
                 sender,
             }),
+            templates: Rc::new(TemplateRegistry::new()),
+            effects: Default::default(),
+            #[cfg(feature = "hot-reload")]
+            hot_reload: Default::default(),
+            #[cfg(feature = "profile")]
+            profiler: RefCell::new(Profiler::new()),
         }
     }
 
@@ -132,6 +149,8 @@ impl ScopeArena {
             scope.items.get_mut().borrowed_props.clear();
             scope.hook_idx.set(0);
             scope.hook_vals.get_mut().clear();
+            scope.destroy_callbacks.get_mut().clear();
+            scope.render_failed.set(false);
 
             let any_item = self.scopes.borrow_mut().insert(new_scope_id, scope);
             debug_assert!(any_item.is_none());
@@ -162,7 +181,13 @@ impl ScopeArena {
                     generation: 0.into(),
 
                     tasks: self.tasks.clone(),
+                    templates: self.templates.clone(),
+                    effects: self.effects.clone(),
+                    #[cfg(feature = "hot-reload")]
+                    hot_reload: self.hot_reload.clone(),
                     shared_contexts: Default::default(),
+                    destroy_callbacks: Default::default(),
+                    render_failed: Cell::new(false),
 
                     items: RefCell::new(SelfReferentialItems {
                         listeners: Default::default(),
@@ -184,12 +209,19 @@ impl ScopeArena {
         log::trace!("removing scope {:?}", id);
         self.ensure_drop_safety(id);
 
+        // Children are removed before their parents, so their callbacks have already run
+        self.effects.remove_scope(id);
+        if let Some(scope) = self.get_scope(id) {
+            scope.run_destroy_callbacks();
+        }
+
         // Dispose of any ongoing tasks
         let mut tasks = self.tasks.tasks.borrow_mut();
         let mut task_map = self.tasks.task_map.borrow_mut();
         if let Some(cur_tasks) = task_map.remove(&id) {
             for task in cur_tasks {
                 tasks.remove(&task);
+                self.tasks.finish(task);
             }
         }
 
@@ -257,7 +289,11 @@ impl ScopeArena {
         }
     }
 
-    pub(crate) fn run_scope(&self, id: ScopeId) {
+    /// Render a scope into its next frame.
+    ///
+    /// Returns false if the scope was already mounted and its render failed into an error boundary. Its mounted nodes
+    /// are kept as they are instead of being diffed, since the boundary replaces the whole subtree when it re-renders.
+    pub(crate) fn run_scope(&self, id: ScopeId) -> bool {
         // Cycle to the next frame and then reset it
         // This breaks any latent references, invalidating every pointer referencing into it.
         // Remove all the outdated listeners
@@ -298,7 +334,32 @@ impl ScopeArena {
 
         let props = scope.props.borrow();
         let render = props.as_ref().unwrap();
-        if let Some(node) = render.render(scope) {
+
+        #[cfg(feature = "profile")]
+        let render_start = std::time::Instant::now();
+
+        // Panics while rendering are handed to the nearest error boundary, if there is one.
+        // A failed component that wasn't mounted yet renders as a placeholder until the boundary swaps it out.
+        RENDERING_SCOPES.with(|scopes| scopes.borrow_mut().push(id));
+        let result = catch_unwind(AssertUnwindSafe(|| render.render(scope)));
+        RENDERING_SCOPES.with(|scopes| scopes.borrow_mut().pop());
+
+        let rendered = match result {
+            Ok(rendered) => rendered,
+            Err(payload) => match scope.throw_panic(payload) {
+                Ok(()) => None,
+                Err(payload) => resume_unwind(payload),
+            },
+        };
+
+        #[cfg(feature = "profile")]
+        self.profiler.borrow_mut().record_render(id, render_start);
+
+        if scope.render_failed.replace(false) && scope.generation.get() > 0 {
+            return false;
+        }
+
+        if let Some(node) = rendered {
             let frame = scope.wip_frame();
             let node = frame.bump.alloc(node);
             frame.node.set(unsafe { extend_vnode(node) });
@@ -315,6 +376,7 @@ impl ScopeArena {
         // make the "wip frame" contents the "finished frame"
         // any future dipping into completed nodes after "render" will go through "fin head"
         scope.cycle_frame();
+        true
     }
 
     pub fn call_listener_with_bubbling(&self, event: UserEvent, element: ElementId) {
@@ -353,6 +415,10 @@ impl ScopeArena {
                     }
 
                     cur_el = real_el.parent.get();
+                } else if let VNode::Template(template) = real_el {
+                    cur_el = template.parent.get();
+                } else if let VNode::Portal(portal) = real_el {
+                    cur_el = portal.parent.get();
                 }
             }
         }
@@ -435,6 +501,19 @@ impl<'a, P> std::ops::Deref for Scope<'a, P> {
     }
 }
 
+thread_local! {
+    /// The scopes being rendered on this thread, innermost last
+    static RENDERING_SCOPES: RefCell<Vec<ScopeId>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Get the id of the component that is rendering on the current thread, if any.
+///
+/// This lets state that isn't tied to a scope, like signals, know which component read it. Outside of a render, ie in
+/// event handlers, effects and tasks, there is no current scope.
+pub fn current_scope_id() -> Option<ScopeId> {
+    RENDERING_SCOPES.with(|scopes| scopes.borrow().last().copied())
+}
+
 /// A component's unique identifier.
 ///
 /// `ScopeId` is a `usize` that is unique across the entire VirtualDOM and across time. ScopeIDs will never be reused
@@ -489,8 +568,16 @@ pub struct ScopeState {
     pub(crate) hook_idx: Cell<usize>,
 
     // shared state -> todo: move this out of scopestate
-    pub(crate) shared_contexts: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    pub(crate) shared_contexts: RefCell<HashMap<ContextId, ProvidedContext>>,
     pub(crate) tasks: Rc<TaskQueue>,
+    pub(crate) templates: Rc<TemplateRegistry>,
+    pub(crate) effects: Rc<EffectQueue>,
+    #[cfg(feature = "hot-reload")]
+    pub(crate) hot_reload: Rc<HotReloadRegistry>,
+    pub(crate) destroy_callbacks: RefCell<Vec<Box<dyn FnOnce()>>>,
+
+    /// Set when an error boundary captured an error from the render in progress
+    pub(crate) render_failed: Cell<bool>,
 }
 
 pub struct SelfReferentialItems<'a> {
@@ -646,112 +733,47 @@ impl ScopeState {
             .unbounded_send(SchedulerMsg::Immediate(id));
     }
 
-    /// Get the Root Node of this scope
-    pub fn root_node(&self) -> &VNode {
-        let node = unsafe { &*self.fin_frame().node.get() };
-        unsafe { std::mem::transmute(node) }
-    }
-
-    /// This method enables the ability to expose state to children further down the VirtualDOM Tree.
+    /// Run a callback once the renderer has applied the edits produced by the current render.
     ///
-    /// This is a "fundamental" operation and should only be called during initialization of a hook.
-    ///
-    /// For a hook that provides the same functionality, use `use_provide_context` and `use_consume_context` instead.
-    ///
-    /// When the component is dropped, so is the context. Be aware of this behavior when consuming
-    /// the context via Rc/Weak.
+    /// Renderers signal that they've applied their edits with [`VirtualDom::mutations_applied`]. Callbacks queued by
+    /// children run before the callbacks queued by their parents. If the component is removed first, the callback
+    /// never runs.
     ///
     /// # Example
     ///
     /// ```rust, ignore
-    /// struct SharedState(&'static str);
-    ///
-    /// static App: Component = |cx| {
-    ///     cx.use_hook(|_| cx.provide_context(SharedState("world")));
-    ///     rsx!(cx, Child {})
-    /// }
-    ///
-    /// static Child: Component = |cx| {
-    ///     let state = cx.consume_state::<SharedState>();
-    ///     rsx!(cx, div { "hello {state.0}" })
-    /// }
+    /// cx.use_hook(|_| cx.queue_effect(|| log::info!("mounted!")));
     /// ```
-    pub fn provide_context<T: 'static + Clone>(&self, value: T) -> T {
-        self.shared_contexts
-            .borrow_mut()
-            .insert(TypeId::of::<T>(), Box::new(value.clone()))
-            .and_then(|f| f.downcast::<T>().ok());
-        value
+    pub fn queue_effect(&self, f: impl FnOnce() + 'static) {
+        self.effects.push(self.scope_id(), self.height, Box::new(f));
     }
 
-    /// Provide a context for the root component from anywhere in your app.
+    /// Run a callback when this component is removed from the VirtualDom.
     ///
+    /// Destroy callbacks run while the component is being removed - before its hooks are dropped and before the
+    /// renderer applies the edits removing its nodes. Children are destroyed before their parents, and a component's
+    /// callbacks run in the order they were registered.
     ///
     /// # Example
     ///
     /// ```rust, ignore
-    /// struct SharedState(&'static str);
-    ///
-    /// static App: Component = |cx| {
-    ///     cx.use_hook(|_| cx.provide_root_context(SharedState("world")));
-    ///     rsx!(cx, Child {})
-    /// }
-    ///
-    /// static Child: Component = |cx| {
-    ///     let state = cx.consume_state::<SharedState>();
-    ///     rsx!(cx, div { "hello {state.0}" })
-    /// }
+    /// cx.use_hook(|_| cx.on_destroy(|| log::info!("removed!")));
     /// ```
-    pub fn provide_root_context<T: 'static + Clone>(&self, value: T) -> T {
-        // if we *are* the root component, then we can just provide the context directly
-        if self.scope_id() == ScopeId(0) {
-            self.shared_contexts
-                .borrow_mut()
-                .insert(TypeId::of::<T>(), Box::new(value.clone()))
-                .and_then(|f| f.downcast::<T>().ok());
-            return value;
-        }
-
-        let mut search_parent = self.parent_scope;
-
-        while let Some(parent) = search_parent.take() {
-            let parent = unsafe { &*parent };
-
-            if parent.scope_id() == ScopeId(0) {
-                let exists = parent
-                    .shared_contexts
-                    .borrow_mut()
-                    .insert(TypeId::of::<T>(), Box::new(value.clone()));
-
-                if exists.is_some() {
-                    log::warn!("Context already provided to parent scope - replacing it");
-                }
-                return value;
-            }
+    pub fn on_destroy(&self, f: impl FnOnce() + 'static) {
+        self.destroy_callbacks.borrow_mut().push(Box::new(f));
+    }
 
-            search_parent = parent.parent_scope;
+    pub(crate) fn run_destroy_callbacks(&self) {
+        let callbacks = std::mem::take(&mut *self.destroy_callbacks.borrow_mut());
+        for callback in callbacks {
+            callback();
         }
-
-        unreachable!("all apps have a root scope")
     }
 
-    /// Try to retrieve a SharedState with type T from the any parent Scope.
-    pub fn consume_context<T: 'static + Clone>(&self) -> Option<T> {
-        if let Some(shared) = self.shared_contexts.borrow().get(&TypeId::of::<T>()) {
-            Some((*shared.downcast_ref::<T>().unwrap()).clone())
-        } else {
-            let mut search_parent = self.parent_scope;
-
-            while let Some(parent_ptr) = search_parent {
-                // safety: all parent pointers are valid thanks to the bump arena
-                let parent = unsafe { &*parent_ptr };
-                if let Some(shared) = parent.shared_contexts.borrow().get(&TypeId::of::<T>()) {
-                    return Some(shared.downcast_ref::<T>().unwrap().clone());
-                }
-                search_parent = parent.parent_scope;
-            }
-            None
-        }
+    /// Get the Root Node of this scope
+    pub fn root_node(&self) -> &VNode {
+        let node = unsafe { &*self.fin_frame().node.get() };
+        unsafe { std::mem::transmute(node) }
     }
 
     /// Pushes the future onto the poll queue to be polled after the component renders.
@@ -762,7 +784,30 @@ impl ScopeState {
             .unbounded_send(SchedulerMsg::NewTask(self.our_arena_idx))
             .unwrap();
 
-        self.tasks.spawn(self.our_arena_idx, fut)
+        let task = self.tasks.spawn(self.our_arena_idx, fut);
+        self.suspend_on(task);
+        task
+    }
+
+    /// Get a handle that can spawn tasks for this component outside of a render.
+    ///
+    /// Tasks spawned through the handle are cancelled when the component is unmounted, like the tasks spawned with
+    /// [`ScopeState::push_future`], but they don't suspend the component.
+    pub fn task_handle(&self) -> TaskHandle {
+        TaskHandle {
+            tasks: self.tasks.clone(),
+            scope: self.our_arena_idx,
+        }
+    }
+
+    /// Get a handle that spawns tasks at the top of the app, like [`ScopeState::spawn_forever`].
+    ///
+    /// This is useful for state that's shared across the app and outlives the component that created it.
+    pub fn root_task_handle(&self) -> TaskHandle {
+        TaskHandle {
+            tasks: self.tasks.clone(),
+            scope: ScopeId(0),
+        }
     }
 
     /// Spawns the future but does not return the TaskId
@@ -914,6 +959,8 @@ impl ScopeState {
 
         // next: shared context data
         self.shared_contexts.get_mut().clear();
+        self.destroy_callbacks.get_mut().clear();
+        self.render_failed.set(false);
 
         // next: reset the node data
         let SelfReferentialItems {
@@ -969,9 +1016,75 @@ impl BumpFrame {
     }
 }
 
+/// Callbacks waiting for the renderer to apply the edits of the render that queued them.
+#[derive(Default)]
+pub(crate) struct EffectQueue {
+    queue: RefCell<Vec<QueuedEffect>>,
+}
+
+struct QueuedEffect {
+    scope: ScopeId,
+    height: u32,
+    callback: Box<dyn FnOnce()>,
+}
+
+impl EffectQueue {
+    fn push(&self, scope: ScopeId, height: u32, callback: Box<dyn FnOnce()>) {
+        self.queue.borrow_mut().push(QueuedEffect {
+            scope,
+            height,
+            callback,
+        });
+    }
+
+    pub(crate) fn remove_scope(&self, id: ScopeId) {
+        self.queue.borrow_mut().retain(|effect| effect.scope != id);
+    }
+
+    /// Take every queued callback, children before their parents
+    pub(crate) fn take(&self) -> Vec<Box<dyn FnOnce()>> {
+        let mut queue = std::mem::take(&mut *self.queue.borrow_mut());
+        queue.sort_by_key(|effect| std::cmp::Reverse(effect.height));
+        queue.into_iter().map(|effect| effect.callback).collect()
+    }
+}
+
+/// A handle to the tasks of a component, for spawning tasks from event handlers or from other tasks.
+///
+/// Created with [`ScopeState::task_handle`].
+#[derive(Clone)]
+pub struct TaskHandle {
+    tasks: Rc<TaskQueue>,
+    scope: ScopeId,
+}
+
+impl TaskHandle {
+    /// Spawn a task that belongs to the component the handle was created for
+    pub fn spawn(&self, fut: impl Future<Output = ()> + 'static) -> TaskId {
+        // wake up the scheduler if it is sleeping
+        let _ = self
+            .tasks
+            .sender
+            .unbounded_send(SchedulerMsg::NewTask(self.scope));
+
+        self.tasks.spawn(self.scope, fut)
+    }
+
+    /// Cancel a task
+    pub fn remove(&self, id: TaskId) {
+        self.tasks.remove(id);
+    }
+}
+
 pub(crate) struct TaskQueue {
     pub(crate) tasks: RefCell<FxHashMap<TaskId, InnerTask>>,
     pub(crate) task_map: RefCell<FxHashMap<ScopeId, HashSet<TaskId>>>,
+    pub(crate) suspended: RefCell<FxHashMap<TaskId, Rc<SuspenseBoundary>>>,
+
+    // tasks spawned and removed while the tasks are being polled, which are applied once polling is done
+    spawned: RefCell<Vec<(TaskId, InnerTask)>>,
+    removed: RefCell<Vec<TaskId>>,
+
     gen: Cell<usize>,
     sender: UnboundedSender<SchedulerMsg>,
 }
@@ -984,7 +1097,12 @@ impl TaskQueue {
         self.gen.set(id + 1);
         let tid = TaskId { id, scope };
 
-        self.tasks.borrow_mut().insert(tid, pinned);
+        match self.tasks.try_borrow_mut() {
+            Ok(mut tasks) => {
+                tasks.insert(tid, pinned);
+            }
+            Err(_) => self.spawned.borrow_mut().push((tid, pinned)),
+        }
 
         // also add to the task map
         // when the component is unmounted we know to remove it from the map
@@ -998,18 +1116,59 @@ impl TaskQueue {
     }
 
     fn remove(&self, id: TaskId) {
-        if let Ok(mut tasks) = self.tasks.try_borrow_mut() {
-            let _ = tasks.remove(&id);
+        match self.tasks.try_borrow_mut() {
+            Ok(mut tasks) => {
+                let _ = tasks.remove(&id);
+            }
+            Err(_) => {
+                self.removed.borrow_mut().push(id);
+                let spawned = self
+                    .spawned
+                    .borrow_mut()
+                    .iter()
+                    .position(|(tid, _)| *tid == id);
+                if let Some(idx) = spawned {
+                    let task = self.spawned.borrow_mut().remove(idx);
+                    drop(task);
+                }
+            }
         }
 
         // the task map is still around, but it'll be removed when the scope is unmounted
         if let Some(task_map) = self.task_map.borrow_mut().get_mut(&id.scope) {
             task_map.remove(&id);
         }
+
+        self.finish(id);
+    }
+
+    /// Let any suspense boundary waiting on this task know that it's done
+    pub(crate) fn finish(&self, id: TaskId) {
+        let boundary = self.suspended.borrow_mut().remove(&id);
+        if let Some(boundary) = boundary {
+            boundary.finish_task(id);
+        }
     }
 
     pub(crate) fn has_tasks(&self) -> bool {
-        !self.tasks.borrow().is_empty()
+        !self.tasks.borrow().is_empty() || !self.spawned.borrow().is_empty()
+    }
+
+    /// Apply the spawns and removals that happened while the tasks were being polled.
+    ///
+    /// Returns true if any tasks were spawned, since they haven't been polled yet.
+    pub(crate) fn flush(&self) -> bool {
+        let removed = std::mem::take(&mut *self.removed.borrow_mut());
+        let mut tasks = self.tasks.borrow_mut();
+        for id in removed {
+            let _ = tasks.remove(&id);
+        }
+
+        let spawned = std::mem::take(&mut *self.spawned.borrow_mut());
+        let any_spawned = !spawned.is_empty();
+        tasks.extend(spawned);
+
+        any_spawned
     }
 }
 
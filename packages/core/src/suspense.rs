@@ -0,0 +1,197 @@
+//! Suspense boundaries hold back a subtree until the futures it spawned have finished.
+//!
+//! A boundary collects every task pushed with [`ScopeState::push_future`] by the scopes beneath it
+//! while they are first being created. If any of those tasks are still pending once the subtree
+//! has been built, the boundary shows its fallback component instead. Edits for the suspended
+//! subtree are never sent to the renderer - once the last task completes, the whole subtree is
+//! created in one go and swapped in for the fallback.
+
+use crate::innerlude::*;
+use fxhash::FxHashSet;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::Arc,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum SuspenseStatus {
+    /// The boundary's subtree is being created and is collecting tasks
+    Collecting,
+
+    /// Tasks are pending and the fallback is being shown
+    Suspended,
+
+    /// Every task finished - the subtree is waiting to be swapped in
+    Resolving,
+
+    /// The subtree has been sent to the renderer
+    Resolved,
+}
+
+/// A boundary that shows a fallback until the tasks spawned beneath it have completed.
+///
+/// Boundaries are created with [`ScopeState::provide_suspense_boundary`].
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn Loading(cx: Scope) -> Element {
+///     cx.render(rsx!("loading..."))
+/// }
+///
+/// fn Suspense<'a>(cx: Scope<'a, SuspenseProps<'a>>) -> Element {
+///     cx.provide_suspense_boundary(Loading);
+///     cx.render(rsx!(&cx.props.children))
+/// }
+/// ```
+pub struct SuspenseBoundary {
+    id: ScopeId,
+    pub(crate) fallback: Component,
+    pub(crate) status: Cell<SuspenseStatus>,
+    pub(crate) fallback_scope: Cell<Option<ScopeId>>,
+    pending: RefCell<FxHashSet<TaskId>>,
+    update_any: Arc<dyn Fn(ScopeId) + Send + Sync>,
+}
+
+impl SuspenseBoundary {
+    /// The scope this boundary belongs to
+    pub fn scope_id(&self) -> ScopeId {
+        self.id
+    }
+
+    /// Check if the boundary is currently showing its fallback
+    pub fn is_suspended(&self) -> bool {
+        matches!(
+            self.status.get(),
+            SuspenseStatus::Suspended | SuspenseStatus::Resolving
+        )
+    }
+
+    /// Check if the boundary's subtree has been handed to the renderer
+    pub fn is_resolved(&self) -> bool {
+        self.status.get() == SuspenseStatus::Resolved
+    }
+
+    /// The number of tasks the boundary is still waiting on
+    pub fn pending_tasks(&self) -> usize {
+        self.pending.borrow().len()
+    }
+
+    pub(crate) fn is_collecting(&self) -> bool {
+        matches!(
+            self.status.get(),
+            SuspenseStatus::Collecting | SuspenseStatus::Suspended
+        )
+    }
+
+    pub(crate) fn add_task(&self, id: TaskId) {
+        self.pending.borrow_mut().insert(id);
+    }
+
+    /// Called whenever a task the boundary is waiting on completes or is cancelled
+    pub(crate) fn finish_task(&self, id: TaskId) {
+        let mut pending = self.pending.borrow_mut();
+        pending.remove(&id);
+
+        if pending.is_empty() && self.status.get() == SuspenseStatus::Suspended {
+            self.status.set(SuspenseStatus::Resolving);
+            (self.update_any)(self.id);
+        }
+    }
+}
+
+impl ScopeState {
+    /// Make this scope a [`SuspenseBoundary`] for its descendants.
+    ///
+    /// Any task pushed by this scope or its descendants while they are first created will hold
+    /// back the whole subtree, rendering the `fallback` component in its place until every task
+    /// has completed.
+    ///
+    /// Calling this more than once returns the same boundary, so it's fine to call it every render.
+    pub fn provide_suspense_boundary(&self, fallback: Component) -> Rc<SuspenseBoundary> {
+        if let Some(boundary) = self.own_suspense_boundary() {
+            return boundary;
+        }
+
+        self.provide_context(Rc::new(SuspenseBoundary {
+            id: self.scope_id(),
+            fallback,
+            status: Cell::new(SuspenseStatus::Collecting),
+            fallback_scope: Cell::new(None),
+            pending: Default::default(),
+            update_any: self.schedule_update_any(),
+        }))
+    }
+
+    /// If this scope is a suspended [`SuspenseBoundary`], get the scope of the fallback it is
+    /// showing in place of its children.
+    ///
+    /// Renderers that walk the tree directly (like SSR) should render this scope instead.
+    pub fn suspense_fallback(&self) -> Option<ScopeId> {
+        self.own_suspense_boundary()
+            .filter(|boundary| !boundary.is_resolved())
+            .and_then(|boundary| boundary.fallback_scope.get())
+    }
+
+    pub(crate) fn own_suspense_boundary(&self) -> Option<Rc<SuspenseBoundary>> {
+        self.own_context::<Rc<SuspenseBoundary>>()
+    }
+
+    /// Register a newly spawned task with the nearest boundary that's still collecting tasks
+    pub(crate) fn suspend_on(&self, task: TaskId) {
+        if let Some(boundary) = self.consume_context::<Rc<SuspenseBoundary>>() {
+            if boundary.is_collecting() {
+                boundary.add_task(task);
+                self.tasks.suspended.borrow_mut().insert(task, boundary);
+            }
+        }
+    }
+}
+
+impl ScopeArena {
+    /// Check if the edits for this scope would land inside a boundary that hasn't been sent to the
+    /// renderer yet.
+    ///
+    /// Fallbacks are children of their boundary, but they are visible while it is suspended.
+    pub(crate) fn is_suspended(&self, id: ScopeId) -> bool {
+        let mut child = None;
+        let mut search = self.get_scope(id);
+
+        while let Some(scope) = search {
+            if let Some(boundary) = scope.own_suspense_boundary() {
+                let showing_fallback = child.is_some() && boundary.fallback_scope.get() == child;
+                if !boundary.is_resolved() && !showing_fallback {
+                    return true;
+                }
+            }
+
+            child = Some(scope.scope_id());
+
+            // safety: scopes live in the arena's bump allocator, and a parent is only removed after its children, so
+            // the parent of a scope we could look up is still alive
+            search = scope.parent_scope.map(|parent| unsafe { &*parent });
+        }
+
+        false
+    }
+
+    /// Get the node that's actually mounted for this scope - the fallback's if the scope is suspended
+    pub(crate) fn mounted_root(&self, id: ScopeId) -> &VNode<'_> {
+        match self
+            .get_scope(id)
+            .and_then(|scope| scope.suspense_fallback())
+        {
+            Some(fallback) => self.root_node(fallback),
+            None => self.root_node(id),
+        }
+    }
+
+    /// Check if any suspense boundaries are still waiting on tasks
+    pub(crate) fn has_suspended_boundaries(&self) -> bool {
+        self.scopes.borrow().values().any(|scope| {
+            let scope = unsafe { &**scope };
+            matches!(scope.own_suspense_boundary(), Some(boundary) if !boundary.is_resolved())
+        })
+    }
+}
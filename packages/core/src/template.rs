@@ -0,0 +1,115 @@
+//! Templates are static subtrees hoisted out of `rsx!`.
+//!
+//! `rsx!` wraps every element whose attributes and children are all plain string literals in a call to
+//! [`NodeFactory::template`]. By default the subtree is built and diffed like any other markup, so renderers that walk
+//! the VirtualDom directly don't need to know templates exist.
+//!
+//! Renderers that can clone DOM nodes opt in with [`VirtualDom::enable_templates`]. From then on, each template is
+//! built once, sent to the renderer a single time with [`DomEdit::SaveTemplate`], and instantiated with
+//! [`DomEdit::CloneTemplate`]. Templates never change between renders, so diffing two instances of the same template
+//! only has to carry over the ElementId of its root.
+//!
+//! Templates have no dynamic slots. An element with a formatted attribute or text, a listener, a key, a node ref, or a
+//! component anywhere beneath it is emitted as a regular [`VElement`] and diffed attribute by attribute as before; only
+//! its fully static children become templates of their own.
+//!
+//! A template is identified by the address of the function `rsx!` generates for it. The compiler may merge identical
+//! functions or duplicate one across codegen units, which only means identical subtrees share a template or the same
+//! subtree is saved more than once.
+
+use crate::{innerlude::*, unsafe_utils::extend_vnode};
+use bumpalo::Bump;
+use fxhash::FxHashMap;
+use std::cell::{Cell, RefCell};
+
+/// The function `rsx!` generates to build a static subtree.
+///
+/// The address of the function identifies the template.
+pub type TemplateBuilder = for<'a> fn(NodeFactory<'a>) -> VNode<'a>;
+
+/// An instance of a static subtree.
+pub struct VTemplate {
+    /// The function that builds the subtree.
+    pub build: TemplateBuilder,
+
+    /// The [`ElementId`] of the root of the subtree.
+    ///
+    /// Nodes inside the template don't get IDs of their own.
+    pub id: Cell<Option<ElementId>>,
+
+    /// The parent of the template (if any).
+    ///
+    /// Used when bubbling events
+    pub parent: Cell<Option<ElementId>>,
+}
+
+impl VTemplate {
+    pub(crate) fn key(&self) -> usize {
+        self.build as usize
+    }
+}
+
+/// Every template the VirtualDom has built, shared between the arena and its scopes.
+pub(crate) struct TemplateRegistry {
+    pub enabled: Cell<bool>,
+    bump: Bump,
+    templates: RefCell<FxHashMap<usize, RegisteredTemplate>>,
+}
+
+struct RegisteredTemplate {
+    id: u64,
+    node: *const VNode<'static>,
+    saved: bool,
+}
+
+impl TemplateRegistry {
+    pub fn new() -> Self {
+        Self {
+            enabled: Cell::new(false),
+            bump: Bump::new(),
+            templates: RefCell::new(FxHashMap::default()),
+        }
+    }
+
+    /// Get the ID and the static tree of a template, building it on first use.
+    ///
+    /// Also returns whether the renderer has already been sent the template.
+    pub fn get<'a>(
+        &'a self,
+        template: &VTemplate,
+        scope: &ScopeState,
+    ) -> (u64, &'a VNode<'a>, bool) {
+        let key = template.key();
+
+        if let Some(entry) = self.templates.borrow().get(&key) {
+            return (entry.id, unsafe { extend_vnode(&*entry.node) }, entry.saved);
+        }
+
+        let factory = NodeFactory {
+            scope,
+            bump: &self.bump,
+        };
+        let node: &'a VNode<'a> =
+            unsafe { extend_vnode(self.bump.alloc((template.build)(factory))) };
+
+        let mut templates = self.templates.borrow_mut();
+        let id = templates.len() as u64;
+        templates.insert(
+            key,
+            RegisteredTemplate {
+                id,
+                node: unsafe { extend_vnode(node) },
+                saved: false,
+            },
+        );
+
+        (id, node, false)
+    }
+
+    /// Record whether the renderer holds a copy of the template.
+    pub fn set_saved(&self, key: usize, saved: bool) {
+        if let Some(entry) = self.templates.borrow_mut().get_mut(&key) {
+            entry.saved = saved;
+        }
+    }
+}
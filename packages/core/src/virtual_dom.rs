@@ -2,12 +2,12 @@
 //!
 //! This module provides the primary mechanics to create a hook-based, concurrent VDOM for Rust.
 
-use crate::diff::DiffState;
+use crate::diff::{DiffState, PausedDiff};
 use crate::innerlude::*;
 use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
 use futures_util::{future::poll_fn, StreamExt};
 use fxhash::FxHashSet;
-use indexmap::IndexSet;
+use indexmap::IndexMap;
 use std::{collections::VecDeque, iter::FromIterator, task::Poll};
 
 /// A virtual node system that progresses user events and diffs UI trees.
@@ -106,14 +106,29 @@ pub struct VirtualDom {
     scopes: ScopeArena,
 
     pending_messages: VecDeque<SchedulerMsg>,
-    dirty_scopes: IndexSet<ScopeId>,
+    dirty_scopes: IndexMap<ScopeId, EventPriority>,
 
     channel: (
         UnboundedSender<SchedulerMsg>,
         UnboundedReceiver<SchedulerMsg>,
     ),
+
+    message_observer: Option<MessageObserver>,
+
+    paused: Option<PausedScope>,
+}
+
+/// A dirty scope whose diff ran out of time partway through. It's finished before anything else is rendered.
+struct PausedScope {
+    diff: PausedDiff<'static>,
+    priority: EventPriority,
+
+    /// The scopes that had rendered when the diff paused, reported along with the rest once it finishes.
+    rendered: FxHashSet<ScopeId>,
 }
 
+type MessageObserver = Box<dyn FnMut(&SchedulerMsg)>;
+
 /// The type of message that can be sent to the scheduler.
 ///
 /// These messages control how the scheduler will process updates to the UI.
@@ -230,8 +245,10 @@ impl VirtualDom {
         Self {
             scopes,
             channel,
-            dirty_scopes: IndexSet::from_iter([ScopeId(0)]),
+            dirty_scopes: IndexMap::from_iter([(ScopeId(0), EventPriority::Low)]),
             pending_messages: VecDeque::new(),
+            message_observer: None,
+            paused: None,
         }
     }
 
@@ -281,6 +298,53 @@ impl VirtualDom {
         self.scopes.get_element(id)
     }
 
+    /// Hoist the static subtrees generated by `rsx!` into templates.
+    ///
+    /// Each template is sent to the renderer once with [`DomEdit::SaveTemplate`] and then instantiated with
+    /// [`DomEdit::CloneTemplate`], and diffing a template against itself is free. Only enable templates if the renderer
+    /// understands those edits. Renderers that walk the tree directly can get the contents of a [`VNode::Template`] with
+    /// [`VirtualDom::get_template`]. Renderers built on `dioxus-native-core` can't track the nodes inside a template and
+    /// reject these edits, so the TUI leaves templates off.
+    ///
+    /// Only subtrees with no dynamic content at all are hoisted. Anything formatted, keyed or interactive is still built
+    /// and diffed as regular elements.
+    ///
+    /// This must be called before the first call to `rebuild`.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(App);
+    /// dom.enable_templates();
+    /// let edits = dom.rebuild();
+    /// ```
+    pub fn enable_templates(&mut self) {
+        self.scopes.templates.enabled.set(true);
+    }
+
+    /// Get the static tree a [`VTemplate`] stands in for.
+    ///
+    /// The tree is shared between every instance of the template, so the IDs of its nodes are meaningless.
+    pub fn get_template(&self, template: &VTemplate) -> &VNode<'_> {
+        let (_, node, _) = self.scopes.templates.get(template, self.base_scope());
+        node
+    }
+
+    /// Call a function with every message right before the VirtualDom processes it.
+    ///
+    /// Updates scheduled by event listeners are handled as part of their event, so they aren't observed. Setting a
+    /// new observer replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(App);
+    /// dom.observe_messages(|msg| log::info!("processing {:?}", msg));
+    /// ```
+    pub fn observe_messages(&mut self, observer: impl FnMut(&SchedulerMsg) + 'static) {
+        self.message_observer = Some(Box::new(observer));
+    }
+
     /// Add a new message to the scheduler queue directly.
     ///
     ///
@@ -309,7 +373,7 @@ impl VirtualDom {
     /// assert!(dom.has_any_work());
     /// ```
     pub fn has_work(&self) -> bool {
-        !(self.dirty_scopes.is_empty() && self.pending_messages.is_empty())
+        !(self.paused.is_none() && self.dirty_scopes.is_empty() && self.pending_messages.is_empty())
     }
 
     /// Wait for the scheduler to have any work.
@@ -319,6 +383,9 @@ impl VirtualDom {
     ///
     /// This lets us poll async tasks during idle periods without blocking the main thread.
     ///
+    /// When the last task a suspense boundary is waiting on completes, the boundary is marked dirty and this method
+    /// returns. The next call to `work_with_deadline` will swap the boundary's fallback for its resolved subtree.
+    ///
     /// # Example
     ///
     /// ```rust, ignore
@@ -327,7 +394,8 @@ impl VirtualDom {
     /// ```
     pub async fn wait_for_work(&mut self) {
         loop {
-            if !self.dirty_scopes.is_empty() && self.pending_messages.is_empty() {
+            let has_work = self.paused.is_some() || !self.dirty_scopes.is_empty();
+            if has_work && self.pending_messages.is_empty() {
                 break;
             }
 
@@ -354,6 +422,14 @@ impl VirtualDom {
 
                         for id in to_remove {
                             tasks.remove(&id);
+                            scopes.tasks.finish(id);
+                        }
+                        drop(tasks);
+
+                        // tasks spawned by other tasks haven't been polled yet
+                        if scopes.tasks.flush() {
+                            any_pending = true;
+                            cx.waker().wake_by_ref();
                         }
 
                         // Resolve the future if any singular task is ready
@@ -378,6 +454,29 @@ impl VirtualDom {
         }
     }
 
+    /// Wait for every suspense boundary in the VirtualDom to resolve.
+    ///
+    /// This drives tasks and processes updates until no boundary is showing its fallback. The edits
+    /// produced along the way are dropped, so this is meant for renderers that walk the tree
+    /// directly, like SSR, that want to render the complete page instead of its fallbacks.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(App);
+    /// dom.rebuild();
+    ///
+    /// dom.wait_for_suspense().await;
+    ///
+    /// let html = dioxus_ssr::render_vdom(&dom);
+    /// ```
+    pub async fn wait_for_suspense(&mut self) {
+        while self.scopes.has_suspended_boundaries() {
+            self.wait_for_work().await;
+            self.work_with_deadline(|| false);
+        }
+    }
+
     /// Manually kick the VirtualDom to process any
     pub fn process_all_messages(&mut self) {
         // clear out the scheduler queue
@@ -395,21 +494,57 @@ impl VirtualDom {
     ///
     /// This will either call an event listener or mark a component as dirty.
     pub fn process_message(&mut self, msg: SchedulerMsg) {
+        if let Some(observer) = self.message_observer.as_mut() {
+            observer(&msg);
+        }
+
         match msg {
             SchedulerMsg::NewTask(_id) => {
                 // uh, not sure? I think end up re-polling it anyways
             }
             SchedulerMsg::Event(event) => {
+                #[cfg(feature = "profile")]
+                let name = event.name;
+                let priority = event.priority;
+
                 if let Some(element) = event.element {
                     self.scopes.call_listener_with_bubbling(event, element);
                 }
+
+                // updates scheduled by the listeners inherit the priority of the event
+                while let Ok(msg) = self.channel.1.try_recv() {
+                    match msg {
+                        SchedulerMsg::Immediate(id) => {
+                            #[cfg(feature = "profile")]
+                            self.scopes
+                                .profiler
+                                .borrow_mut()
+                                .mark_dirty(id, DirtyReason::Event(name));
+
+                            self.mark_dirty(id, priority);
+                        }
+                        msg => self.pending_messages.push_front(msg),
+                    }
+                }
             }
             SchedulerMsg::Immediate(s) => {
-                self.dirty_scopes.insert(s);
+                #[cfg(feature = "profile")]
+                self.scopes
+                    .profiler
+                    .borrow_mut()
+                    .mark_dirty(s, DirtyReason::Immediate);
+
+                // updates that don't come from the user are background work
+                self.mark_dirty(s, EventPriority::Low);
             }
         }
     }
 
+    fn mark_dirty(&mut self, id: ScopeId, priority: EventPriority) {
+        let entry = self.dirty_scopes.entry(id).or_insert(priority);
+        *entry = (*entry).max(priority);
+    }
+
     /// Run the virtualdom with a deadline.
     ///
     /// This method will perform any outstanding diffing work and try to return as many mutations as possible before the
@@ -431,6 +566,20 @@ impl VirtualDom {
     /// If the work is not finished by the deadline, Dioxus will store it for later and return when work_with_deadline
     /// is called again. This means you can ensure some level of free time on the VirtualDom's thread during the work phase.
     ///
+    /// The deadline is checked between dirty components, and between siblings while a component is diffed. Once it has
+    /// passed, child components with `'static` props whose props changed are left unrendered and picked up by the next
+    /// call as their own unit of work. Any other siblings that are left are saved, and the next call finishes the diff
+    /// where it stopped before rendering anything else. The edits of a component that was stopped partway are held back
+    /// until its diff finishes, so every returned [`Mutations`] is complete on its own and can be applied right away.
+    ///
+    /// At least one pair of siblings is diffed per call, so the work always moves forward. Keyed children that are moved
+    /// around and subtrees inside a suspended boundary are diffed in one go, since the edits after them depend on them.
+    ///
+    /// Dirty components are worked on in order of the [`EventPriority`] of the event that made them dirty. Updates
+    /// that weren't caused by an event are [`EventPriority::Low`]. If an event with a higher priority than the work in
+    /// progress arrives while diffing, the diff stops the same way it does at the deadline and this method returns early
+    /// so the event can be handled first - the remaining work is resumed by the next call.
+    ///
     /// For use in the web, it is expected that this method will be called to be executed during "idle times" and the
     /// mutations to be applied during the "paint times" IE "animation frames". With this strategy, it is possible to craft
     /// entirely jank-free applications that perform a ton of work.
@@ -455,66 +604,172 @@ impl VirtualDom {
     ///     apply_mutations(mutations);
     /// }
     /// ```
-    #[allow(unused)]
     pub fn work_with_deadline(&mut self, mut deadline: impl FnMut() -> bool) -> Vec<Mutations> {
         let mut committed_mutations = vec![];
+        let mut resolving = vec![];
+
+        #[cfg(feature = "profile")]
+        self.scopes
+            .profiler
+            .borrow_mut()
+            .begin_cycle(CycleKind::Work);
 
-        while !self.dirty_scopes.is_empty() {
+        while self.paused.is_some() || !self.dirty_scopes.is_empty() {
             let scopes = &self.scopes;
+
+            // a scope that ran out of time partway through is finished before anything else is rendered
+            let (paused, scopeid, priority) = match self.paused.take() {
+                Some(paused) => {
+                    let (scopeid, priority) = (paused.diff.scope, paused.priority);
+                    (Some(paused), scopeid, priority)
+                }
+                None => {
+                    self.dirty_scopes
+                        .retain(|id, _| scopes.get_scope(*id).is_some());
+
+                    // Sort the scopes by priority, then by height. Theoretically, we'll de-duplicate scopes by height
+                    self.dirty_scopes.sort_by(|a, p1, b, p2| {
+                        let h1 = scopes.get_scope(*a).unwrap().height;
+                        let h2 = scopes.get_scope(*b).unwrap().height;
+                        p1.cmp(p2).then(h1.cmp(&h2).reverse())
+                    });
+
+                    log::trace!("dirty_scopes: {:?}", self.dirty_scopes);
+
+                    match self.dirty_scopes.pop() {
+                        Some((scopeid, priority)) => (None, scopeid, priority),
+                        None => break,
+                    }
+                }
+            };
+
+            // events that are more important than this scope stop the diff the same way the deadline does
+            let (channel, pending_messages) = (&mut self.channel.1, &mut self.pending_messages);
+            let mut interrupted =
+                || deadline() || has_preempting_event(channel, pending_messages, priority);
+
             let mut diff_state = DiffState::new(scopes);
+            diff_state.deadline = Some(&mut interrupted);
+
+            let mut rendered = FxHashSet::default();
+
+            if let Some(paused) = paused {
+                rendered = paused.rendered;
+                resume(&mut diff_state, paused.diff);
+            } else if !self.scopes.run_scope(scopeid) {
+                // a failed scope keeps its nodes, and the boundary that caught the error replaces them in this
+                // same call rather than leaving a placeholder behind for a frame
+                let boundary = scopes
+                    .get_scope(scopeid)
+                    .and_then(ScopeState::parent_error_boundary);
+                diff_state.deferred.extend(boundary.map(|b| b.scope_id()));
+            } else if self.scopes.is_suspended(scopeid) {
+                // scopes inside a suspended boundary are recreated from scratch once it resolves
+                diff_state.muted(|state| state.diff_scope(scopeid));
+            } else {
+                diff_state.diff_scope(scopeid);
+            }
 
-            let mut ran_scopes = FxHashSet::default();
+            if diff_state.is_paused() {
+                // the scope's edits are held back until the rest of its nodes have been diffed
+                for scope in diff_state.mutations.dirty_scopes.drain() {
+                    self.dirty_scopes.remove(&scope);
+                    rendered.insert(scope);
+                }
 
-            // Sort the scopes by height. Theoretically, we'll de-duplicate scopes by height
-            self.dirty_scopes
-                .retain(|id| scopes.get_scope(*id).is_some());
+                for scope in diff_state.deferred.drain(..) {
+                    let entry = self.dirty_scopes.entry(scope).or_insert(priority);
+                    *entry = (*entry).max(priority);
+                }
 
-            self.dirty_scopes.sort_by(|a, b| {
-                let h1 = scopes.get_scope(*a).unwrap().height;
-                let h2 = scopes.get_scope(*b).unwrap().height;
-                h1.cmp(&h2).reverse()
-            });
+                // safety: the nodes the diff holds onto stay alive, since no scope renders until it has finished
+                let diff = unsafe {
+                    std::mem::transmute::<PausedDiff<'_>, PausedDiff<'static>>(
+                        diff_state.pause(scopeid),
+                    )
+                };
+                self.paused = Some(PausedScope {
+                    diff,
+                    priority,
+                    rendered,
+                });
+                break;
+            }
 
-            log::trace!("dirty_scopes: {:?}", self.dirty_scopes);
+            // boundaries are swapped in once their descendants have caught up
+            let boundary = scopes
+                .get_scope(scopeid)
+                .and_then(ScopeState::own_suspense_boundary);
 
-            if let Some(scopeid) = self.dirty_scopes.pop() {
-                if !ran_scopes.contains(&scopeid) {
-                    ran_scopes.insert(scopeid);
+            if let Some(boundary) = boundary {
+                if boundary.status.get() == SuspenseStatus::Resolving {
+                    resolving.push(boundary);
+                }
+            }
 
-                    self.scopes.run_scope(scopeid);
+            let DiffState {
+                mut mutations,
+                deferred,
+                out_of_time,
+                ..
+            } = diff_state;
 
-                    diff_state.diff_scope(scopeid);
+            log::trace!("succesffuly resolved scopes {:?}", mutations.dirty_scopes);
 
-                    let DiffState { mutations, .. } = diff_state;
+            for scope in &mutations.dirty_scopes {
+                self.dirty_scopes.remove(scope);
+            }
+            mutations.dirty_scopes.extend(rendered);
 
-                    log::trace!("succesffuly resolved scopes {:?}", mutations.dirty_scopes);
+            // children we ran out of time for, and boundaries that caught an error, are picked up next
+            for scope in deferred {
+                let entry = self.dirty_scopes.entry(scope).or_insert(priority);
+                *entry = (*entry).max(priority);
+            }
 
-                    for scope in &mutations.dirty_scopes {
-                        self.dirty_scopes.remove(scope);
-                    }
+            // a ref can move to another element without any edits
+            if !mutations.edits.is_empty() || !mutations.refs.is_empty() {
+                committed_mutations.push(mutations);
+            }
 
-                    if !mutations.edits.is_empty() {
-                        committed_mutations.push(mutations);
-                    }
+            if out_of_time || deadline() {
+                break;
+            }
+
+            // events that are more important than the remaining work are handled first
+            let remaining = self.dirty_scopes.values().max().copied();
+            let preempted = match remaining {
+                Some(remaining) => {
+                    has_preempting_event(&mut self.channel.1, &mut self.pending_messages, remaining)
+                }
+                None => false,
+            };
+
+            if preempted {
+                log::trace!("preempted by a higher priority event");
+                break;
+            }
+        }
+
+        if !resolving.is_empty() {
+            let mut diff_state = DiffState::new(&self.scopes);
 
-                    // todo: pause the diff machine
-                    // if diff_state.work(&mut deadline) {
-                    //     let DiffState { mutations, .. } = diff_state;
-                    //     for scope in &mutations.dirty_scopes {
-                    //         self.dirty_scopes.remove(scope);
-                    //     }
-                    //     committed_mutations.push(mutations);
-                    // } else {
-                    //     // leave the work in an incomplete state
-                    //     //
-                    //     // todo: we should store the edits and re-apply them later
-                    //     // for now, we just dump the work completely (threadsafe)
-                    //     return committed_mutations;
-                    // }
+            for boundary in resolving {
+                let still_mounted = self.scopes.get_scope(boundary.scope_id()).is_some();
+                if still_mounted && boundary.status.get() == SuspenseStatus::Resolving {
+                    diff_state.resolve_suspense(&boundary);
                 }
             }
+
+            committed_mutations.push(diff_state.mutations);
         }
 
+        #[cfg(feature = "profile")]
+        self.scopes
+            .profiler
+            .borrow_mut()
+            .end_cycle(committed_mutations.iter().map(|m| m.edits.len()).sum());
+
         committed_mutations
     }
 
@@ -539,22 +794,100 @@ impl VirtualDom {
         let scope_id = ScopeId(0);
         let mut diff_state = DiffState::new(&self.scopes);
 
+        #[cfg(feature = "profile")]
+        {
+            let mut profiler = self.scopes.profiler.borrow_mut();
+            profiler.begin_cycle(CycleKind::Rebuild);
+            profiler.mark_mounted(scope_id, None);
+        }
+
         self.scopes.run_scope(scope_id);
 
         diff_state.element_stack.push(ElementId(0));
         diff_state.scope_stack.push(scope_id);
 
         let node = self.scopes.fin_head(scope_id);
-        let created = diff_state.create_node(node);
+        let boundary = self
+            .scopes
+            .get_scope(scope_id)
+            .unwrap()
+            .own_suspense_boundary();
+        let created = diff_state.profile_diff(scope_id, |state| match boundary {
+            Some(boundary) if !boundary.is_resolved() => {
+                state.create_suspense_boundary(&boundary, node)
+            }
+            _ => state.create_node(node),
+        });
 
         diff_state.mutations.append_children(created as u32);
 
         self.dirty_scopes.clear();
         assert!(self.dirty_scopes.is_empty());
 
+        #[cfg(feature = "profile")]
+        self.scopes
+            .profiler
+            .borrow_mut()
+            .end_cycle(diff_state.mutations.edits.len());
+
         diff_state.mutations
     }
 
+    /// Tell the VirtualDom that the renderer has applied the mutations returned by [`VirtualDom::rebuild`] or
+    /// [`VirtualDom::work_with_deadline`].
+    ///
+    /// This runs the callbacks queued with [`ScopeState::queue_effect`], children before their parents. Renderers
+    /// should call this once the edits are visible, so the callbacks can rely on the nodes being mounted.
+    ///
+    /// Renderers that hand their edits to another process - the desktop webview and liveview - wait for the page to
+    /// acknowledge every batch they sent before calling this.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(App);
+    ///
+    /// apply_edits(dom.rebuild());
+    /// dom.mutations_applied();
+    ///
+    /// loop {
+    ///     dom.wait_for_work().await;
+    ///     apply_edits(dom.work_with_deadline(|| false));
+    ///     dom.mutations_applied();
+    /// }
+    /// ```
+    pub fn mutations_applied(&mut self) {
+        for effect in self.scopes.effects.take() {
+            effect();
+        }
+    }
+
+    /// Get the profile recorded so far.
+    ///
+    /// Only available with the `profile` feature. See [`ProfileReport`] for what gets recorded.
+    #[cfg(feature = "profile")]
+    pub fn profile(&self) -> std::cell::Ref<'_, ProfileReport> {
+        std::cell::Ref::map(self.scopes.profiler.borrow(), |profiler| &profiler.report)
+    }
+
+    /// Take the profile recorded so far, starting a fresh one.
+    ///
+    /// Only available with the `profile` feature. Profiles grow with every cycle, so long-running apps should take
+    /// them periodically.
+    ///
+    /// # Example
+    ///
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(App);
+    /// dom.rebuild();
+    ///
+    /// dom.take_profile().write_chrome_trace("rebuild.json").unwrap();
+    /// ```
+    #[cfg(feature = "profile")]
+    pub fn take_profile(&mut self) -> ProfileReport {
+        std::mem::take(&mut self.scopes.profiler.borrow_mut().report)
+    }
+
     /// Compute a manual diff of the VirtualDom between states.
     ///
     /// This can be useful when state inside the DOM is remotely changed from the outside, but not propagated as an event.
@@ -586,22 +919,57 @@ impl VirtualDom {
     /// let edits = dom.diff();
     /// ```
     pub fn hard_diff(&mut self, scope_id: ScopeId) -> Mutations {
-        let mut diff_machine = DiffState::new(&self.scopes);
-        self.scopes.run_scope(scope_id);
+        force_diff(&self.scopes, scope_id, self.paused.take())
+    }
 
-        let (old, new) = (
-            diff_machine.scopes.wip_head(scope_id),
-            diff_machine.scopes.fin_head(scope_id),
-        );
+    /// Replace the static parts of an `rsx!` call with a template sent by the hot reloader.
+    ///
+    /// Every scope that rendered the call is re-rendered with [`VirtualDom::hard_diff`], and the call keeps using the
+    /// template in future renders. The template is ignored if it uses dynamic parts the call wasn't compiled with.
+    ///
+    /// # Example
+    /// ```rust, ignore
+    /// let mut dom = VirtualDom::new(app);
+    /// dom.rebuild();
+    ///
+    /// for template in templates_from_the_file_watcher {
+    ///     for mutations in dom.replace_template(template) {
+    ///         renderer.apply(mutations);
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "hot-reload")]
+    pub fn replace_template(&mut self, template: RsxTemplate) -> Vec<Mutations<'_>> {
+        let scopes = &self.scopes;
+        scopes
+            .hot_reload
+            .retain_scopes(|id| scopes.get_scope(id).is_some());
+
+        let mut dirty = scopes.hot_reload.replace(template);
+        dirty.sort_by_key(|id| scopes.get_scope(*id).map(|scope| scope.height));
 
-        diff_machine.force_diff = true;
-        diff_machine.scope_stack.push(scope_id);
-        let scope = diff_machine.scopes.get_scope(scope_id).unwrap();
-        diff_machine.element_stack.push(scope.container);
+        // re-rendering a scope forces its children to re-render too
+        let mut rendered = FxHashSet::default();
+        let mut mutations = Vec::new();
+
+        if let Some(paused) = self.paused.take() {
+            let mut diff_state = DiffState::new(scopes);
+            resume(&mut diff_state, paused.diff);
+            mutations.push(diff_state.mutations);
+        }
+
+        for id in dirty {
+            if rendered.contains(&id) || scopes.get_scope(id).is_none() {
+                continue;
+            }
 
-        diff_machine.diff_node(old, new);
+            let diff = force_diff(scopes, id, None);
+            rendered.insert(id);
+            rendered.extend(diff.dirty_scopes.iter().copied());
+            mutations.push(diff);
+        }
 
-        diff_machine.mutations
+        mutations
     }
 
     /// Renders an `rsx` call into the Base Scope's allocator.
@@ -735,11 +1103,18 @@ impl Drop for VirtualDom {
         // todo: move the remove nodes method onto scopearena
         // this will clear *all* scopes *except* the root scope
         let mut machine = DiffState::new(&self.scopes);
+
+        // a paused diff leaves new nodes unmounted, so it's finished before they're removed
+        if let Some(paused) = self.paused.take() {
+            resume(&mut machine, paused.diff);
+        }
+
         machine.remove_nodes([scope.root_node()], false);
 
         // Now, clean up the root scope
         // safety: there are no more references to the root scope
         let scope = unsafe { &mut *self.scopes.get_scope_raw(ScopeId(0)).unwrap() };
+        scope.run_destroy_callbacks();
         scope.reset();
 
         // make sure there are no "live" components
@@ -759,3 +1134,58 @@ impl Drop for VirtualDom {
         }
     }
 }
+
+/// Re-render a scope and diff it, re-rendering its children even if their props are memoized.
+///
+/// A diff that ran out of time is finished first, since the scope can't render while it's holding onto its nodes.
+fn force_diff(
+    scopes: &ScopeArena,
+    scope_id: ScopeId,
+    paused: Option<PausedScope>,
+) -> Mutations<'_> {
+    let mut diff_machine = DiffState::new(scopes);
+    if let Some(paused) = paused {
+        resume(&mut diff_machine, paused.diff);
+    }
+
+    if !scopes.run_scope(scope_id) {
+        // the scope failed into an error boundary, which replaces its nodes when it re-renders
+        return diff_machine.mutations;
+    }
+
+    let (old, new) = (
+        diff_machine.scopes.wip_head(scope_id),
+        diff_machine.scopes.fin_head(scope_id),
+    );
+
+    diff_machine.force_diff = true;
+    diff_machine.scope_stack.push(scope_id);
+    let scope = diff_machine.scopes.get_scope(scope_id).unwrap();
+    diff_machine.element_stack.push(scope.container);
+
+    diff_machine.diff_node(old, new);
+
+    diff_machine.mutations
+}
+
+/// Pick up the diff of a scope that ran out of time partway through.
+fn resume<'b>(diff_state: &mut DiffState<'b, '_>, paused: PausedDiff<'static>) {
+    // safety: the nodes the diff holds onto are still alive, since no scope renders until it has finished
+    let paused = unsafe { std::mem::transmute::<PausedDiff<'static>, PausedDiff<'b>>(paused) };
+    diff_state.resume(paused);
+}
+
+/// Check if an event waiting to be handled is more important than work of the given priority.
+fn has_preempting_event(
+    channel: &mut UnboundedReceiver<SchedulerMsg>,
+    pending_messages: &mut VecDeque<SchedulerMsg>,
+    priority: EventPriority,
+) -> bool {
+    while let Ok(msg) = channel.try_recv() {
+        pending_messages.push_front(msg);
+    }
+
+    pending_messages
+        .iter()
+        .any(|msg| matches!(msg, SchedulerMsg::Event(event) if event.priority > priority))
+}
@@ -1,5 +1,9 @@
-use crate::desktop_context::{DesktopContext, UserWindowEvent};
+use crate::desktop_context::{DesktopContext, DesktopElement, UserWindowEvent};
 use dioxus_core::*;
+use futures_util::{
+    future::{select, Either},
+    pin_mut, StreamExt,
+};
 use std::{
     collections::HashMap,
     sync::Arc,
@@ -14,6 +18,7 @@ use wry::{
 pub(super) struct DesktopController {
     pub(super) webviews: HashMap<WindowId, WebView>,
     pub(super) sender: futures_channel::mpsc::UnboundedSender<SchedulerMsg>,
+    pub(super) edits_applied: futures_channel::mpsc::UnboundedSender<()>,
     pub(super) pending_edits: Arc<Mutex<Vec<String>>>,
     pub(super) quit_app_on_close: bool,
     pub(super) is_ready: Arc<AtomicBool>,
@@ -29,6 +34,7 @@ impl DesktopController {
     ) -> Self {
         let edit_queue = Arc::new(Mutex::new(Vec::new()));
         let (sender, receiver) = futures_channel::mpsc::unbounded::<SchedulerMsg>();
+        let (edits_applied, applied_receiver) = futures_channel::mpsc::unbounded::<()>();
 
         let pending_edits = edit_queue.clone();
         let return_sender = sender.clone();
@@ -44,10 +50,11 @@ impl DesktopController {
             runtime.block_on(async move {
                 let mut dom =
                     VirtualDom::new_with_props_and_scheduler(root, props, (sender, receiver));
+                dom.enable_templates();
 
                 let window_context = DesktopContext::new(desktop_context_proxy);
 
-                dom.base_scope().provide_context(window_context);
+                dom.base_scope().provide_context(window_context.clone());
 
                 let edits = dom.rebuild();
 
@@ -58,20 +65,49 @@ impl DesktopController {
 
                 // Make sure the window is ready for any new updates
                 proxy.send_event(UserWindowEvent::Update).unwrap();
+                set_node_refs(&edits, &window_context);
 
-                loop {
-                    dom.wait_for_work().await;
-
-                    let muts = dom.work_with_deadline(|| false);
+                // batches sent to the webview that it hasn't acknowledged yet
+                let mut in_flight = 1;
+                let mut messages = vdom_messages(applied_receiver);
 
-                    for edit in muts {
+                loop {
+                    let message = {
+                        let work = dom.wait_for_work();
+                        pin_mut!(work);
+                        match select(work, messages.next()).await {
+                            Either::Left(_) => None,
+                            Either::Right((message, _)) => message,
+                        }
+                    };
+
+                    let muts = match message {
+                        // effects wait until the webview has caught up with every batch
+                        Some(VdomMessage::EditsApplied) => {
+                            in_flight -= 1;
+                            if in_flight == 0 {
+                                dom.mutations_applied();
+                            }
+                            continue;
+                        }
+                        // templates from the hot reloader are applied as soon as they arrive
+                        #[cfg(feature = "hot-reload")]
+                        Some(VdomMessage::Template(template)) => dom.replace_template(template),
+                        None => dom.work_with_deadline(|| false),
+                    };
+
+                    for edit in &muts {
                         edit_queue
                             .lock()
                             .unwrap()
                             .push(serde_json::to_string(&edit.edits).unwrap());
+                        in_flight += 1;
                     }
 
                     let _ = proxy.send_event(UserWindowEvent::Update);
+                    for edit in &muts {
+                        set_node_refs(edit, &window_context);
+                    }
                 }
             })
         });
@@ -79,6 +115,7 @@ impl DesktopController {
         Self {
             pending_edits,
             sender: return_sender,
+            edits_applied,
             webviews: HashMap::new(),
             is_ready: Arc::new(AtomicBool::new(false)),
             quit_app_on_close: true,
@@ -105,3 +142,36 @@ impl DesktopController {
         }
     }
 }
+
+/// A message for the VirtualDom from outside of it
+enum VdomMessage {
+    /// The webview applied a batch of edits
+    EditsApplied,
+
+    /// A new template for an `rsx!` call, sent by the hot reloader
+    #[cfg(feature = "hot-reload")]
+    Template(RsxTemplate),
+}
+
+/// Merge the acknowledgements from the webview with the templates from the hot reloader
+fn vdom_messages(
+    applied: futures_channel::mpsc::UnboundedReceiver<()>,
+) -> impl futures_util::Stream<Item = VdomMessage> + Unpin {
+    let messages = applied.map(|_| VdomMessage::EditsApplied);
+
+    #[cfg(feature = "hot-reload")]
+    let messages = futures_util::stream::select(
+        messages,
+        dioxus_rsx::hot_reload::subscribe().map(VdomMessage::Template),
+    );
+
+    messages
+}
+
+// scripts are evaluated in the order they're sent, so the handles only run after the edits are applied
+fn set_node_refs(mutations: &Mutations, desktop: &DesktopContext) {
+    for r in &mutations.refs {
+        r.node_ref
+            .set(DesktopElement::new(r.element_id, desktop.clone()));
+    }
+}
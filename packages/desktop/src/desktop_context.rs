@@ -1,5 +1,5 @@
 use crate::controller::DesktopController;
-use dioxus_core::ScopeState;
+use dioxus_core::{ElementId, ScopeState};
 use wry::application::event_loop::ControlFlow;
 use wry::application::event_loop::EventLoopProxy;
 use wry::application::window::Fullscreen as WryFullscreen;
@@ -136,6 +136,49 @@ impl DesktopContext {
     }
 }
 
+/// A handle to an element in the WebView, provided to the [`dioxus_core::NodeRef`]s attached to it.
+///
+/// The element lives in another process, so methods are called by evaluating a script in the WebView.
+#[derive(Clone)]
+pub struct DesktopElement {
+    id: ElementId,
+    desktop: DesktopContext,
+}
+
+impl DesktopElement {
+    pub(crate) fn new(id: ElementId, desktop: DesktopContext) -> Self {
+        Self { id, desktop }
+    }
+
+    /// The id of the element
+    pub fn id(&self) -> ElementId {
+        self.id
+    }
+
+    /// Focus the element
+    pub fn focus(&self) {
+        self.call("focus");
+    }
+
+    /// Remove focus from the element
+    pub fn blur(&self) {
+        self.call("blur");
+    }
+
+    /// Scroll the element's parents until the element is visible
+    pub fn scroll_into_view(&self) {
+        self.call("scrollIntoView");
+    }
+
+    /// Call a method of the element with no arguments, ie `click`
+    pub fn call(&self, method: &str) {
+        self.desktop.eval(format!(
+            "window.interpreter.nodes[{}].{}()",
+            self.id.0, method
+        ));
+    }
+}
+
 #[derive(Debug)]
 pub enum UserWindowEvent {
     Update,
@@ -11,7 +11,7 @@ mod events;
 mod protocol;
 
 use desktop_context::UserWindowEvent;
-pub use desktop_context::{use_eval, use_window, DesktopContext};
+pub use desktop_context::{use_eval, use_window, DesktopContext, DesktopElement};
 pub use wry;
 pub use wry::application as tao;
 
@@ -121,6 +121,7 @@ pub fn launch_with_props<P: 'static + Send>(
                 let window_id = window.id();
 
                 let (is_ready, sender) = (desktop.is_ready.clone(), desktop.sender.clone());
+                let edits_applied = desktop.edits_applied.clone();
 
                 let proxy = proxy.clone();
 
@@ -142,6 +143,9 @@ pub fn launch_with_props<P: 'static + Send>(
                                     log::trace!("User event: {:?}", event);
                                     sender.unbounded_send(SchedulerMsg::Event(event)).unwrap();
                                 }
+                                "edits_applied" => {
+                                    let _ = edits_applied.unbounded_send(());
+                                }
                                 "initialize" => {
                                     is_ready.store(true, std::sync::atomic::Ordering::Relaxed);
                                     let _ = proxy.send_event(UserWindowEvent::Update);
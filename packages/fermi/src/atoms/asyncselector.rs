@@ -0,0 +1,145 @@
+use crate::{AtomId, AtomRoot, Derivation, Readable, SelectorBuilder};
+use dioxus_hooks::UseFutureState;
+use std::{future::Future, pin::Pin, rc::Rc};
+
+/// The future of an [`AsyncSelector`]
+pub type SelectorFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>>>>;
+
+/// A value derived from other atoms by a future, like a request to a server.
+///
+/// The atoms are read before the future is created, and the future is spawned as a task of the app. When one of the
+/// atoms changes, the running future is cancelled and a new one is started. Reading the selector gives its
+/// [`AsyncState`].
+///
+/// ```rust, ignore
+/// static USER_ID: Atom<u32> = |_| 0;
+/// static USER: AsyncSelector<User, String> = |s| {
+///     let id = s.get(USER_ID);
+///     Box::pin(async move { fetch_user(id).await })
+/// };
+///
+/// fn Profile(cx: Scope) -> Element {
+///     match use_read(&cx, USER).state() {
+///         UseFutureState::Pending => cx.render(rsx!("loading...")),
+///         UseFutureState::Complete(Ok(user)) | UseFutureState::Reloading(Ok(user)) => {
+///             cx.render(rsx!("{user.name}"))
+///         }
+///         UseFutureState::Complete(Err(error)) | UseFutureState::Reloading(Err(error)) => {
+///             cx.render(rsx!("failed to load the user: {error}"))
+///         }
+///     }
+/// }
+/// ```
+pub type AsyncSelector<T, E> = fn(SelectorBuilder) -> SelectorFuture<T, E>;
+
+/// The state of an [`AsyncSelector`]: pending until its first future resolves, then ready or failed.
+///
+/// When the selector is restarted, it keeps its last result until the new future resolves.
+pub struct AsyncState<T, E> {
+    result: Option<Rc<Result<T, E>>>,
+    reloading: bool,
+}
+
+impl<T, E> AsyncState<T, E> {
+    pub(crate) fn pending() -> Self {
+        Self {
+            result: None,
+            reloading: false,
+        }
+    }
+
+    pub(crate) fn reloading(previous: &Self) -> Self {
+        Self {
+            result: previous.result.clone(),
+            reloading: previous.result.is_some(),
+        }
+    }
+
+    pub(crate) fn ready(result: Result<T, E>) -> Self {
+        Self {
+            result: Some(Rc::new(result)),
+            reloading: false,
+        }
+    }
+
+    /// Check if no future has resolved yet
+    pub fn is_pending(&self) -> bool {
+        self.result.is_none()
+    }
+
+    /// Check if a new future is running to replace the current result
+    pub fn is_reloading(&self) -> bool {
+        self.reloading
+    }
+
+    /// Get the value of the last future that resolved, if it succeeded
+    pub fn value(&self) -> Option<&T> {
+        self.result()?.as_ref().ok()
+    }
+
+    /// Get the error of the last future that resolved, if it failed
+    pub fn error(&self) -> Option<&E> {
+        self.result()?.as_ref().err()
+    }
+
+    pub fn result(&self) -> Option<&Result<T, E>> {
+        self.result.as_deref()
+    }
+
+    /// Get the state in the form [`use_future`](dioxus_hooks::use_future) gives it
+    pub fn state(&self) -> UseFutureState<'_, Result<T, E>> {
+        match (&self.result, self.reloading) {
+            (None, _) => UseFutureState::Pending,
+            (Some(result), true) => UseFutureState::Reloading(result),
+            (Some(result), false) => UseFutureState::Complete(result),
+        }
+    }
+}
+
+// every future produces a new result, so results are compared by identity rather than by value
+impl<T, E> PartialEq for AsyncState<T, E> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_result = match (&self.result, &other.result) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        same_result && self.reloading == other.reloading
+    }
+}
+
+impl<T, E> Clone for AsyncState<T, E> {
+    fn clone(&self) -> Self {
+        Self {
+            result: self.result.clone(),
+            reloading: self.reloading,
+        }
+    }
+}
+
+impl<T: 'static, E: 'static> Readable<AsyncState<T, E>> for AsyncSelector<T, E> {
+    // reading never starts the future, so a selector that hasn't resolved yet has nothing to give
+    fn read(&self, root: &AtomRoot) -> Option<AsyncState<T, E>> {
+        let atoms = root.atoms.borrow();
+        let slot = atoms.get(&self.unique_id())?;
+        let state = slot.value.downcast_ref::<AsyncState<T, E>>()?;
+        (!state.is_pending()).then(|| state.clone())
+    }
+
+    // futures only run inside of a root
+    fn init(&self) -> AsyncState<T, E> {
+        AsyncState::pending()
+    }
+
+    fn unique_id(&self) -> AtomId {
+        AtomId::new(*self as *const ())
+    }
+
+    fn derivation(&self) -> Option<Derivation> {
+        let f = *self;
+        Some(Derivation::new(move |builder: SelectorBuilder| {
+            let (root, id) = (builder.root(), builder.id().clone());
+            root.start_async(&id, f(builder))
+        }))
+    }
+}
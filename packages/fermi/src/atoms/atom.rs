@@ -3,21 +3,24 @@ use crate::{AtomId, AtomRoot, Readable, Writable};
 pub type Atom<T> = fn(AtomBuilder) -> T;
 pub struct AtomBuilder;
 
-impl<V> Readable<V> for Atom<V> {
-    fn read(&self, _root: AtomRoot) -> Option<V> {
-        todo!()
+impl<V: Clone + 'static> Readable<V> for Atom<V> {
+    fn read(&self, root: &AtomRoot) -> Option<V> {
+        let atoms = root.atoms.borrow();
+        let slot = atoms.get(&self.unique_id())?;
+        slot.value.downcast_ref::<V>().cloned()
     }
     fn init(&self) -> V {
         (*self)(AtomBuilder)
     }
     fn unique_id(&self) -> AtomId {
-        *self as *const ()
+        AtomId::new(*self as *const ())
     }
 }
 
-impl<V> Writable<V> for Atom<V> {
-    fn write(&self, _root: AtomRoot, _value: V) {
-        todo!()
+impl<V: Clone + 'static> Writable<V> for Atom<V> {
+    fn write(&self, root: &AtomRoot, value: V) {
+        root.initialize(*self);
+        root.set(self.unique_id(), value);
     }
 }
 
@@ -26,3 +29,14 @@ fn atom_compiles() {
     static TEST_ATOM: Atom<&str> = |_| "hello";
     dbg!(TEST_ATOM.init());
 }
+
+#[test]
+fn atoms_are_read_from_the_root() {
+    static TEST_ATOM: Atom<&str> = |_| "hello";
+    let root = AtomRoot::new(std::sync::Arc::new(|_| {}));
+
+    // atoms are only in the root once something initializes them
+    assert_eq!(TEST_ATOM.read(&root), None);
+    TEST_ATOM.write(&root, "world");
+    assert_eq!(TEST_ATOM.read(&root), Some("world"));
+}
@@ -1,12 +1,28 @@
 use crate::{AtomId, AtomRoot, Readable, Writable};
 use im_rc::HashMap as ImMap;
+use std::hash::Hash;
 
 pub struct AtomFamilyBuilder;
+
+/// A keyed collection of atoms.
+///
+/// Components can read a single key with [`use_atom_family`](crate::use_atom_family), and only re-render when that
+/// key changes. Reading the whole family subscribes to every key.
+///
+/// ```rust, ignore
+/// static USERS: AtomFamily<u32, String> = |_| Default::default();
+/// ```
 pub type AtomFamily<K, V> = fn(AtomFamilyBuilder) -> ImMap<K, V>;
 
-impl<K, V> Readable<ImMap<K, V>> for AtomFamily<K, V> {
-    fn read(&self, _root: AtomRoot) -> Option<ImMap<K, V>> {
-        todo!()
+impl<K, V> Readable<ImMap<K, V>> for AtomFamily<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn read(&self, root: &AtomRoot) -> Option<ImMap<K, V>> {
+        let atoms = root.atoms.borrow();
+        let slot = atoms.get(&self.unique_id())?;
+        slot.value.downcast_ref::<ImMap<K, V>>().cloned()
     }
 
     fn init(&self) -> ImMap<K, V> {
@@ -14,12 +30,17 @@ impl<K, V> Readable<ImMap<K, V>> for AtomFamily<K, V> {
     }
 
     fn unique_id(&self) -> AtomId {
-        *self as *const ()
+        AtomId::new(*self as *const ())
     }
 }
 
-impl<K, V> Writable<ImMap<K, V>> for AtomFamily<K, V> {
-    fn write(&self, _root: AtomRoot, _value: ImMap<K, V>) {
-        todo!()
+impl<K, V> Writable<ImMap<K, V>> for AtomFamily<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    fn write(&self, root: &AtomRoot, value: ImMap<K, V>) {
+        root.initialize(*self);
+        root.set(self.unique_id(), value);
     }
 }
@@ -5,7 +5,7 @@ pub struct AtomRefBuilder;
 pub type AtomRef<T> = fn(AtomRefBuilder) -> T;
 
 impl<V> Readable<RefCell<V>> for AtomRef<V> {
-    fn read(&self, _root: AtomRoot) -> Option<RefCell<V>> {
+    fn read(&self, _root: &AtomRoot) -> Option<RefCell<V>> {
         todo!()
     }
 
@@ -14,7 +14,7 @@ impl<V> Readable<RefCell<V>> for AtomRef<V> {
     }
 
     fn unique_id(&self) -> AtomId {
-        *self as *const ()
+        AtomId::new(*self as *const ())
     }
 }
 
@@ -1 +1,107 @@
+use crate::{AtomId, AtomRoot, Readable};
+use std::{any::Any, rc::Rc, sync::Arc};
 
+/// A value derived from other atoms.
+///
+/// Selectors are recomputed lazily when one of the atoms they read changes, and the components that read them only
+/// re-render if the new value isn't equal to the old one.
+///
+/// ```rust, ignore
+/// static PRICE: Atom<u32> = |_| 10;
+/// static QUANTITY: Atom<u32> = |_| 2;
+/// static TOTAL: Selector<u32> = |s| s.get(PRICE) * s.get(QUANTITY);
+/// ```
+pub type Selector<T> = fn(SelectorBuilder) -> T;
+
+/// Gives a selector access to the atoms it depends on.
+///
+/// Every atom read through the builder becomes a dependency of the selector.
+pub struct SelectorBuilder<'a> {
+    root: &'a AtomRoot,
+    id: AtomId,
+}
+
+impl<'a> SelectorBuilder<'a> {
+    pub(crate) fn new(root: &'a AtomRoot, id: AtomId) -> Self {
+        Self { root, id }
+    }
+
+    pub(crate) fn root(&self) -> &'a AtomRoot {
+        self.root
+    }
+
+    pub(crate) fn id(&self) -> &AtomId {
+        &self.id
+    }
+
+    /// Get a clone of the current value of an atom
+    pub fn get<V: Clone + 'static>(&self, atom: impl Readable<V>) -> V {
+        self.get_rc(atom).as_ref().clone()
+    }
+
+    /// Get the current value of an atom in its Rc container
+    pub fn get_rc<V: 'static>(&self, atom: impl Readable<V>) -> Rc<V> {
+        self.root.read_dependency(atom, &self.id)
+    }
+}
+
+type Compute = Rc<dyn Fn(SelectorBuilder) -> Rc<dyn Any>>;
+
+/// How the [`AtomRoot`] computes a derived value, and decides whether it changed.
+#[derive(Clone)]
+pub struct Derivation {
+    compute: Compute,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+impl Derivation {
+    pub fn new<V: PartialEq + 'static>(f: impl Fn(SelectorBuilder) -> V + 'static) -> Self {
+        Self {
+            compute: Rc::new(move |builder| Rc::new(f(builder))),
+            eq: same_value::<V>,
+        }
+    }
+
+    pub(crate) fn compute(&self, builder: SelectorBuilder) -> Rc<dyn Any> {
+        (self.compute)(builder)
+    }
+
+    pub(crate) fn same(&self, old: &dyn Any, new: &dyn Any) -> bool {
+        (self.eq)(old, new)
+    }
+}
+
+fn same_value<V: PartialEq + 'static>(old: &dyn Any, new: &dyn Any) -> bool {
+    match (old.downcast_ref::<V>(), new.downcast_ref::<V>()) {
+        (Some(old), Some(new)) => old == new,
+        _ => false,
+    }
+}
+
+impl<V: PartialEq + Clone + 'static> Readable<V> for Selector<V> {
+    // goes through the root so a stale value is recomputed first
+    fn read(&self, root: &AtomRoot) -> Option<V> {
+        Some(root.read(*self).as_ref().clone())
+    }
+
+    // outside of a root, the selector sees the initial value of every atom
+    fn init(&self) -> V {
+        let root = AtomRoot::new(Arc::new(|_| {}));
+        (*self)(SelectorBuilder::new(&root, self.unique_id()))
+    }
+
+    fn unique_id(&self) -> AtomId {
+        AtomId::new(*self as *const ())
+    }
+
+    fn derivation(&self) -> Option<Derivation> {
+        Some(Derivation::new(*self))
+    }
+}
+
+#[test]
+fn selector_compiles() {
+    static NAME: crate::Atom<&str> = |_| "world";
+    static GREETING: Selector<String> = |s| format!("hello {}", s.get(NAME));
+    assert_eq!(GREETING.init(), "hello world");
+}
@@ -1 +1,48 @@
+use crate::{AtomId, AtomRoot, Derivation, Readable, SelectorBuilder};
+use std::{hash::Hash, sync::Arc};
 
+/// A selector that takes a key, like the id of an item.
+///
+/// Each key is computed and tracked separately, and is read by pairing the family with the key.
+///
+/// ```rust, ignore
+/// static PRICES: Atom<Vec<u32>> = |_| vec![10, 20];
+/// static PRICE: SelectorFamily<usize, Option<u32>> = |s, id| s.get(PRICES).get(*id).copied();
+///
+/// let price = use_read(&cx, (PRICE, 1));
+/// ```
+pub type SelectorFamily<K, V> = fn(SelectorBuilder, &K) -> V;
+
+impl<K, V> Readable<V> for (SelectorFamily<K, V>, K)
+where
+    K: Hash + Eq + Clone + 'static,
+    V: PartialEq + Clone + 'static,
+{
+    // goes through the root so a stale value is recomputed first
+    fn read(&self, root: &AtomRoot) -> Option<V> {
+        Some(root.read(self.clone()).as_ref().clone())
+    }
+
+    fn init(&self) -> V {
+        let root = AtomRoot::new(Arc::new(|_| {}));
+        (self.0)(SelectorBuilder::new(&root, self.unique_id()), &self.1)
+    }
+
+    fn unique_id(&self) -> AtomId {
+        AtomId::with_key(self.0 as *const (), self.1.clone())
+    }
+
+    fn derivation(&self) -> Option<Derivation> {
+        let (f, key) = self.clone();
+        Some(Derivation::new(move |builder| f(builder, &key)))
+    }
+}
+
+#[test]
+fn selector_family_compiles() {
+    static COUNT: crate::Atom<u32> = |_| 2;
+    static TIMES: SelectorFamily<u32, u32> = |s, by| s.get(COUNT) * by;
+    assert_eq!((TIMES, 3).init(), 6);
+    assert_eq!((TIMES, 3).unique_id(), (TIMES, 3).unique_id());
+    assert_ne!((TIMES, 3).unique_id(), (TIMES, 4).unique_id());
+}
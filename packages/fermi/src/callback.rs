@@ -1,11 +1,13 @@
-#![allow(clippy::all, unused)]
-
 use std::rc::Rc;
 
 use dioxus_core::prelude::*;
 
-use crate::{AtomRoot, Readable, Writable};
+use crate::{use_atom_root, AtomRoot, Readable, Writable};
 
+/// A handle to read and write atoms from event handlers and tasks.
+///
+/// Unlike [`use_read`](crate::use_read), reading atoms through the handle doesn't subscribe the component to them.
+/// The handle is cheap to clone and can be moved into async tasks.
 #[derive(Clone)]
 pub struct CallbackApi {
     root: Rc<AtomRoot>,
@@ -13,41 +15,52 @@ pub struct CallbackApi {
 
 impl CallbackApi {
     // get the current value of the atom
-    pub fn get<V>(&self, atom: impl Readable<V>) -> &V {
-        todo!()
+    pub fn get<V: Clone + 'static>(&self, atom: impl Readable<V>) -> V {
+        self.get_rc(atom).as_ref().clone()
     }
 
     // get the current value of the atom in its RC container
-    pub fn get_rc<V>(&self, atom: impl Readable<V>) -> &Rc<V> {
-        todo!()
+    pub fn get_rc<V: 'static>(&self, atom: impl Readable<V>) -> Rc<V> {
+        self.root.read(atom)
     }
 
     // set the current value of the atom
-    pub fn set<V>(&self, atom: impl Writable<V>, value: V) {
-        todo!()
+    pub fn set<V: 'static>(&self, atom: impl Writable<V>, value: V) {
+        atom.write(&self.root, value);
     }
-}
 
-pub fn use_atom_context(cx: &ScopeState) -> &CallbackApi {
-    todo!()
-}
-
-macro_rules! use_callback {
-    (&$cx:ident, [$($cap:ident),*],  move || $body:expr) => {
-        move || {
-            $(
-                #[allow(unused_mut)]
-                let mut $cap = $cap.to_owned();
-            )*
-            $cx.spawn($body);
-        }
-    };
+    /// Make several writes that notify the subscribers of the atoms they change only once
+    ///
+    /// ```rust, ignore
+    /// api.transaction(|api| {
+    ///     api.set(FIRST_NAME, "Ann".to_string());
+    ///     api.set(LAST_NAME, "Smith".to_string());
+    /// });
+    /// ```
+    pub fn transaction<R>(&self, f: impl FnOnce(&Self) -> R) -> R {
+        self.root.transaction(|| f(self))
+    }
 }
 
-#[macro_export]
-macro_rules! to_owned {
-    ($($es:ident),+) => {$(
-        #[allow(unused_mut)]
-        let mut $es = $es.to_owned();
-    )*}
+/// Get a [`CallbackApi`] for reading and writing atoms outside of render.
+///
+/// ```rust, ignore
+/// fn Refresh(cx: Scope) -> Element {
+///     let api = use_atom_context(&cx);
+///
+///     cx.render(rsx!(button {
+///         onclick: move |_| {
+///             let api = api.clone();
+///             cx.spawn(async move {
+///                 let users = fetch_users(api.get(PAGE)).await;
+///                 api.set(USERS, users);
+///             });
+///         },
+///         "refresh"
+///     }))
+/// }
+/// ```
+pub fn use_atom_context(cx: &ScopeState) -> &CallbackApi {
+    let root = use_atom_root(cx);
+    cx.use_hook(|_| CallbackApi { root: root.clone() })
 }
@@ -0,0 +1,107 @@
+use crate::{use_atom_root, AtomFamily, AtomId, AtomRoot};
+use dioxus_core::{ScopeId, ScopeState};
+use std::{hash::Hash, rc::Rc};
+
+/// Read and write one key of an [`AtomFamily`].
+///
+/// The component only re-renders when that key is written or removed, or when the whole family is replaced.
+///
+/// ```rust, ignore
+/// static USERS: AtomFamily<u32, String> = |_| Default::default();
+///
+/// #[inline_props]
+/// fn User(cx: Scope, id: u32) -> Element {
+///     let user = use_atom_family(&cx, USERS, *id);
+///
+///     match user.get() {
+///         Some(name) => cx.render(rsx!(
+///             "{name}"
+///             button { onclick: move |_| { user.remove(); }, "remove" }
+///         )),
+///         None => cx.render(rsx!("unknown user")),
+///     }
+/// }
+/// ```
+pub fn use_atom_family<K, V>(
+    cx: &ScopeState,
+    family: AtomFamily<K, V>,
+    key: K,
+) -> &UseAtomFamily<K, V>
+where
+    K: Hash + Eq + Clone + 'static,
+    V: Clone + 'static,
+{
+    let root = use_atom_root(cx);
+
+    let inner = cx.use_hook(|_| UseAtomFamily {
+        root: root.clone(),
+        family,
+        key: key.clone(),
+        scope_id: cx.scope_id(),
+        value: None,
+    });
+
+    // the component can switch to another key between renders
+    if inner.key != key {
+        inner.root.unsubscribe(inner.member_id(), inner.scope_id);
+        inner.key = key;
+    }
+
+    inner.value = inner
+        .root
+        .register_member(family, &inner.key, cx.scope_id());
+
+    inner
+}
+
+pub struct UseAtomFamily<K: Hash + Eq + Clone + 'static, V: Clone + 'static> {
+    root: Rc<AtomRoot>,
+    family: AtomFamily<K, V>,
+    key: K,
+    scope_id: ScopeId,
+    value: Option<V>,
+}
+
+impl<K: Hash + Eq + Clone + 'static, V: Clone + 'static> UseAtomFamily<K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Get the value of the key when the component rendered, if the family has it
+    pub fn get(&self) -> Option<&V> {
+        self.value.as_ref()
+    }
+
+    /// Get the current value of the key, which can be newer than [`get`](Self::get) in async contexts
+    pub fn current(&self) -> Option<V> {
+        self.root.read_member(self.family, &self.key)
+    }
+
+    /// Set the value of the key, adding it to the family if it's missing
+    pub fn set(&self, value: V) {
+        self.root.set_member(self.family, self.key.clone(), value);
+    }
+
+    /// Modify the current value of the key, if the family has it
+    pub fn with_mut(&self, apply: impl FnOnce(&mut V)) {
+        if let Some(mut value) = self.current() {
+            apply(&mut value);
+            self.set(value);
+        }
+    }
+
+    /// Remove the key from the family, returning its value
+    pub fn remove(&self) -> Option<V> {
+        self.root.remove_member(self.family, &self.key)
+    }
+
+    fn member_id(&self) -> AtomId {
+        AtomId::with_key(self.family as *const (), self.key.clone())
+    }
+}
+
+impl<K: Hash + Eq + Clone + 'static, V: Clone + 'static> Drop for UseAtomFamily<K, V> {
+    fn drop(&mut self) {
+        self.root.unsubscribe(self.member_id(), self.scope_id)
+    }
+}
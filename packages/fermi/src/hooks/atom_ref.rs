@@ -43,7 +43,7 @@ pub struct AtomRefSubscription {
 
 impl Drop for AtomRefSubscription {
     fn drop(&mut self) {
-        self.root.unsubscribe(self.ptr, self.scope_id)
+        self.root.unsubscribe(self.ptr.clone(), self.scope_id)
     }
 }
 
@@ -57,7 +57,7 @@ pub struct UseAtomRef<T> {
 impl<T> Clone for UseAtomRef<T> {
     fn clone(&self) -> Self {
         Self {
-            ptr: self.ptr,
+            ptr: self.ptr.clone(),
             value: self.value.clone(),
             root: self.root.clone(),
             scope_id: self.scope_id,
@@ -71,7 +71,7 @@ impl<T: 'static> UseAtomRef<T> {
     }
 
     pub fn write(&self) -> RefMut<T> {
-        self.root.force_update(self.ptr);
+        self.root.force_update(self.ptr.clone());
         self.value.borrow_mut()
     }
 
@@ -80,7 +80,7 @@ impl<T: 'static> UseAtomRef<T> {
     }
 
     pub fn set(&self, new: T) {
-        self.root.force_update(self.ptr);
-        self.root.set(self.ptr, new);
+        self.root.force_update(self.ptr.clone());
+        self.root.set(self.ptr.clone(), new);
     }
 }
@@ -6,6 +6,9 @@ use std::rc::Rc;
 pub fn use_atom_root(cx: &ScopeState) -> &Rc<AtomRoot> {
     cx.use_hook(|_| match cx.consume_context::<Rc<AtomRoot>>() {
         Some(root) => root,
-        None => cx.provide_root_context(Rc::new(AtomRoot::new(cx.schedule_update_any()))),
+        None => cx.provide_root_context(AtomRoot::with_tasks(
+            cx.schedule_update_any(),
+            cx.root_task_handle(),
+        )),
     })
 }
@@ -6,6 +6,9 @@ use std::rc::Rc;
 pub fn use_init_atom_root(cx: &ScopeState) -> &Rc<AtomRoot> {
     cx.use_hook(|_| match cx.consume_context::<Rc<AtomRoot>>() {
         Some(ctx) => ctx,
-        None => cx.provide_context(Rc::new(AtomRoot::new(cx.schedule_update_any()))),
+        None => cx.provide_context(AtomRoot::with_tasks(
+            cx.schedule_update_any(),
+            cx.task_handle(),
+        )),
     })
 }
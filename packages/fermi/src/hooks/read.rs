@@ -18,7 +18,7 @@ pub fn use_read_rc<'a, V: 'static>(cx: &'a ScopeState, f: impl Readable<V>) -> &
 
     impl<V> Drop for UseReadInner<V> {
         fn drop(&mut self) {
-            self.root.unsubscribe(self.id, self.scope_id)
+            self.root.unsubscribe(self.id.clone(), self.scope_id)
         }
     }
 
@@ -8,6 +8,6 @@ pub fn use_set<'a, T: 'static>(cx: &'a ScopeState, f: impl Writable<T>) -> &'a R
         let id = f.unique_id();
         let root = root.clone();
         root.initialize(f);
-        Rc::new(move |new| root.set(id, new)) as Rc<dyn Fn(T)>
+        Rc::new(move |new| root.set(id.clone(), new)) as Rc<dyn Fn(T)>
     })
 }
@@ -54,14 +54,14 @@ pub struct AtomState<V: 'static> {
 
 impl<V> Drop for AtomState<V> {
     fn drop(&mut self) {
-        self.root.unsubscribe(self.id, self.scope_id)
+        self.root.unsubscribe(self.id.clone(), self.scope_id)
     }
 }
 
 impl<T: 'static> AtomState<T> {
     /// Set the state to a new value.
     pub fn set(&self, new: T) {
-        self.root.set(self.id, new)
+        self.root.set(self.id.clone(), new)
     }
 
     /// Get the current value of the state by cloning its container Rc.
@@ -113,8 +113,8 @@ impl<T: 'static> AtomState<T> {
     #[must_use]
     pub fn setter(&self) -> Rc<dyn Fn(T)> {
         let root = self.root.clone();
-        let id = self.id;
-        Rc::new(move |new_val| root.set(id, new_val))
+        let id = self.id.clone();
+        Rc::new(move |new_val| root.set(id.clone(), new_val))
     }
 
     /// Set the state to a new value, using the current state value as a reference.
@@ -145,7 +145,7 @@ impl<T: 'static> AtomState<T> {
     /// }
     /// ```
     pub fn modify(&self, f: impl FnOnce(&T) -> T) {
-        self.root.clone().set(self.id, {
+        self.root.clone().set(self.id.clone(), {
             let current = self.value.as_ref().unwrap();
             f(current.as_ref())
         });
@@ -201,7 +201,7 @@ impl<T: 'static> AtomState<T> {
     /// }
     /// ```
     pub fn needs_update(&self) {
-        self.root.force_update(self.id)
+        self.root.force_update(self.id.clone())
     }
 }
 
@@ -272,7 +272,7 @@ impl<T: 'static> Clone for AtomState<T> {
     fn clone(&self) -> Self {
         AtomState {
             root: self.root.clone(),
-            id: self.id,
+            id: self.id.clone(),
             scope_id: self.scope_id,
             value: self.value.clone(),
         }
@@ -4,19 +4,27 @@ pub mod prelude {
     pub use crate::*;
 }
 
+mod callback;
+#[cfg(feature = "serialize")]
+mod persist;
 mod root;
 
 pub use atoms::*;
+pub use callback::*;
 pub use hooks::*;
+#[cfg(feature = "serialize")]
+pub use persist::*;
 pub use root::*;
 
 mod atoms {
+    mod asyncselector;
     mod atom;
     mod atomfamily;
     mod atomref;
     mod selector;
     mod selectorfamily;
 
+    pub use asyncselector::*;
     pub use atom::*;
     pub use atomfamily::*;
     pub use atomref::*;
@@ -25,12 +33,14 @@ mod atoms {
 }
 
 pub mod hooks {
+    mod atom_family;
     mod atom_ref;
     mod atom_root;
     mod init_atom_root;
     mod read;
     mod set;
     mod state;
+    pub use atom_family::*;
     pub use atom_ref::*;
     pub use atom_root::*;
     pub use init_atom_root::*;
@@ -44,9 +54,14 @@ pub mod hooks {
 /// This trait lets Dioxus abstract over Atoms, AtomFamilies, AtomRefs, and Selectors.
 /// It is not very useful for your own code, but could be used to build new Atom primitives.
 pub trait Readable<V> {
-    fn read(&self, root: AtomRoot) -> Option<V>;
+    fn read(&self, root: &AtomRoot) -> Option<V>;
     fn init(&self) -> V;
     fn unique_id(&self) -> AtomId;
+
+    /// How to compute the value from other atoms, if it's derived from them like a selector's.
+    fn derivation(&self) -> Option<Derivation> {
+        None
+    }
 }
 
 /// All Atoms are `Writable` - they support writing their value.
@@ -54,5 +69,5 @@ pub trait Readable<V> {
 /// This trait lets Dioxus abstract over Atoms, AtomFamilies, AtomRefs, and Selectors.
 /// This trait lets Dioxus abstract over Atoms, AtomFamilies, AtomRefs, and Selectors
 pub trait Writable<V>: Readable<V> {
-    fn write(&self, root: AtomRoot, value: V);
+    fn write(&self, root: &AtomRoot, value: V);
 }
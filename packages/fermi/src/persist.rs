@@ -0,0 +1,325 @@
+//! Saving the values of atoms, to keep them between sessions or to hydrate the state rendered on a server.
+//!
+//! Atoms opt into persistence with [`AtomRoot::persist`], under a key that stays the same between sessions. A
+//! [`Snapshot`] holds the values of every persisted atom, and can be restored before the first render or at any time
+//! afterwards to go back in time.
+//!
+//! ```rust, ignore
+//! static THEME: Atom<Theme> = |_| Theme::Light;
+//!
+//! fn app(cx: Scope) -> Element {
+//!     let root = use_init_atom_root(&cx);
+//!     cx.use_hook(|_| {
+//!         root.persist(THEME, "theme");
+//!         root.persist_to(FileStorage::new("state.json")).unwrap();
+//!     });
+//!
+//!     // ...
+//! }
+//! ```
+
+use crate::{AtomId, AtomRoot, Writable};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+/// The values of the persisted atoms of an [`AtomRoot`], by the keys they were persisted with.
+///
+/// Snapshots serialize to a JSON object, so they can be embedded in server-rendered pages.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Snapshot {
+    values: BTreeMap<String, Value>,
+}
+
+impl Snapshot {
+    pub fn to_json(&self) -> Result<String, PersistError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PersistError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Get the serialized value of an atom
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    /// Get the keys of the atoms in the snapshot, in order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+}
+
+/// An error encountered while saving or restoring a [`Snapshot`]
+#[derive(Debug)]
+pub enum PersistError {
+    /// A value couldn't be converted to or from JSON
+    Json(serde_json::Error),
+
+    /// The storage couldn't be read or written
+    Storage(String),
+}
+
+impl Display for PersistError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PersistError::Json(error) => write!(f, "invalid snapshot: {}", error),
+            PersistError::Storage(error) => write!(f, "couldn't access the storage: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PersistError {}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(error: serde_json::Error) -> Self {
+        PersistError::Json(error)
+    }
+}
+
+impl From<std::io::Error> for PersistError {
+    fn from(error: std::io::Error) -> Self {
+        PersistError::Storage(error.to_string())
+    }
+}
+
+/// Somewhere to keep a [`Snapshot`] between sessions
+pub trait Storage {
+    /// Load the saved snapshot, if there is one
+    fn load(&self) -> Result<Option<Snapshot>, PersistError>;
+
+    fn save(&self, snapshot: &Snapshot) -> Result<(), PersistError>;
+}
+
+/// Keeps the last snapshot in memory.
+///
+/// Clones share the same snapshot, which makes it useful for tests.
+#[derive(Clone, Default)]
+pub struct MemoryStorage {
+    snapshot: Rc<RefCell<Option<Snapshot>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the last snapshot that was saved
+    pub fn snapshot(&self) -> Option<Snapshot> {
+        self.snapshot.borrow().clone()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load(&self) -> Result<Option<Snapshot>, PersistError> {
+        Ok(self.snapshot())
+    }
+
+    fn save(&self, snapshot: &Snapshot) -> Result<(), PersistError> {
+        *self.snapshot.borrow_mut() = Some(snapshot.clone());
+        Ok(())
+    }
+}
+
+/// Keeps the snapshot in a JSON file, for desktop and TUI apps
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileStorage {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn load(&self) -> Result<Option<Snapshot>, PersistError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(json) => Snapshot::from_json(&json).map(Some),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    fn save(&self, snapshot: &Snapshot) -> Result<(), PersistError> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(snapshot)?)?;
+        Ok(())
+    }
+}
+
+/// Keeps the snapshot in the browser's localStorage, under a key
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorage {
+    key: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorage {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self { key: key.into() }
+    }
+
+    fn storage() -> Result<web_sys::Storage, PersistError> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| PersistError::Storage("localStorage isn't available".to_string()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for LocalStorage {
+    fn load(&self) -> Result<Option<Snapshot>, PersistError> {
+        match Self::storage()?.get_item(&self.key) {
+            Ok(Some(json)) => Snapshot::from_json(&json).map(Some),
+            Ok(None) => Ok(None),
+            Err(error) => Err(PersistError::Storage(format!("{:?}", error))),
+        }
+    }
+
+    fn save(&self, snapshot: &Snapshot) -> Result<(), PersistError> {
+        Self::storage()?
+            .set_item(&self.key, &snapshot.to_json()?)
+            .map_err(|error| PersistError::Storage(format!("{:?}", error)))
+    }
+}
+
+/// The persisted atoms of a root, and where they're saved
+#[derive(Default)]
+pub(crate) struct Persistence {
+    atoms: RefCell<BTreeMap<String, PersistedAtom>>,
+    storage: RefCell<Option<Box<dyn Storage>>>,
+
+    /// Set when a persisted atom is written during a transaction, so it's saved once the transaction is done
+    unsaved: Cell<bool>,
+}
+
+struct PersistedAtom {
+    id: AtomId,
+    save: fn(&dyn Any) -> serde_json::Result<Value>,
+    load: fn(Value) -> serde_json::Result<Rc<dyn Any>>,
+}
+
+fn save_value<V: Serialize + 'static>(value: &dyn Any) -> serde_json::Result<Value> {
+    serde_json::to_value(value.downcast_ref::<V>().unwrap())
+}
+
+fn load_value<V: DeserializeOwned + 'static>(value: Value) -> serde_json::Result<Rc<dyn Any>> {
+    Ok(Rc::new(serde_json::from_value::<V>(value)?))
+}
+
+impl AtomRoot {
+    /// Include an atom in the snapshots of the root, under a key that stays the same between sessions
+    pub fn persist<V>(&self, atom: impl Writable<V>, key: impl Into<String>)
+    where
+        V: Serialize + DeserializeOwned + 'static,
+    {
+        let id = atom.unique_id();
+        self.initialize(atom);
+
+        self.persistence.atoms.borrow_mut().insert(
+            key.into(),
+            PersistedAtom {
+                id,
+                save: save_value::<V>,
+                load: load_value::<V>,
+            },
+        );
+    }
+
+    /// Take a snapshot of the current values of the persisted atoms
+    pub fn snapshot(&self) -> Result<Snapshot, PersistError> {
+        let atoms = self.atoms.borrow();
+        let mut values = BTreeMap::new();
+
+        for (key, persisted) in self.persistence.atoms.borrow().iter() {
+            if let Some(slot) = atoms.get(&persisted.id) {
+                values.insert(key.clone(), (persisted.save)(&*slot.value)?);
+            }
+        }
+
+        Ok(Snapshot { values })
+    }
+
+    /// Set the persisted atoms to their values in a snapshot, re-rendering the components that read them.
+    ///
+    /// Restoring a snapshot before the first render hydrates the state, and restoring an older snapshot goes back in
+    /// time. Keys that aren't persisted are ignored, and nothing is restored if any of the values can't be read.
+    pub fn restore(&self, snapshot: &Snapshot) -> Result<(), PersistError> {
+        let values = {
+            let persisted = self.persistence.atoms.borrow();
+            let mut values = Vec::new();
+            for (key, value) in snapshot.values.iter() {
+                if let Some(persisted) = persisted.get(key) {
+                    values.push((persisted.id.clone(), (persisted.load)(value.clone())?));
+                }
+            }
+            values
+        };
+
+        self.transaction(|| {
+            for (id, value) in values {
+                self.set_any(id, value);
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Restore the snapshot in a storage if it has one, then save a snapshot to it whenever a persisted atom changes
+    pub fn persist_to(&self, storage: impl Storage + 'static) -> Result<(), PersistError> {
+        if let Some(snapshot) = storage.load()? {
+            self.restore(&snapshot)?;
+        }
+
+        *self.persistence.storage.borrow_mut() = Some(Box::new(storage));
+        Ok(())
+    }
+
+    /// Save a snapshot to the storage given to [`persist_to`](Self::persist_to)
+    pub fn save(&self) -> Result<(), PersistError> {
+        match self.persistence.storage.borrow().as_ref() {
+            Some(storage) => storage.save(&self.snapshot()?),
+            None => Ok(()),
+        }
+    }
+
+    /// Save the persisted atoms after one of them is written, or once the transaction it's written in is done
+    pub(crate) fn persisted_write(&self, id: &AtomId) {
+        let persisted = self
+            .persistence
+            .atoms
+            .borrow()
+            .values()
+            .any(|persisted| persisted.id == *id);
+
+        if persisted && self.persistence.storage.borrow().is_some() {
+            match self.in_transaction() {
+                true => self.persistence.unsaved.set(true),
+                false => self.autosave(),
+            }
+        }
+    }
+
+    pub(crate) fn commit_saves(&self) {
+        if self.persistence.unsaved.replace(false) {
+            self.autosave();
+        }
+    }
+
+    fn autosave(&self) {
+        if let Err(error) = self.save() {
+            log::error!("failed to save the persisted atoms: {}", error);
+        }
+    }
+}
@@ -1,20 +1,159 @@
-use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    rc::{Rc, Weak},
+    sync::Arc,
+};
 
-use dioxus_core::ScopeId;
+use dioxus_core::{ScopeId, TaskHandle, TaskId};
 use im_rc::HashSet;
 
-use crate::Readable;
+use crate::{AsyncState, AtomFamily, Derivation, Readable, SelectorBuilder, SelectorFuture};
+use im_rc::HashMap as ImMap;
 
-pub type AtomId = *const ();
+/// The identity of an atom in the [`AtomRoot`]: the function that defines it, and the key of the member if it's part
+/// of a family.
+#[derive(Clone)]
+pub struct AtomId {
+    ptr: *const (),
+    key: Option<Rc<dyn FamilyKey>>,
+}
+
+impl AtomId {
+    /// The id of an atom defined by a function
+    pub fn new(ptr: *const ()) -> Self {
+        Self { ptr, key: None }
+    }
+
+    /// The id of the member of a family with a key
+    pub fn with_key<K: Hash + Eq + 'static>(ptr: *const (), key: K) -> Self {
+        Self {
+            ptr,
+            key: Some(Rc::new(key)),
+        }
+    }
+}
+
+impl PartialEq for AtomId {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+            && match (&self.key, &other.key) {
+                (None, None) => true,
+                (Some(key), Some(other)) => key.eq_key(other.as_any()),
+                _ => false,
+            }
+    }
+}
+
+impl Eq for AtomId {}
+
+impl Hash for AtomId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+        if let Some(key) = &self.key {
+            key.hash_key(state);
+        }
+    }
+}
+
+impl Debug for AtomId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut id = f.debug_struct("AtomId");
+        id.field("ptr", &self.ptr);
+        if self.key.is_some() {
+            id.field("keyed", &true);
+        }
+        id.finish()
+    }
+}
+
+/// The key of a family member, with its type erased
+trait FamilyKey {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_key(&self, other: &dyn Any) -> bool;
+    fn hash_key(&self, state: &mut dyn Hasher);
+}
+
+impl<K: Hash + Eq + 'static> FamilyKey for K {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_key(&self, other: &dyn Any) -> bool {
+        other.downcast_ref::<K>() == Some(self)
+    }
+
+    fn hash_key(&self, mut state: &mut dyn Hasher) {
+        self.hash(&mut state);
+    }
+}
 
 pub struct AtomRoot {
     pub atoms: RefCell<HashMap<AtomId, Slot>>,
     pub update_any: Arc<dyn Fn(ScopeId)>,
+
+    /// Drives the futures of async selectors, if the root belongs to a VirtualDom
+    tasks: Option<AsyncTasks>,
+
+    transaction: RefCell<Option<Transaction>>,
+
+    #[cfg(feature = "serialize")]
+    pub(crate) persistence: crate::Persistence,
+}
+
+/// The notifications held back until a transaction is done
+#[derive(Default)]
+struct Transaction {
+    scopes: Vec<ScopeId>,
+
+    /// Selectors that components are subscribed to, which need to be recomputed
+    selectors: Vec<AtomId>,
+}
+
+struct AsyncTasks {
+    handle: TaskHandle,
+    root: Weak<AtomRoot>,
 }
 
 pub struct Slot {
     pub value: Rc<dyn Any>,
     pub subscribers: HashSet<ScopeId>,
+
+    /// The selectors that read this slot the last time they were computed
+    pub dependents: HashSet<AtomId>,
+
+    /// How to recompute the value, if the slot belongs to a selector
+    pub selector: Option<SelectorSlot>,
+
+    /// The keys of a family that components are subscribed to one at a time
+    pub members: HashSet<AtomId>,
+}
+
+pub struct SelectorSlot {
+    derivation: Derivation,
+
+    /// Set when one of the dependencies changed since the value was computed
+    stale: bool,
+
+    dependencies: HashSet<AtomId>,
+
+    /// The future of an async selector that hasn't resolved yet
+    task: Option<TaskId>,
+}
+
+impl Slot {
+    fn new(value: Rc<dyn Any>) -> Self {
+        Self {
+            value,
+            subscribers: HashSet::new(),
+            dependents: HashSet::new(),
+            selector: None,
+            members: HashSet::new(),
+        }
+    }
 }
 
 impl AtomRoot {
@@ -22,59 +161,66 @@ impl AtomRoot {
         Self {
             update_any,
             atoms: RefCell::new(HashMap::new()),
+            tasks: None,
+            transaction: RefCell::new(None),
+            #[cfg(feature = "serialize")]
+            persistence: Default::default(),
         }
     }
 
+    /// Create a root that can run async selectors, spawning their futures with the task handle
+    pub fn with_tasks(update_any: Arc<dyn Fn(ScopeId)>, tasks: TaskHandle) -> Rc<Self> {
+        Rc::new_cyclic(|root| Self {
+            update_any,
+            atoms: RefCell::new(HashMap::new()),
+            tasks: Some(AsyncTasks {
+                handle: tasks,
+                root: root.clone(),
+            }),
+            transaction: RefCell::new(None),
+            #[cfg(feature = "serialize")]
+            persistence: Default::default(),
+        })
+    }
+
     pub fn initialize<V: 'static>(&self, f: impl Readable<V>) {
-        let id = f.unique_id();
-        if self.atoms.borrow().get(&id).is_none() {
-            self.atoms.borrow_mut().insert(
-                id,
-                Slot {
-                    value: Rc::new(f.init()),
-                    subscribers: HashSet::new(),
-                },
-            );
-        }
+        self.ensure(&f);
     }
 
     pub fn register<V: 'static>(&self, f: impl Readable<V>, scope: ScopeId) -> Rc<V> {
-        let mut atoms = self.atoms.borrow_mut();
+        let id = self.ensure(&f);
 
-        // initialize the value if it's not already initialized
-        if let Some(slot) = atoms.get_mut(&f.unique_id()) {
+        // compute the value before subscribing so a selector computed for the first time doesn't notify its reader
+        let value = self.value(&id);
+        if let Some(slot) = self.atoms.borrow_mut().get_mut(&id) {
             slot.subscribers.insert(scope);
-            slot.value.clone().downcast().unwrap()
-        } else {
-            let value = Rc::new(f.init());
-            let mut subscribers = HashSet::new();
-            subscribers.insert(scope);
-
-            atoms.insert(
-                f.unique_id(),
-                Slot {
-                    value: value.clone(),
-                    subscribers,
-                },
-            );
-            value
         }
+
+        value.downcast().unwrap()
     }
 
     pub fn set<V: 'static>(&self, ptr: AtomId, value: V) {
-        let mut atoms = self.atoms.borrow_mut();
+        self.set_any(ptr, Rc::new(value));
+    }
 
-        if let Some(slot) = atoms.get_mut(&ptr) {
-            slot.value = Rc::new(value);
-            log::trace!("found item with subscribers {:?}", slot.subscribers);
+    pub(crate) fn set_any(&self, ptr: AtomId, value: Rc<dyn Any>) {
+        {
+            let mut atoms = self.atoms.borrow_mut();
 
-            for scope in &slot.subscribers {
-                log::trace!("updating subcsriber");
-                (self.update_any)(*scope);
+            if let Some(slot) = atoms.get_mut(&ptr) {
+                slot.value = value;
+                log::trace!("found item with subscribers {:?}", slot.subscribers);
+            } else {
+                log::trace!("no atoms found for {:?}", ptr);
             }
-        } else {
-            log::trace!("no atoms found for {:?}", ptr);
+
+            self.notify(&atoms, &ptr);
         }
+
+        self.refresh_dependents(&ptr);
+
+        #[cfg(feature = "serialize")]
+        self.persisted_write(&ptr);
     }
 
     pub fn unsubscribe(&self, ptr: AtomId, scope: ScopeId) {
@@ -82,35 +228,454 @@ impl AtomRoot {
 
         if let Some(slot) = atoms.get_mut(&ptr) {
             slot.subscribers.remove(&scope);
+
+            // members of a family only exist to hold their subscribers
+            let unused_member = slot.subscribers.is_empty() && Self::is_member(&ptr, slot);
+            if unused_member {
+                Self::remove_member_slot(&mut atoms, &ptr);
+            }
         }
     }
 
     // force update of all subscribers
     pub fn force_update(&self, ptr: AtomId) {
-        if let Some(slot) = self.atoms.borrow_mut().get(&ptr) {
-            for scope in slot.subscribers.iter() {
-                log::trace!("updating subcsriber");
-                (self.update_any)(*scope);
-            }
+        self.notify(&self.atoms.borrow(), &ptr);
+        self.refresh_dependents(&ptr);
+    }
+
+    /// Make several writes, notifying the subscribers of the atoms they change once they're all done.
+    ///
+    /// Selectors are marked stale as soon as their atoms are written, so reads inside the transaction see the new
+    /// values, but they're only recomputed for their subscribers at the end. Nested transactions are part of the
+    /// outermost one.
+    pub fn transaction<R>(&self, f: impl FnOnce() -> R) -> R {
+        if self.in_transaction() {
+            return f();
+        }
+
+        *self.transaction.borrow_mut() = Some(Transaction::default());
+        let _commit = CommitOnDrop(self);
+        f()
+    }
+
+    /// Read the value of one key of a family, subscribing the scope to that key only.
+    ///
+    /// Writing other keys of the family won't re-render the scope.
+    pub fn register_member<K, V>(
+        &self,
+        family: AtomFamily<K, V>,
+        key: &K,
+        scope: ScopeId,
+    ) -> Option<V>
+    where
+        K: Hash + Eq + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let id = self.ensure(&family);
+        let member = AtomId::with_key(id.ptr, key.clone());
+
+        let mut atoms = self.atoms.borrow_mut();
+        if let Some(slot) = atoms.get_mut(&id) {
+            slot.members.insert(member.clone());
+        }
+        atoms
+            .entry(member)
+            .or_insert_with(|| Slot::new(Rc::new(())))
+            .subscribers
+            .insert(scope);
+
+        Self::family_map::<K, V>(&atoms, &id).get(key).cloned()
+    }
+
+    /// Read the value of one key of a family
+    pub fn read_member<K, V>(&self, family: AtomFamily<K, V>, key: &K) -> Option<V>
+    where
+        K: Hash + Eq + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let id = self.ensure(&family);
+        Self::family_map::<K, V>(&self.atoms.borrow(), &id)
+            .get(key)
+            .cloned()
+    }
+
+    /// Set the value of one key of a family, re-rendering the scopes subscribed to that key or the whole family
+    pub fn set_member<K, V>(&self, family: AtomFamily<K, V>, key: K, value: V)
+    where
+        K: Hash + Eq + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let id = self.ensure(&family);
+        self.update_member(&id, key, |map, key| (map.update(key, value), ()));
+    }
+
+    /// Remove a key from a family, re-rendering the scopes subscribed to that key or the whole family.
+    ///
+    /// The subscriptions to the key are dropped along with it.
+    pub fn remove_member<K, V>(&self, family: AtomFamily<K, V>, key: &K) -> Option<V>
+    where
+        K: Hash + Eq + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let id = self.ensure(&family);
+        if !Self::family_map::<K, V>(&self.atoms.borrow(), &id).contains_key(key) {
+            return None;
         }
+
+        let removed = self.update_member(&id, key.clone(), |map, key| {
+            let (value, map) = map.extract(&key).unwrap();
+            (map, value)
+        });
+        Self::remove_member_slot(
+            &mut self.atoms.borrow_mut(),
+            &AtomId::with_key(id.ptr, key.clone()),
+        );
+
+        Some(removed)
     }
 
     pub fn read<V: 'static>(&self, f: impl Readable<V>) -> Rc<V> {
+        let id = self.ensure(&f);
+        self.value(&id).downcast().unwrap()
+    }
+
+    /// Read an atom while computing a selector, recording that the selector depends on it
+    pub(crate) fn read_dependency<V: 'static>(
+        &self,
+        f: impl Readable<V>,
+        selector: &AtomId,
+    ) -> Rc<V> {
+        let id = self.ensure(&f);
+        let value = self.value(&id);
+
         let mut atoms = self.atoms.borrow_mut();
+        if let Some(slot) = atoms.get_mut(&id) {
+            slot.dependents.insert(selector.clone());
+        }
+        if let Some(selector) = atoms
+            .get_mut(selector)
+            .and_then(|slot| slot.selector.as_mut())
+        {
+            selector.dependencies.insert(id);
+        }
+
+        value.downcast().unwrap()
+    }
+
+    /// Start the future of an async selector, cancelling the one it replaces.
+    ///
+    /// The selector keeps its last result while the new future runs.
+    pub(crate) fn start_async<T: 'static, E: 'static>(
+        &self,
+        id: &AtomId,
+        fut: SelectorFuture<T, E>,
+    ) -> AsyncState<T, E> {
+        let (previous, state) = {
+            let mut atoms = self.atoms.borrow_mut();
+            let slot = atoms.get_mut(id);
+            let state = slot
+                .as_ref()
+                .and_then(|slot| slot.value.downcast_ref::<AsyncState<T, E>>())
+                .map_or_else(AsyncState::pending, AsyncState::reloading);
+            let previous = slot
+                .and_then(|slot| slot.selector.as_mut())
+                .and_then(|selector| selector.task.take());
+            (previous, state)
+        };
+
+        let tasks = match &self.tasks {
+            Some(tasks) => tasks,
+            None => {
+                log::warn!("async selectors need a root created with `AtomRoot::with_tasks`");
+                return state;
+            }
+        };
+
+        if let Some(previous) = previous {
+            tasks.handle.remove(previous);
+        }
+
+        // the task can't know its own id until it's spawned, but it isn't polled before then
+        let task_id = Rc::new(std::cell::Cell::new(None));
+        let task = tasks.handle.spawn({
+            let (root, id, task_id) = (tasks.root.clone(), id.clone(), task_id.clone());
+            async move {
+                let result = fut.await;
+                if let (Some(root), Some(task)) = (root.upgrade(), task_id.get()) {
+                    root.resolve(&id, task, result);
+                }
+            }
+        });
+        task_id.set(Some(task));
+
+        if let Some(selector) = self
+            .atoms
+            .borrow_mut()
+            .get_mut(id)
+            .and_then(|slot| slot.selector.as_mut())
+        {
+            selector.task = Some(task);
+        }
+
+        state
+    }
+
+    /// Store the result of an async selector's future, unless the future was replaced in the meantime
+    fn resolve<T: 'static, E: 'static>(&self, id: &AtomId, task: TaskId, result: Result<T, E>) {
+        {
+            let mut atoms = self.atoms.borrow_mut();
+            let slot = match atoms.get_mut(id) {
+                Some(slot) => slot,
+                None => return,
+            };
+
+            match slot.selector.as_mut() {
+                Some(selector) if selector.task == Some(task) => selector.task = None,
+                _ => return,
+            }
+
+            slot.value = Rc::new(AsyncState::ready(result));
+            self.notify_slot(slot);
+        }
+
+        self.refresh_dependents(id);
+    }
+
+    /// Replace the map of a family, notifying the subscribers of the family and of the changed key
+    fn update_member<K, V, R>(
+        &self,
+        id: &AtomId,
+        key: K,
+        f: impl FnOnce(&ImMap<K, V>, K) -> (ImMap<K, V>, R),
+    ) -> R
+    where
+        K: Hash + Eq + Clone + 'static,
+        V: Clone + 'static,
+    {
+        let result = {
+            let mut atoms = self.atoms.borrow_mut();
+            let member = AtomId::with_key(id.ptr, key.clone());
+            let (map, result) = f(Self::family_map(&atoms, id), key);
+
+            if let Some(slot) = atoms.get_mut(id) {
+                slot.value = Rc::new(map);
+            }
+            for slot in [atoms.get(id), atoms.get(&member)].iter().flatten() {
+                self.notify_slot(slot);
+            }
+
+            result
+        };
+
+        self.refresh_dependents(id);
+
+        #[cfg(feature = "serialize")]
+        self.persisted_write(id);
+
+        result
+    }
+
+    fn family_map<'a, K: 'static, V: 'static>(
+        atoms: &'a HashMap<AtomId, Slot>,
+        id: &AtomId,
+    ) -> &'a ImMap<K, V> {
+        atoms[id].value.downcast_ref().unwrap()
+    }
+
+    fn is_member(id: &AtomId, slot: &Slot) -> bool {
+        id.key.is_some() && slot.selector.is_none()
+    }
+
+    fn remove_member_slot(atoms: &mut HashMap<AtomId, Slot>, member: &AtomId) {
+        atoms.remove(member);
+        if let Some(family) = atoms.get_mut(&AtomId::new(member.ptr)) {
+            family.members.remove(member);
+        }
+    }
+
+    /// Re-render the scopes subscribed to a slot, and to any key of it if it's a family
+    fn notify(&self, atoms: &HashMap<AtomId, Slot>, id: &AtomId) {
+        if let Some(slot) = atoms.get(id) {
+            self.notify_slot(slot);
+            for member in slot.members.iter() {
+                if let Some(member) = atoms.get(member) {
+                    self.notify_slot(member);
+                }
+            }
+        }
+    }
+
+    fn notify_slot(&self, slot: &Slot) {
+        let mut transaction = self.transaction.borrow_mut();
+
+        for scope in slot.subscribers.iter() {
+            match transaction.as_mut() {
+                Some(transaction) if !transaction.scopes.contains(scope) => {
+                    transaction.scopes.push(*scope)
+                }
+                Some(_) => {}
+                None => {
+                    log::trace!("updating subcsriber");
+                    (self.update_any)(*scope);
+                }
+            }
+        }
+    }
+
+    fn commit(&self) {
+        // a panicking transaction still notifies its subscribers, but selectors aren't run while unwinding
+        while !std::thread::panicking() {
+            let selectors = match self.transaction.borrow_mut().as_mut() {
+                Some(transaction) => std::mem::take(&mut transaction.selectors),
+                None => break,
+            };
+            if selectors.is_empty() {
+                break;
+            }
+
+            for selector in selectors {
+                self.value(&selector);
+            }
+        }
 
-        // initialize the value if it's not already initialized
-        if let Some(slot) = atoms.get_mut(&f.unique_id()) {
-            slot.value.clone().downcast().unwrap()
-        } else {
-            let value = Rc::new(f.init());
-            atoms.insert(
-                f.unique_id(),
-                Slot {
-                    value: value.clone(),
-                    subscribers: HashSet::new(),
-                },
-            );
-            value
+        let transaction = self.transaction.borrow_mut().take();
+        for scope in transaction
+            .into_iter()
+            .flat_map(|transaction| transaction.scopes)
+        {
+            (self.update_any)(scope);
         }
+
+        #[cfg(feature = "serialize")]
+        self.commit_saves();
+    }
+
+    pub(crate) fn in_transaction(&self) -> bool {
+        self.transaction.borrow().is_some()
+    }
+
+    /// Create the slot of an atom if it doesn't have one yet.
+    ///
+    /// Selectors aren't computed until they're read.
+    fn ensure<V: 'static>(&self, f: &impl Readable<V>) -> AtomId {
+        let id = f.unique_id();
+        if self.atoms.borrow().contains_key(&id) {
+            return id;
+        }
+
+        let slot = match f.derivation() {
+            Some(derivation) => Slot {
+                selector: Some(SelectorSlot {
+                    derivation,
+                    stale: true,
+                    dependencies: HashSet::new(),
+                    task: None,
+                }),
+                ..Slot::new(Rc::new(()))
+            },
+            None => Slot::new(Rc::new(f.init())),
+        };
+
+        self.atoms.borrow_mut().insert(id.clone(), slot);
+        id
+    }
+
+    /// Get the value of a slot, recomputing it first if it's a stale selector
+    fn value(&self, id: &AtomId) -> Rc<dyn Any> {
+        let stale = matches!(
+            self.atoms
+                .borrow()
+                .get(id)
+                .and_then(|slot| slot.selector.as_ref()),
+            Some(SelectorSlot { stale: true, .. })
+        );
+
+        if stale {
+            self.recompute(id);
+        }
+
+        self.atoms.borrow()[id].value.clone()
+    }
+
+    /// Compute a selector again, notifying its subscribers if its value changed
+    fn recompute(&self, id: &AtomId) {
+        let derivation = {
+            let mut atoms = self.atoms.borrow_mut();
+            let selector = match atoms.get_mut(id).and_then(|slot| slot.selector.as_mut()) {
+                Some(selector) => selector,
+                None => return,
+            };
+
+            // the dependencies are recorded again while computing, since they can change between computations
+            selector.stale = false;
+            let dependencies = std::mem::take(&mut selector.dependencies);
+            let derivation = selector.derivation.clone();
+
+            for dependency in dependencies {
+                if let Some(slot) = atoms.get_mut(&dependency) {
+                    slot.dependents.remove(id);
+                }
+            }
+
+            derivation
+        };
+
+        let value = derivation.compute(SelectorBuilder::new(self, id.clone()));
+
+        let mut atoms = self.atoms.borrow_mut();
+        if let Some(slot) = atoms.get_mut(id) {
+            if !derivation.same(&*slot.value, &*value) {
+                slot.value = value;
+                self.notify_slot(slot);
+            }
+        }
+    }
+
+    /// Mark every selector that depends on a slot as stale, and recompute the ones that components are subscribed to
+    /// so they're only re-rendered if the selector's value changed.
+    ///
+    /// Selectors nobody is subscribed to are recomputed the next time they're read.
+    fn refresh_dependents(&self, id: &AtomId) {
+        let mut subscribed = Vec::new();
+
+        {
+            let mut atoms = self.atoms.borrow_mut();
+            let mut queue = match atoms.get(id) {
+                Some(slot) => slot.dependents.iter().cloned().collect::<Vec<_>>(),
+                None => return,
+            };
+
+            while let Some(dependent) = queue.pop() {
+                if let Some(slot) = atoms.get_mut(&dependent) {
+                    if let Some(selector) = slot.selector.as_mut() {
+                        if !selector.stale {
+                            selector.stale = true;
+                            queue.extend(slot.dependents.iter().cloned());
+                            if !slot.subscribers.is_empty() {
+                                subscribed.push(dependent);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(transaction) = self.transaction.borrow_mut().as_mut() {
+            transaction.selectors.extend(subscribed);
+            return;
+        }
+
+        // reading a selector brings its own dependencies up to date first
+        for selector in subscribed {
+            self.value(&selector);
+        }
+    }
+}
+
+struct CommitOnDrop<'a>(&'a AtomRoot);
+
+impl Drop for CommitOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.commit();
     }
 }
@@ -19,5 +19,20 @@ pub use usefuture::*;
 mod useeffect;
 pub use useeffect::*;
 
-// mod usesuspense;
-// pub use usesuspense::*;
+mod uselifecycle;
+pub use uselifecycle::*;
+
+mod usenoderef;
+pub use usenoderef::*;
+
+mod usesuspense;
+pub use usesuspense::*;
+
+mod usesignal;
+pub use usesignal::*;
+
+mod usecollection;
+pub use usecollection::*;
+
+mod usemodel;
+pub use usemodel::*;
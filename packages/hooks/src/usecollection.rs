@@ -1,76 +1,499 @@
-/*
-a form of use_state explicitly for map-style collections (BTreeMap, HashMap, etc).
-
-Why?
----
-Traditionally, it's possible to use the "use_state" hook for collections in the React world.
-Adding a new entry would look something similar to:
-
-```js
-let (map, set_map) = useState({});
-set_map({ ...map, [key]: value });
-```
-The new value then causes the appropriate update when passed into children.
-
-This is moderately efficient because the fields of the map are moved, but the data itself is not cloned.
-However, if you used similar approach with Dioxus:
-
-```rust
-let (map, set_map) = use_state(&cx, || HashMap::new());
-set_map({
-    let mut newmap = map.clone();
-    newmap.set(key, value);
-    newmap
-})
-```
-Unfortunately, you'd be cloning the entire state every time a value is changed. The obvious solution is to
-wrap every element in the HashMap with an Rc. That way, cloning the HashMap is on par with its JS equivalent.
-
-Fortunately, we can make this operation even more efficient in Dioxus, leveraging the borrow rules of Rust.
-
-This hook provides a memoized collection, memoized setters, and memoized getters. This particular hook is
-extremely powerful for implementing lists and supporting core state management needs for small apps.
-
-If you need something even more powerful, check out the dedicated atomic state management Dioxus Dataflow, which
-uses the same memoization on top of the use_context API.
-
-Here's a fully-functional todo app using the use_map API:
-```rust
-static TodoList: Component = |cx| {
-    let todos = use_map(cx, || HashMap::new());
-    let input = use_ref(|| None);
-
-    cx.render(rsx!{
-        div {
-            button {
-                "Add todo"
-                onclick: move |_| {
-                    let new_todo = TodoItem::new(input.contents());
-                    todos.insert(new_todo.id.clone(), new_todo);
-                    input.clear();
-                }
+//! Collections whose entries can change without re-rendering the whole list.
+//!
+//! Storing a list in `use_state` means every change to an item clones the list and re-renders the component that
+//! renders it, along with every item that was passed a reference into it. These hooks split the collection up: the
+//! order of the entries is one signal, and every entry is a signal of its own. Components that read the structure of
+//! the collection re-render when entries are added, removed or moved, while a component that reads an entry re-renders
+//! only when that entry changes.
+//!
+//! Rendering the entries with their keys lets the keyed diff turn moves into the fewest possible DOM moves, without
+//! recreating the items:
+//!
+//! ```rust, ignore
+//! fn TodoList(cx: Scope) -> Element {
+//!     let todos = use_vec(&cx, Vec::<String>::new);
+//!
+//!     cx.render(rsx!(
+//!         button { onclick: move |_| todos.push("new todo".to_string()), "add" }
+//!         ul {
+//!             todos.entries().into_iter().map(|todo| {
+//!                 let key = *todo.key();
+//!                 rsx!(TodoItem { key: "{key}", todo: todo })
+//!             })
+//!         }
+//!     ))
+//! }
+//!
+//! #[inline_props]
+//! fn TodoItem(cx: Scope, todo: Entry<usize, String>) -> Element {
+//!     cx.render(rsx!(li { "{todo}" }))
+//! }
+//! ```
+
+use crate::usesignal::{OwnedSignal, Signal, UpdateAny};
+use dioxus_core::ScopeState;
+use std::{
+    cell::{Ref, RefMut},
+    collections::HashMap,
+    fmt::{self, Debug, Display},
+    hash::Hash,
+};
+
+/// Store a list whose items can be updated and moved without re-rendering the whole list.
+///
+/// Every item gets a key that never changes, see [`Entry::key`].
+pub fn use_vec<T: 'static>(cx: &ScopeState, init: impl FnOnce() -> Vec<T>) -> UseVec<T> {
+    let state = crate::use_signal(cx, || {
+        let mut collection = Collection::new(cx.schedule_update_any());
+        for value in init() {
+            let key = collection.next_key();
+            collection.insert_at(collection.order.len(), key, value);
+        }
+        collection
+    });
+
+    UseVec { state }
+}
+
+/// Store a map whose entries keep their insertion order and can be updated and moved without re-rendering the whole
+/// map.
+pub fn use_map<K, V>(cx: &ScopeState, init: impl FnOnce() -> Vec<(K, V)>) -> UseMap<K, V>
+where
+    K: Clone + Eq + Hash + 'static,
+    V: 'static,
+{
+    let state = crate::use_signal(cx, || {
+        let mut collection = Collection::new(cx.schedule_update_any());
+        for (key, value) in init() {
+            match collection.entries.get(&key) {
+                Some(entry) => *entry.signal().write() = value,
+                None => collection.insert_at(collection.order.len(), key, value),
             }
-            button {
-                "Clear todos"
-                onclick: move |_| todos.clear()
+        }
+        collection
+    });
+
+    UseMap { state }
+}
+
+/// A handle to a list created with [`use_vec`].
+///
+/// Reading the structure of the list - its length or entries - subscribes the component that is rendering to items
+/// being added, removed or moved.
+pub struct UseVec<T: 'static> {
+    state: Signal<Collection<usize, T>>,
+}
+
+impl<T: 'static> UseVec<T> {
+    /// The number of items
+    pub fn len(&self) -> usize {
+        self.state.read().order.len()
+    }
+
+    /// Check if the list has no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the item at an index
+    pub fn get(&self, index: usize) -> Option<Entry<usize, T>> {
+        let state = self.state.read();
+        let key = state.order.get(index)?;
+        state.entry(key)
+    }
+
+    /// Get every item, in order
+    pub fn entries(&self) -> Vec<Entry<usize, T>> {
+        self.state.read().entries()
+    }
+
+    /// Add an item to the end of the list, returning its key
+    pub fn push(&self, value: T) -> usize {
+        let mut state = self.state.write();
+        let key = state.next_key();
+        let index = state.order.len();
+        state.insert_at(index, key, value);
+        key
+    }
+
+    /// Insert an item at an index, returning its key.
+    ///
+    /// Panics if the index is greater than the length of the list.
+    pub fn insert(&self, index: usize, value: T) -> usize {
+        let mut state = self.state.write();
+        assert!(
+            index <= state.order.len(),
+            "insertion index is out of bounds"
+        );
+        let key = state.next_key();
+        state.insert_at(index, key, value);
+        key
+    }
+
+    /// Remove the item at an index.
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn remove(&self, index: usize) -> T {
+        let mut state = self.state.write();
+        let key = state.order[index];
+        state.remove(&key).unwrap()
+    }
+
+    /// Remove the last item
+    pub fn pop(&self) -> Option<T> {
+        let key = *self.state.peek().order.last()?;
+        self.state.write().remove(&key)
+    }
+
+    /// Update the item at an index, re-rendering only the components that read it.
+    ///
+    /// Returns `false` if the index is out of bounds.
+    pub fn update(&self, index: usize, f: impl FnOnce(&mut T)) -> bool {
+        let entry = self.state.peek().order.get(index).copied();
+        match entry.and_then(|key| self.state.peek().entry(&key)) {
+            Some(entry) => {
+                entry.with_mut(f);
+                true
             }
-            input {
-                placeholder: "What needs to be done?"
-                ref: input
+            None => false,
+        }
+    }
+
+    /// Move the item at one index to another.
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn move_item(&self, from: usize, to: usize) {
+        let len = self.state.peek().order.len();
+        assert!(from < len && to < len, "index is out of bounds");
+        if from != to {
+            let mut state = self.state.write();
+            let key = state.order.remove(from);
+            state.order.insert(to, key);
+        }
+    }
+
+    /// Swap two items.
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn swap(&self, a: usize, b: usize) {
+        let len = self.state.peek().order.len();
+        assert!(a < len && b < len, "index is out of bounds");
+        if a != b {
+            self.state.write().order.swap(a, b);
+        }
+    }
+
+    /// Keep only the items the closure returns `true` for
+    pub fn retain(&self, mut f: impl FnMut(&T) -> bool) {
+        retain(self.state, |_, value| f(value));
+    }
+
+    /// Remove every item
+    pub fn clear(&self) {
+        retain(self.state, |_, _| false);
+    }
+}
+
+impl<T: 'static> Clone for UseVec<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for UseVec<T> {}
+
+impl<T: 'static> PartialEq for UseVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state
+    }
+}
+
+/// A handle to a map created with [`use_map`].
+///
+/// Reading the structure of the map - its length, keys or entries - subscribes the component that is rendering to
+/// entries being added, removed or moved.
+pub struct UseMap<K: 'static, V: 'static> {
+    state: Signal<Collection<K, V>>,
+}
+
+impl<K: Clone + Eq + Hash + 'static, V: 'static> UseMap<K, V> {
+    /// The number of entries
+    pub fn len(&self) -> usize {
+        self.state.read().order.len()
+    }
+
+    /// Check if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check if the map has an entry for a key
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.state.read().entries.contains_key(key)
+    }
+
+    /// Get the keys, in order
+    pub fn keys(&self) -> Vec<K> {
+        self.state.read().order.clone()
+    }
+
+    /// Get the entry for a key
+    pub fn get(&self, key: &K) -> Option<Entry<K, V>> {
+        self.state.read().entry(key)
+    }
+
+    /// Get every entry, in order
+    pub fn entries(&self) -> Vec<Entry<K, V>> {
+        self.state.read().entries()
+    }
+
+    /// Insert an entry, returning the value it replaced.
+    ///
+    /// New entries are added to the end. Replacing the value of an entry keeps its place and re-renders only the
+    /// components that read it.
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        let entry = self.state.peek().entry(&key);
+        match entry {
+            Some(entry) => Some(std::mem::replace(&mut *entry.write(), value)),
+            None => {
+                let mut state = self.state.write();
+                let index = state.order.len();
+                state.insert_at(index, key, value);
+                None
+            }
+        }
+    }
+
+    /// Remove the entry for a key, returning its value
+    pub fn remove(&self, key: &K) -> Option<V> {
+        if !self.state.peek().entries.contains_key(key) {
+            return None;
+        }
+        self.state.write().remove(key)
+    }
+
+    /// Update the entry for a key, re-rendering only the components that read it.
+    ///
+    /// Returns `false` if there is no entry for the key.
+    pub fn update(&self, key: &K, f: impl FnOnce(&mut V)) -> bool {
+        let entry = self.state.peek().entry(key);
+        match entry {
+            Some(entry) => {
+                entry.with_mut(f);
+                true
             }
-            ul {
-                {todos.iter().map(|todo| rsx!(
-                    li {
-                        key: todo.id
-                        span { "{todo.content}" }
-                        button {"x", onclick: move |_| todos.remove(todo.key.clone())}
-                    }
-                ))}
+            None => false,
+        }
+    }
+
+    /// Move the entry for a key to an index, returning `false` if there is no entry for the key.
+    ///
+    /// Panics if the index is out of bounds.
+    pub fn move_to(&self, key: &K, index: usize) -> bool {
+        let current = {
+            let state = self.state.peek();
+            assert!(index < state.order.len(), "index is out of bounds");
+            state.order.iter().position(|k| k == key)
+        };
+
+        match current {
+            Some(current) => {
+                if current != index {
+                    let mut state = self.state.write();
+                    let key = state.order.remove(current);
+                    state.order.insert(index, key);
+                }
+                true
             }
+            None => false,
         }
-    })
+    }
+
+    /// Keep only the entries the closure returns `true` for
+    pub fn retain(&self, f: impl FnMut(&K, &V) -> bool) {
+        retain(self.state, f);
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) {
+        retain(self.state, |_, _| false);
+    }
+}
+
+impl<K: 'static, V: 'static> Clone for UseMap<K, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K: 'static, V: 'static> Copy for UseMap<K, V> {}
+
+impl<K: 'static, V: 'static> PartialEq for UseMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.state == other.state
+    }
 }
 
-```
+/// An entry of a [`UseVec`] or [`UseMap`].
+///
+/// Entries can be passed to child components: reading one subscribes only to changes of that entry, and writing to it
+/// re-renders only the components that read it.
+pub struct Entry<K, V: 'static> {
+    key: K,
+    value: Signal<V>,
+}
+
+impl<K, V: 'static> Entry<K, V> {
+    /// The key of the entry, which is what the entry should be rendered with
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// The value of the entry as a signal
+    pub fn signal(&self) -> Signal<V> {
+        self.value
+    }
+
+    /// Borrow the value, subscribing the component that is rendering to changes.
+    pub fn read(&self) -> Ref<'static, V> {
+        self.value.read()
+    }
+
+    /// Get a clone of the value, subscribing the component that is rendering to changes.
+    pub fn get(&self) -> V
+    where
+        V: Clone,
+    {
+        self.value.get()
+    }
+
+    /// Run a closure with the value, subscribing the component that is rendering to changes.
+    pub fn with<O>(&self, f: impl FnOnce(&V) -> O) -> O {
+        self.value.with(f)
+    }
 
-*/
+    /// Mutably borrow the value, re-rendering the components that read it.
+    pub fn write(&self) -> RefMut<'static, V> {
+        self.value.write()
+    }
+
+    /// Replace the value, re-rendering the components that read it.
+    pub fn set(&self, value: V) {
+        self.value.set(value)
+    }
+
+    /// Run a closure with a mutable reference to the value, re-rendering the components that read it.
+    pub fn with_mut<O>(&self, f: impl FnOnce(&mut V) -> O) -> O {
+        self.value.with_mut(f)
+    }
+}
+
+impl<K: Clone, V: 'static> Clone for Entry<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            value: self.value,
+        }
+    }
+}
+
+impl<K: Copy, V: 'static> Copy for Entry<K, V> {}
+
+impl<K: PartialEq, V: 'static> PartialEq for Entry<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value
+    }
+}
+
+impl<K, V: Display + 'static> Display for Entry<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.value, f)
+    }
+}
+
+impl<K: Debug, V: Debug + 'static> Debug for Entry<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entry")
+            .field("key", &self.key)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+/// The order of the entries of a collection, and a signal for every entry
+struct Collection<K: 'static, V: 'static> {
+    order: Vec<K>,
+    entries: HashMap<K, OwnedSignal<V>>,
+
+    /// The key of the next item pushed to a [`UseVec`]
+    next_key: usize,
+
+    update_any: UpdateAny,
+}
+
+impl<K: Clone + Eq + Hash + 'static, V: 'static> Collection<K, V> {
+    fn new(update_any: UpdateAny) -> Self {
+        Self {
+            order: Vec::new(),
+            entries: HashMap::new(),
+            next_key: 0,
+            update_any,
+        }
+    }
+
+    fn next_key(&mut self) -> usize {
+        self.next_key += 1;
+        self.next_key - 1
+    }
+
+    fn entry(&self, key: &K) -> Option<Entry<K, V>> {
+        self.entries.get(key).map(|value| Entry {
+            key: key.clone(),
+            value: value.signal(),
+        })
+    }
+
+    fn entries(&self) -> Vec<Entry<K, V>> {
+        self.order
+            .iter()
+            .filter_map(|key| self.entry(key))
+            .collect()
+    }
+
+    fn insert_at(&mut self, index: usize, key: K, value: V) {
+        let value = OwnedSignal::new(self.update_any.clone(), value);
+        self.order.insert(index, key.clone());
+        self.entries.insert(key, value);
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.entries.remove(key)?;
+        self.order.retain(|k| k != key);
+        Some(value.into_inner())
+    }
+}
+
+/// Remove the entries the closure returns `false` for, only re-rendering the components that read the structure of the
+/// collection if something was removed
+fn retain<K, V>(collection: Signal<Collection<K, V>>, mut f: impl FnMut(&K, &V) -> bool)
+where
+    K: Clone + Eq + Hash + 'static,
+    V: 'static,
+{
+    let removed = {
+        let state = collection.peek();
+        state
+            .order
+            .iter()
+            .filter(|key| !f(key, &state.entries[*key].signal().peek()))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    if !removed.is_empty() {
+        let mut state = collection.write();
+        for key in &removed {
+            state.remove(key);
+        }
+    }
+}
@@ -81,8 +81,8 @@ pub fn use_coroutine_handle<M: 'static>(cx: &ScopeState) -> Option<&CoroutineHan
 }
 
 pub struct CoroutineHandle<T> {
-    tx: UnboundedSender<T>,
-    task: TaskId,
+    pub(crate) tx: UnboundedSender<T>,
+    pub(crate) task: TaskId,
 }
 
 impl<T> Clone for CoroutineHandle<T> {
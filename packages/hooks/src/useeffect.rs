@@ -1,13 +1,81 @@
 use dioxus_core::{ScopeState, TaskId};
-use std::{any::Any, cell::Cell, future::Future};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    future::Future,
+    rc::Rc,
+};
 
 use crate::UseFutureDep;
 
+/// A value an effect leaves behind to undo what it did.
+///
+/// Effects can return nothing, or a closure that's called before the effect runs again and when the component is
+/// removed.
+pub trait EffectCleanup: 'static {
+    /// Undo the effect
+    fn cleanup(self);
+}
+
+impl EffectCleanup for () {
+    fn cleanup(self) {}
+}
+
+impl<F: FnOnce() + 'static> EffectCleanup for F {
+    fn cleanup(self) {
+        self()
+    }
+}
+
+/// What happens to a pending effect when the dependencies change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectMode {
+    /// Let the previous future finish. If it returns a cleanup, the cleanup runs as soon as it finishes.
+    Continue,
+
+    /// Cancel the previous future before starting the new one.
+    Cancel,
+}
+
+/// The cleanup of the most recent effect, shared with the tasks and callbacks that produce it
+#[derive(Default)]
+struct Cleanup {
+    generation: Cell<usize>,
+    pending: RefCell<Option<Box<dyn FnOnce()>>>,
+}
+
+impl Cleanup {
+    /// Start a new effect, cleaning up after the previous one
+    fn next(&self) -> usize {
+        self.run();
+        self.generation.set(self.generation.get() + 1);
+        self.generation.get()
+    }
+
+    /// Store the cleanup of an effect, or run it right away if a newer effect has already started
+    fn finish(&self, generation: usize, cleanup: impl EffectCleanup) {
+        match generation == self.generation.get() {
+            true => *self.pending.borrow_mut() = Some(Box::new(move || cleanup.cleanup())),
+            false => cleanup.cleanup(),
+        }
+    }
+
+    fn run(&self) {
+        let pending = self.pending.borrow_mut().take();
+        if let Some(cleanup) = pending {
+            cleanup();
+        }
+    }
+}
+
 /// A hook that provides a future that executes after the hooks have been applied
 ///
 /// Whenever the hooks dependencies change, the future will be re-evaluated.
 /// If a future is pending when the dependencies change, the previous future
-/// will be allowed to continue
+/// will be allowed to continue. Use [`use_effect_with_mode`] to cancel it instead.
+///
+/// The future can resolve to an [`EffectCleanup`], which runs before the next effect and when the component is
+/// removed.
 ///
 /// - dependencies: a tuple of references to values that are PartialEq + Clone
 ///
@@ -22,37 +90,157 @@ use crate::UseFutureDep;
 ///     }))
 /// }
 /// ```
-pub fn use_effect<T, F, D>(cx: &ScopeState, dependencies: D, future: impl FnOnce(D::Out) -> F)
+pub fn use_effect<C, F, D>(cx: &ScopeState, dependencies: D, future: impl FnOnce(D::Out) -> F)
 where
-    T: 'static,
-    F: Future<Output = T> + 'static,
+    C: EffectCleanup,
+    F: Future<Output = C> + 'static,
+    D: UseFutureDep,
+{
+    use_effect_with_mode(cx, EffectMode::Continue, dependencies, future)
+}
+
+/// [`use_effect`], with control over what happens to a pending future when the dependencies change.
+///
+/// ## Examples
+///
+/// ```rust, ignore
+/// #[inline_props]
+/// fn app(cx: Scope, query: String) -> Element {
+///     let results = use_state(&cx, Vec::new);
+///
+///     // only the results of the latest query are ever shown
+///     use_effect_with_mode(&cx, EffectMode::Cancel, (query,), |(query,)| {
+///         to_owned![results];
+///         async move {
+///             results.set(search(&query).await);
+///         }
+///     });
+///
+///     cx.render(rsx!( Results { results: results } ))
+/// }
+/// ```
+pub fn use_effect_with_mode<C, F, D>(
+    cx: &ScopeState,
+    mode: EffectMode,
+    dependencies: D,
+    future: impl FnOnce(D::Out) -> F,
+) where
+    C: EffectCleanup,
+    F: Future<Output = C> + 'static,
     D: UseFutureDep,
 {
     struct UseEffect {
         needs_regen: bool,
         task: Cell<Option<TaskId>>,
         dependencies: Vec<Box<dyn Any>>,
+        cleanup: Rc<Cleanup>,
     }
 
-    let state = cx.use_hook(move |_| UseEffect {
-        needs_regen: true,
-        task: Cell::new(None),
-        dependencies: Vec::new(),
+    let state = cx.use_hook(move |_| {
+        let cleanup = Rc::new(Cleanup::default());
+        cx.on_destroy({
+            let cleanup = cleanup.clone();
+            move || cleanup.run()
+        });
+
+        UseEffect {
+            needs_regen: true,
+            task: Cell::new(None),
+            dependencies: Vec::new(),
+            cleanup,
+        }
     });
 
     if dependencies.clone().apply(&mut state.dependencies) || state.needs_regen {
         // We don't need regen anymore
         state.needs_regen = false;
 
+        if let (EffectMode::Cancel, Some(task)) = (mode, state.task.take()) {
+            cx.remove_future(task);
+        }
+
+        let generation = state.cleanup.next();
+
         // Create the new future
         let fut = future(dependencies.out());
+        let cleanup = state.cleanup.clone();
 
         state.task.set(Some(cx.push_future(async move {
-            fut.await;
+            cleanup.finish(generation, fut.await);
         })));
     }
 }
 
+/// A hook that runs a callback synchronously, once the renderer has applied the edits of the render where its
+/// dependencies changed.
+///
+/// Unlike [`use_effect`], the elements the component rendered are guaranteed to be mounted when the callback runs,
+/// so it can measure them or move focus before anything else happens. The callback can return an [`EffectCleanup`],
+/// which runs before the callback runs again and when the component is removed.
+///
+/// The callback runs when the renderer calls `VirtualDom::mutations_applied`.
+///
+/// ## Examples
+///
+/// ```rust, ignore
+/// #[inline_props]
+/// fn app(cx: Scope, open: bool) -> Element {
+///     let input = use_node_ref(&cx);
+///
+///     use_layout_effect(&cx, (open,), {
+///         let input = input.clone();
+///         move |(open,)| {
+///             if let (true, Some(el)) = (open, input.get::<web_sys::HtmlElement>()) {
+///                 el.focus().unwrap();
+///             }
+///         }
+///     });
+///
+///     cx.render(rsx!( input { node_ref: input } ))
+/// }
+/// ```
+pub fn use_layout_effect<C, D>(
+    cx: &ScopeState,
+    dependencies: D,
+    effect: impl FnOnce(D::Out) -> C + 'static,
+) where
+    C: EffectCleanup,
+    D: UseFutureDep,
+    D::Out: 'static,
+{
+    struct UseLayoutEffect {
+        needs_regen: bool,
+        dependencies: Vec<Box<dyn Any>>,
+        cleanup: Rc<Cleanup>,
+    }
+
+    let state = cx.use_hook(move |_| {
+        let cleanup = Rc::new(Cleanup::default());
+        cx.on_destroy({
+            let cleanup = cleanup.clone();
+            move || cleanup.run()
+        });
+
+        UseLayoutEffect {
+            needs_regen: true,
+            dependencies: Vec::new(),
+            cleanup,
+        }
+    });
+
+    if dependencies.clone().apply(&mut state.dependencies) || state.needs_regen {
+        state.needs_regen = false;
+
+        let dependencies = dependencies.out();
+        let cleanup = state.cleanup.clone();
+
+        cx.queue_effect(move || {
+            let generation = cleanup.next();
+            cleanup.finish(generation, effect(dependencies));
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,6 +274,18 @@ mod tests {
                 //
             });
 
+            // cleans up before running again
+            use_effect(&cx, (&cx.props.c,), |(c,)| async move {
+                move || {
+                    //
+                }
+            });
+
+            // runs after the renderer applies the edits
+            use_layout_effect(&cx, (&cx.props.d,), |(d,)| {
+                //
+            });
+
             None
         }
     }
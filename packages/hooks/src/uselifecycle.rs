@@ -0,0 +1,37 @@
+use dioxus_core::ScopeState;
+
+/// Run a callback once, after the renderer has applied the edits that created this component.
+///
+/// The callback runs when the renderer calls `VirtualDom::mutations_applied`, so the component's nodes are mounted by
+/// the time it runs. Children are mounted before their parents.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn app(cx: Scope) -> Element {
+///     use_on_mount(&cx, || log::info!("the app is on the screen"));
+///
+///     cx.render(rsx!( div { id: "main" } ))
+/// }
+/// ```
+pub fn use_on_mount(cx: &ScopeState, f: impl FnOnce() + 'static) {
+    cx.use_hook(|_| cx.queue_effect(f));
+}
+
+/// Run a callback when this component is removed.
+///
+/// The callback runs before the component's hooks are dropped and before the renderer removes its nodes. Children are
+/// destroyed before their parents.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn app(cx: Scope) -> Element {
+///     use_on_destroy(&cx, || log::info!("goodbye"));
+///
+///     cx.render(rsx!( div { id: "main" } ))
+/// }
+/// ```
+pub fn use_on_destroy(cx: &ScopeState, f: impl FnOnce() + 'static) {
+    cx.use_hook(|_| cx.on_destroy(f));
+}
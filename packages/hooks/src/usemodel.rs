@@ -4,39 +4,103 @@
 //!
 //! In these cases, we provide `use_model` - a convenient way of abstracting over some state and async functions.
 
-use dioxus_core::prelude::ScopeState;
+use crate::CoroutineHandle;
+use dioxus_core::{prelude::ScopeState, TaskId};
+use futures_channel::mpsc::UnboundedReceiver;
 use std::{
     cell::{Cell, Ref, RefCell, RefMut},
+    fmt::Display,
     future::Future,
-    marker::PhantomData,
-    pin::Pin,
     rc::Rc,
+    sync::Arc,
 };
 
+/// Store a model: some state, and the async tasks that work on it.
+///
+/// Tasks started by the model belong to the component and are cancelled when it is unmounted. They get a
+/// [`ModelHandle`] to the model, and writing to it from a task re-renders the component. The status of every task is
+/// kept, so the component can show that it's loading or that a task failed.
+///
+/// ```rust, ignore
+/// struct SignupForm {
+///     email: String,
+///     available: Option<bool>,
+/// }
+///
+/// fn Signup(cx: Scope) -> Element {
+///     let form = use_model(&cx, || SignupForm { email: String::new(), available: None });
+///
+///     let check = move |_| {
+///         form.try_start(|form| async move {
+///             let email = form.read().email.clone();
+///             form.write().available = Some(check_email(&email).await?);
+///             Ok::<_, reqwest::Error>(())
+///         });
+///     };
+///
+///     cx.render(rsx!(
+///         button { onclick: check, "check availability" }
+///         form.is_loading().then(|| rsx!("checking..."))
+///         form.error().map(|error| rsx!("{error}"))
+///     ))
+/// }
+/// ```
 pub fn use_model<'a, T: 'static>(cx: &'a ScopeState, f: impl FnOnce() -> T) -> UseModel<'a, T> {
-    let inner = cx.use_hook(|_| UseModelInner {
-        update_scheduled: Cell::new(false),
-        update_callback: cx.schedule_update(),
-        value: RefCell::new(f()),
-        // tasks: RefCell::new(Vec::new()),
+    let inner = cx.use_hook(|_| {
+        Rc::new(UseModelInner {
+            update_scheduled: Cell::new(false),
+            update_callback: cx.schedule_update(),
+            value: RefCell::new(f()),
+            tasks: RefCell::new(Vec::new()),
+        })
     });
 
     inner.update_scheduled.set(false);
-    UseModel { inner }
+    UseModel { cx, inner }
+}
+
+/// Keep a coroutine going that works on a model.
+///
+/// Like [`use_coroutine`](crate::use_coroutine), but the coroutine gets a handle to the model, and it's tracked with
+/// the model's other tasks.
+pub fn use_model_coroutine<'a, T, M, F>(
+    cx: &'a ScopeState,
+    model: UseModel<T>,
+    f: impl FnOnce(ModelHandle<T>, UnboundedReceiver<M>) -> F,
+) -> &'a CoroutineHandle<M>
+where
+    T: 'static,
+    M: 'static,
+    F: Future<Output = ()> + 'static,
+{
+    cx.use_hook(|_| {
+        let (tx, rx) = futures_channel::mpsc::unbounded();
+        let task = model.start(|model| f(model, rx));
+        CoroutineHandle { tx, task }
+    })
 }
 
 pub struct UseModel<'a, T> {
-    inner: &'a UseModelInner<T>,
+    cx: &'a ScopeState,
+    inner: &'a Rc<UseModelInner<T>>,
 }
 
 struct UseModelInner<T> {
     update_scheduled: Cell<bool>,
-    update_callback: Rc<dyn Fn()>,
+    update_callback: Arc<dyn Fn()>,
     value: RefCell<T>,
-    // tasks: RefCell<Vec<ModelTask>>,
+    tasks: RefCell<Vec<(TaskId, TaskStatus)>>,
 }
 
-type ModelTask = Pin<Box<dyn Future<Output = ()> + 'static>>;
+/// The status of a task started by a model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskStatus {
+    Running,
+    Finished,
+
+    /// The task returned an error, which is kept as its message
+    Failed(String),
+}
 
 impl<'a, T: 'static> UseModel<'a, T> {
     pub fn read(&self) -> Ref<'_, T> {
@@ -52,10 +116,7 @@ impl<'a, T: 'static> UseModel<'a, T> {
     }
 
     pub fn needs_update(&self) {
-        if !self.inner.update_scheduled.get() {
-            self.inner.update_scheduled.set(true);
-            (self.inner.update_callback)();
-        }
+        self.inner.needs_update();
     }
 
     pub fn set(&self, new: T) {
@@ -67,26 +128,187 @@ impl<'a, T: 'static> UseModel<'a, T> {
         (self.read(), self)
     }
 
-    pub fn start(&self, _f: impl FnOnce() -> ModelTask) {
-        todo!()
+    /// Get a handle to the model that can be moved into tasks and callbacks
+    pub fn handle(&self) -> ModelHandle<T> {
+        ModelHandle {
+            inner: self.inner.clone(),
+        }
     }
-}
 
-// keep a coroutine going
-pub fn use_model_coroutine<'a, T, F: Future<Output = ()> + 'static>(
-    cx: &'a ScopeState,
-    _model: UseModel<T>,
-    _f: impl FnOnce(AppModels) -> F,
-) -> UseModelCoroutine {
-    cx.use_hook(|_| UseModelTaskInner {
-        task: Default::default(),
-    });
-    todo!()
+    /// Start a task that works on the model.
+    ///
+    /// The task is cancelled when the component is unmounted.
+    pub fn start<F>(&self, f: impl FnOnce(ModelHandle<T>) -> F) -> TaskId
+    where
+        F: Future<Output = ()> + 'static,
+    {
+        let fut = f(self.handle());
+        self.spawn(async move {
+            fut.await;
+            TaskStatus::Finished
+        })
+    }
+
+    /// Start a task that works on the model and can fail.
+    ///
+    /// If the task returns an error, its status becomes [`TaskStatus::Failed`] with the error's message.
+    pub fn try_start<F, E>(&self, f: impl FnOnce(ModelHandle<T>) -> F) -> TaskId
+    where
+        F: Future<Output = Result<(), E>> + 'static,
+        E: Display,
+    {
+        let fut = f(self.handle());
+        self.spawn(async move {
+            match fut.await {
+                Ok(()) => TaskStatus::Finished,
+                Err(error) => TaskStatus::Failed(error.to_string()),
+            }
+        })
+    }
+
+    fn spawn(&self, fut: impl Future<Output = TaskStatus> + 'static) -> TaskId {
+        // the task isn't polled until after it's spawned, so it can always find its id
+        let id = Rc::new(Cell::new(None));
+        let inner = self.inner.clone();
+
+        let task = self.cx.push_future({
+            let id = id.clone();
+            async move {
+                let status = fut.await;
+                if let Some(id) = id.get() {
+                    inner.set_status(id, status);
+                }
+            }
+        });
+
+        id.set(Some(task));
+        self.inner
+            .tasks
+            .borrow_mut()
+            .push((task, TaskStatus::Running));
+        self.needs_update();
+
+        task
+    }
+
+    /// Cancel a task, forgetting its status
+    pub fn cancel(&self, task: TaskId) {
+        self.cx.remove_future(task);
+        self.inner.tasks.borrow_mut().retain(|(id, _)| *id != task);
+        self.needs_update();
+    }
+
+    /// Get the status of a task, if it wasn't cancelled or cleared
+    pub fn status(&self, task: TaskId) -> Option<TaskStatus> {
+        self.inner
+            .tasks
+            .borrow()
+            .iter()
+            .find(|(id, _)| *id == task)
+            .map(|(_, status)| status.clone())
+    }
+
+    /// Get the status of every task that wasn't cancelled or cleared, in the order they were started
+    pub fn tasks(&self) -> Vec<(TaskId, TaskStatus)> {
+        self.inner.tasks.borrow().clone()
+    }
+
+    /// Check if any task is still running
+    pub fn is_loading(&self) -> bool {
+        self.inner
+            .tasks
+            .borrow()
+            .iter()
+            .any(|(_, status)| *status == TaskStatus::Running)
+    }
+
+    /// Get the error of the last task that failed
+    pub fn error(&self) -> Option<String> {
+        self.inner
+            .tasks
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|(_, status)| match status {
+                TaskStatus::Failed(error) => Some(error.clone()),
+                _ => None,
+            })
+    }
+
+    /// Forget the tasks that are no longer running
+    pub fn clear_finished(&self) {
+        self.inner
+            .tasks
+            .borrow_mut()
+            .retain(|(_, status)| *status == TaskStatus::Running);
+        self.needs_update();
+    }
 }
 
 impl<T> Copy for UseModel<'_, T> {}
 impl<'a, T> Clone for UseModel<'a, T> {
     fn clone(&self) -> Self {
-        Self { inner: self.inner }
+        *self
+    }
+}
+
+/// A handle to a model that isn't tied to a render, for use in tasks and callbacks.
+///
+/// Writing to the model re-renders the component that owns it.
+pub struct ModelHandle<T> {
+    inner: Rc<UseModelInner<T>>,
+}
+
+impl<T: 'static> ModelHandle<T> {
+    pub fn read(&self) -> Ref<'_, T> {
+        self.inner.value.borrow()
+    }
+
+    pub fn write(&self) -> RefMut<'_, T> {
+        self.inner.needs_update();
+        self.inner.value.borrow_mut()
+    }
+
+    /// Allows the ability to write the value without forcing a re-render
+    pub fn write_silent(&self) -> RefMut<'_, T> {
+        self.inner.value.borrow_mut()
+    }
+
+    pub fn set(&self, new: T) {
+        *self.inner.value.borrow_mut() = new;
+        self.inner.needs_update();
+    }
+
+    pub fn needs_update(&self) {
+        self.inner.needs_update();
+    }
+}
+
+impl<T> Clone for ModelHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> UseModelInner<T> {
+    fn needs_update(&self) {
+        if !self.update_scheduled.get() {
+            self.update_scheduled.set(true);
+            (self.update_callback)();
+        }
+    }
+
+    fn set_status(&self, task: TaskId, status: TaskStatus) {
+        if let Some((_, current)) = self
+            .tasks
+            .borrow_mut()
+            .iter_mut()
+            .find(|(id, _)| *id == task)
+        {
+            *current = status;
+        }
+        self.needs_update();
     }
 }
@@ -0,0 +1,30 @@
+use dioxus_core::{NodeRef, ScopeState};
+
+/// Get a [`NodeRef`] that can be attached to an element with the `node_ref` attribute.
+///
+/// Once the element is mounted, the renderer provides a handle to it that can be read with [`NodeRef::get`]. The
+/// handle's type depends on the renderer: a `web_sys::Element` on the web, a `TuiNode` in the TUI renderer and a
+/// `DesktopElement` on desktop.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn app(cx: Scope) -> Element {
+///     let input = use_node_ref(&cx);
+///
+///     cx.render(rsx!(
+///         input { node_ref: input }
+///         button {
+///             onclick: move |_| {
+///                 if let Some(el) = input.get::<web_sys::Element>() {
+///                     el.scroll_into_view();
+///                 }
+///             },
+///             "show the input"
+///         }
+///     ))
+/// }
+/// ```
+pub fn use_node_ref(cx: &ScopeState) -> &NodeRef {
+    cx.use_hook(|_| NodeRef::new())
+}
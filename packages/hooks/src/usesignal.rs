@@ -0,0 +1,444 @@
+use dioxus_core::{current_scope_id, ScopeId, ScopeState};
+use std::{
+    any::Any,
+    cell::{Cell, Ref, RefCell, RefMut},
+    fmt::{self, Debug, Display},
+    marker::PhantomData,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Create a signal: a piece of state that knows which components read it.
+///
+/// Reading a signal while a component renders subscribes that component, and writing to it re-renders only the
+/// subscribed components - not the component that owns the signal, unless it read it too. The signal is dropped with
+/// the component that called `use_signal`.
+///
+/// [`Signal`] is a `Copy` handle, so it can be moved into event handlers and tasks, or passed to child components,
+/// without cloning or `to_owned!`.
+///
+/// ```rust, ignore
+/// fn App(cx: Scope) -> Element {
+///     let mut count = use_signal(&cx, || 0);
+///
+///     cx.spawn(async move {
+///         count.set(10);
+///     });
+///
+///     cx.render(rsx!(
+///         button { onclick: move |_| count += 1, "increment" }
+///         Display { count: count }
+///     ))
+/// }
+///
+/// #[inline_props]
+/// fn Display(cx: Scope, count: Signal<i32>) -> Element {
+///     // only this component re-renders when the count changes
+///     cx.render(rsx!("{count}"))
+/// }
+/// ```
+pub fn use_signal<T: 'static>(cx: &ScopeState, init: impl FnOnce() -> T) -> Signal<T> {
+    let owner = cx.use_hook(|_| {
+        SignalOwner(SlotRef::new(
+            cx.schedule_update_any(),
+            Some(Box::new(init())),
+            None,
+        ))
+    });
+
+    Signal {
+        inner: owner.0,
+        _marker: PhantomData,
+    }
+}
+
+/// Create a signal derived from other signals.
+///
+/// The value is computed the first time it's read, and recomputed on the next read after any signal it read changes.
+/// Components that read the memo re-render when one of its signals changes.
+///
+/// ```rust, ignore
+/// let count = use_signal(&cx, || 1);
+/// let doubled = use_memo(&cx, move || count.get() * 2);
+///
+/// cx.render(rsx!("{doubled}"))
+/// ```
+pub fn use_memo<T: 'static>(
+    cx: &ScopeState,
+    compute: impl Fn() -> T + 'static,
+) -> ReadOnlySignal<T> {
+    let owner = cx.use_hook(|_| {
+        let compute = Rc::new(move || Box::new(compute()) as Box<dyn Any>);
+        SignalOwner(SlotRef::new(cx.schedule_update_any(), None, Some(compute)))
+    });
+
+    ReadOnlySignal {
+        signal: Signal {
+            inner: owner.0,
+            _marker: PhantomData,
+        },
+    }
+}
+
+/// A handle to a signal created with [`use_signal`].
+///
+/// Using a signal after the component that owns it was dropped panics.
+pub struct Signal<T: 'static> {
+    inner: SlotRef,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> Signal<T> {
+    /// Borrow the value, subscribing the component that is rendering to changes.
+    pub fn read(&self) -> Ref<'static, T> {
+        self.inner.subscribe();
+        self.peek()
+    }
+
+    /// Borrow the value without subscribing to changes.
+    pub fn peek(&self) -> Ref<'static, T> {
+        self.inner.recompute_if_dirty();
+        Ref::map(self.inner.slot().value.borrow(), |value| {
+            value
+                .as_ref()
+                .and_then(|value| value.downcast_ref::<T>())
+                .expect("signals always hold a value of their type")
+        })
+    }
+
+    /// Get a clone of the value, subscribing the component that is rendering to changes.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.read().clone()
+    }
+
+    /// Run a closure with the value, subscribing the component that is rendering to changes.
+    pub fn with<O>(&self, f: impl FnOnce(&T) -> O) -> O {
+        f(&*self.read())
+    }
+
+    /// Mutably borrow the value, re-rendering every subscribed component.
+    pub fn write(&self) -> RefMut<'static, T> {
+        self.inner.notify();
+        RefMut::map(self.inner.slot().value.borrow_mut(), |value| {
+            value
+                .as_mut()
+                .and_then(|value| value.downcast_mut::<T>())
+                .expect("signals always hold a value of their type")
+        })
+    }
+
+    /// Replace the value, re-rendering every subscribed component.
+    pub fn set(&self, value: T) {
+        *self.write() = value;
+    }
+
+    /// Run a closure with a mutable reference to the value, re-rendering every subscribed component.
+    pub fn with_mut<O>(&self, f: impl FnOnce(&mut T) -> O) -> O {
+        f(&mut *self.write())
+    }
+}
+
+impl<T: 'static> Clone for Signal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for Signal<T> {}
+
+impl<T: 'static> PartialEq for Signal<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: Display + 'static> Display for Signal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.read(), f)
+    }
+}
+
+impl<T: Debug + 'static> Debug for Signal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&*self.read(), f)
+    }
+}
+
+impl<T: std::ops::Add<Output = T> + Copy + 'static> std::ops::AddAssign<T> for Signal<T> {
+    fn add_assign(&mut self, rhs: T) {
+        self.with_mut(|value| *value = *value + rhs);
+    }
+}
+
+impl<T: std::ops::Sub<Output = T> + Copy + 'static> std::ops::SubAssign<T> for Signal<T> {
+    fn sub_assign(&mut self, rhs: T) {
+        self.with_mut(|value| *value = *value - rhs);
+    }
+}
+
+/// A signal that can only be read, like the memos created with [`use_memo`].
+pub struct ReadOnlySignal<T: 'static> {
+    signal: Signal<T>,
+}
+
+impl<T: 'static> ReadOnlySignal<T> {
+    /// Borrow the value, subscribing the component that is rendering to changes.
+    pub fn read(&self) -> Ref<'static, T> {
+        self.signal.read()
+    }
+
+    /// Borrow the value without subscribing to changes.
+    pub fn peek(&self) -> Ref<'static, T> {
+        self.signal.peek()
+    }
+
+    /// Get a clone of the value, subscribing the component that is rendering to changes.
+    pub fn get(&self) -> T
+    where
+        T: Clone,
+    {
+        self.signal.get()
+    }
+
+    /// Run a closure with the value, subscribing the component that is rendering to changes.
+    pub fn with<O>(&self, f: impl FnOnce(&T) -> O) -> O {
+        self.signal.with(f)
+    }
+}
+
+impl<T: 'static> From<Signal<T>> for ReadOnlySignal<T> {
+    fn from(signal: Signal<T>) -> Self {
+        Self { signal }
+    }
+}
+
+impl<T: 'static> Clone for ReadOnlySignal<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: 'static> Copy for ReadOnlySignal<T> {}
+
+impl<T: 'static> PartialEq for ReadOnlySignal<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.signal == other.signal
+    }
+}
+
+impl<T: Display + 'static> Display for ReadOnlySignal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.signal, f)
+    }
+}
+
+impl<T: Debug + 'static> Debug for ReadOnlySignal<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.signal, f)
+    }
+}
+
+type Compute = Rc<dyn Fn() -> Box<dyn Any>>;
+pub(crate) type UpdateAny = Arc<dyn Fn(ScopeId)>;
+
+/// The storage of a signal.
+///
+/// Slots are leaked and reused for new signals once their signal is dropped, which is what lets handles be `Copy` and
+/// `'static`. The generation tells handles to a reused slot that their signal is gone.
+#[derive(Default)]
+struct Slot {
+    generation: Cell<u32>,
+    value: RefCell<Option<Box<dyn Any>>>,
+
+    /// Recomputes the value of a memo
+    compute: RefCell<Option<Compute>>,
+
+    /// Set when a signal the memo read changed since it was computed
+    dirty: Cell<bool>,
+
+    subscribers: RefCell<Vec<Subscriber>>,
+    update_any: RefCell<Option<UpdateAny>>,
+}
+
+thread_local! {
+    static FREE_SLOTS: RefCell<Vec<&'static Slot>> = const { RefCell::new(Vec::new()) };
+
+    /// The memos being computed, innermost last
+    static COMPUTING: RefCell<Vec<SlotRef>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Copy)]
+struct SlotRef {
+    slot: &'static Slot,
+    generation: u32,
+}
+
+impl PartialEq for SlotRef {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.slot, other.slot) && self.generation == other.generation
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Subscriber {
+    Scope(ScopeId),
+    Memo(SlotRef),
+}
+
+impl SlotRef {
+    fn new(update_any: UpdateAny, value: Option<Box<dyn Any>>, compute: Option<Compute>) -> Self {
+        let slot = FREE_SLOTS
+            .with(|free| free.borrow_mut().pop())
+            .unwrap_or_else(|| Box::leak(Box::new(Slot::default())));
+
+        slot.dirty.set(compute.is_some());
+        *slot.value.borrow_mut() = value;
+        *slot.compute.borrow_mut() = compute;
+        *slot.update_any.borrow_mut() = Some(update_any);
+
+        Self {
+            slot,
+            generation: slot.generation.get(),
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.slot.generation.get() == self.generation
+    }
+
+    fn slot(&self) -> &'static Slot {
+        assert!(
+            self.is_alive(),
+            "a signal was used after the component that owns it was dropped"
+        );
+        self.slot
+    }
+
+    /// Subscribe the memo being computed, or else the component being rendered
+    fn subscribe(&self) {
+        let subscriber = COMPUTING
+            .with(|computing| computing.borrow().last().copied())
+            .map(Subscriber::Memo)
+            .or_else(|| current_scope_id().map(Subscriber::Scope));
+
+        if let Some(subscriber) = subscriber {
+            let mut subscribers = self.slot().subscribers.borrow_mut();
+            if !subscribers.contains(&subscriber) {
+                subscribers.push(subscriber);
+            }
+        }
+    }
+
+    /// Mark the subscribed components dirty and the subscribed memos stale.
+    ///
+    /// Subscribers are forgotten until they read the signal again.
+    fn notify(&self) {
+        let slot = self.slot();
+        let subscribers = std::mem::take(&mut *slot.subscribers.borrow_mut());
+        let update_any = slot.update_any.borrow().clone();
+
+        for subscriber in subscribers {
+            match subscriber {
+                Subscriber::Scope(id) => {
+                    if let Some(update_any) = &update_any {
+                        update_any(id);
+                    }
+                }
+                Subscriber::Memo(memo) => {
+                    if memo.is_alive() && !memo.slot.dirty.get() {
+                        memo.slot.dirty.set(true);
+                        memo.notify();
+                    }
+                }
+            }
+        }
+    }
+
+    fn recompute_if_dirty(&self) {
+        let slot = self.slot();
+        if !slot.dirty.get() {
+            return;
+        }
+
+        let compute = slot.compute.borrow().clone();
+        if let Some(compute) = compute {
+            let value = {
+                let _computing = Computing::push(*self);
+                compute()
+            };
+            *slot.value.borrow_mut() = Some(value);
+        }
+        slot.dirty.set(false);
+    }
+}
+
+/// Tracks the reads of a memo while it's computed, even if computing it panics
+struct Computing;
+
+impl Computing {
+    fn push(memo: SlotRef) -> Self {
+        COMPUTING.with(|computing| computing.borrow_mut().push(memo));
+        Computing
+    }
+}
+
+impl Drop for Computing {
+    fn drop(&mut self) {
+        COMPUTING.with(|computing| computing.borrow_mut().pop());
+    }
+}
+
+/// A signal owned by other state instead of a component, like the entries of a collection.
+///
+/// The signal is dropped with its owner.
+pub(crate) struct OwnedSignal<T: 'static> {
+    owner: SignalOwner,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> OwnedSignal<T> {
+    pub(crate) fn new(update_any: UpdateAny, value: T) -> Self {
+        Self {
+            owner: SignalOwner(SlotRef::new(update_any, Some(Box::new(value)), None)),
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn signal(&self) -> Signal<T> {
+        Signal {
+            inner: self.owner.0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Drop the signal, returning its value
+    pub(crate) fn into_inner(self) -> T {
+        let value = self.owner.0.slot().value.borrow_mut().take();
+        *value
+            .and_then(|value| value.downcast::<T>().ok())
+            .expect("signals always hold a value of their type")
+    }
+}
+
+/// Frees the slot of a signal when the component or state that owns it is dropped
+struct SignalOwner(SlotRef);
+
+impl Drop for SignalOwner {
+    fn drop(&mut self) {
+        let slot = self.0.slot;
+        slot.generation.set(slot.generation.get().wrapping_add(1));
+
+        let value = slot.value.borrow_mut().take();
+        let compute = slot.compute.borrow_mut().take();
+        slot.subscribers.borrow_mut().clear();
+        slot.update_any.borrow_mut().take();
+        FREE_SLOTS.with(|free| free.borrow_mut().push(slot));
+
+        // the value may own other signals, so it's dropped once the slot is back to normal
+        drop(value);
+        drop(compute);
+    }
+}
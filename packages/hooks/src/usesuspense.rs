@@ -2,30 +2,53 @@ use std::{cell::Cell, future::Future, rc::Rc};
 
 use dioxus_core::{Element, ScopeState, TaskId};
 
-pub fn use_suspense<R: 'static, F: Future<Output = R> + 'static>(
-    cx: &ScopeState,
+/// Render the output of a future once it has resolved, rendering nothing until then.
+///
+/// The future is created once, on the first render. When this component sits beneath a suspense
+/// boundary (see [`ScopeState::provide_suspense_boundary`]), the boundary shows its fallback until
+/// the future has completed.
+///
+/// ## Example
+///
+/// ```rust, ignore
+/// fn Profile(cx: Scope) -> Element {
+///     use_suspense(
+///         &cx,
+///         || fetch_user(),
+///         |user| cx.render(rsx!("hello {user.name}")),
+///     )
+/// }
+/// ```
+pub fn use_suspense<'a, R: 'static, F: Future<Output = R> + 'static>(
+    cx: &'a ScopeState,
     create_future: impl FnOnce() -> F,
-    render: impl FnOnce(&R) -> Element,
-) -> Element {
+    render: impl FnOnce(&R) -> Element<'a>,
+) -> Element<'a> {
     let sus = cx.use_hook(|_| {
         let fut = create_future();
 
         let wip_value: Rc<Cell<Option<R>>> = Default::default();
 
         let wip = wip_value.clone();
+        let update = cx.schedule_update();
         let new_fut = async move {
             let val = fut.await;
             wip.set(Some(val));
+            update();
         };
 
         let task = cx.push_future(new_fut);
         SuspenseInner {
             _task: task,
             value: None,
-            _wip_value: wip_value,
+            wip_value,
         }
     });
 
+    if let Some(value) = sus.wip_value.take() {
+        sus.value = Some(value);
+    }
+
     if let Some(value) = sus.value.as_ref() {
         render(value)
     } else {
@@ -36,6 +59,6 @@ pub fn use_suspense<R: 'static, F: Future<Output = R> + 'static>(
 
 struct SuspenseInner<R> {
     _task: TaskId,
-    _wip_value: Rc<Cell<Option<R>>>,
+    wip_value: Rc<Cell<Option<R>>>,
     value: Option<R>,
 }
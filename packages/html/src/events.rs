@@ -57,6 +57,56 @@ pub mod on {
                     }
                 )*
             )*
+
+            /// Get the `'static` name of an event, ie "click"
+            #[cfg(feature = "serialize")]
+            pub(crate) fn static_event_name(name: &str) -> Option<&'static str> {
+                $(
+                    $(
+                        let shortname: &'static str = &stringify!($name)[2..];
+                        if name == shortname {
+                            return Some(shortname);
+                        }
+                    )*
+                )*
+                None
+            }
+
+            /// Serialize the data of an event, if it holds the type its listeners expect
+            #[cfg(feature = "serialize")]
+            pub(crate) fn event_data_to_json(
+                name: &str,
+                data: &dyn std::any::Any,
+            ) -> Option<serde_json::Value> {
+                $(
+                    $(
+                        if name == &stringify!($name)[2..] {
+                            return data
+                                .downcast_ref::<$data>()
+                                .and_then(|data| serde_json::to_value(data).ok());
+                        }
+                    )*
+                )*
+                None
+            }
+
+            /// Deserialize the data of an event into the type its listeners expect
+            #[cfg(feature = "serialize")]
+            pub(crate) fn event_data_from_json(
+                name: &str,
+                data: serde_json::Value,
+            ) -> Option<std::sync::Arc<dyn std::any::Any + Send + Sync>> {
+                $(
+                    $(
+                        if name == &stringify!($name)[2..] {
+                            return serde_json::from_value::<$data>(data)
+                                .ok()
+                                .map(|data| std::sync::Arc::new(data) as _);
+                        }
+                    )*
+                )*
+                None
+            }
         };
     }
 
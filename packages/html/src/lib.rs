@@ -18,9 +18,13 @@ mod events;
 pub mod geometry;
 mod global_attributes;
 pub mod input_data;
+#[cfg(feature = "serialize")]
+mod recording;
 #[cfg(feature = "wasm-bind")]
 mod web_sys_bind;
 
 pub use elements::*;
 pub use events::*;
 pub use global_attributes::*;
+#[cfg(feature = "serialize")]
+pub use recording::*;
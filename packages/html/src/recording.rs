@@ -0,0 +1,385 @@
+//! Record the messages a VirtualDom processes and replay them against a fresh one.
+//!
+//! A [`Recorder`] logs every [`SchedulerMsg`] the VirtualDom processes along with the mutations each call to
+//! `rebuild` and `work_with_deadline` returned. Every entry gets a logical timestamp, so a [`Recording`] captures the
+//! order things happened in without depending on wall-clock time. Event data is serialized by the event's name, so
+//! only the events in [`crate::on`] can be recorded.
+//!
+//! [`Recording::replay`] drives a fresh VirtualDom through the same sequence and checks that it produces the same
+//! mutations, turning a bug report into a regression test.
+//!
+//! Tasks are never polled during a replay. Updates they scheduled are replayed from the recording, but any state they
+//! changed is not, so a recording is only deterministic for apps driven by their events.
+//!
+//! ```rust, ignore
+//! let mut dom = VirtualDom::new(App);
+//! let recorder = Recorder::attach(&mut dom);
+//!
+//! apply_edits(recorder.rebuild(&mut dom));
+//! loop {
+//!     dom.wait_for_work().await;
+//!     apply_edits(recorder.work_with_deadline(&mut dom, || false));
+//! }
+//!
+//! // later, in a test
+//! let recording: Recording = serde_json::from_str(BUG_REPORT)?;
+//! recording.replay(&mut VirtualDom::new(App))?;
+//! ```
+
+use crate::on::{event_data_from_json, event_data_to_json, static_event_name};
+use dioxus_core::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+/// Everything a [`Recorder`] saw, in the order it happened.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The recorded entries
+    pub entries: Vec<RecordedEntry>,
+}
+
+/// A single step of a [`Recording`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum RecordedEntry {
+    /// The VirtualDom processed a message
+    Message {
+        /// The logical time the message was processed at
+        time: u64,
+
+        /// The message itself
+        message: RecordedMessage,
+    },
+
+    /// `rebuild` was called
+    Rebuild {
+        /// The logical time of the rebuild
+        time: u64,
+
+        /// The mutations the rebuild produced.
+        ///
+        /// `None` if they held an [`AttributeValue::Any`] and can't be checked.
+        mutations: Option<MutationsOwned>,
+    },
+
+    /// `work_with_deadline` was called
+    Work {
+        /// The logical time of the work
+        time: u64,
+
+        /// How many times the deadline was checked before it was reached, if it was reached
+        deadline: Option<usize>,
+
+        /// The mutations the work produced.
+        ///
+        /// `None` if they held an [`AttributeValue::Any`] and can't be checked.
+        mutations: Option<Vec<MutationsOwned>>,
+    },
+}
+
+/// A serialized [`SchedulerMsg`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum RecordedMessage {
+    /// A [`UserEvent`]
+    Event {
+        /// The name of the event, ie "click"
+        name: String,
+
+        /// The element the event was triggered on
+        element: Option<ElementId>,
+
+        /// The scope that triggered the event
+        scope: Option<ScopeId>,
+
+        /// The priority of the event
+        priority: EventPriority,
+
+        /// The event's data, or `None` if it wasn't the type the event's listeners expect
+        data: Option<serde_json::Value>,
+    },
+
+    /// A component was marked dirty
+    Immediate {
+        /// The component
+        scope: ScopeId,
+    },
+
+    /// A component spawned a task
+    NewTask {
+        /// The component
+        scope: ScopeId,
+    },
+}
+
+impl RecordedMessage {
+    /// Serialize a message
+    pub fn new(msg: &SchedulerMsg) -> Self {
+        match msg {
+            SchedulerMsg::Event(event) => {
+                let data: &dyn Any = &*event.data;
+                RecordedMessage::Event {
+                    name: event.name.to_string(),
+                    element: event.element,
+                    scope: event.scope_id,
+                    priority: event.priority,
+                    data: event_data_to_json(event.name, data),
+                }
+            }
+            SchedulerMsg::Immediate(scope) => RecordedMessage::Immediate { scope: *scope },
+            SchedulerMsg::NewTask(scope) => RecordedMessage::NewTask { scope: *scope },
+        }
+    }
+
+    /// Turn the message back into a [`SchedulerMsg`].
+    ///
+    /// Returns `None` if the message is an event that isn't in [`crate::on`], or its data is missing or can't be
+    /// deserialized.
+    pub fn to_message(&self) -> Option<SchedulerMsg> {
+        Some(match self {
+            RecordedMessage::Event {
+                name,
+                element,
+                scope,
+                priority,
+                data,
+            } => {
+                let name = static_event_name(name)?;
+                // the listeners downcast to the event's data type, so an event without data can't be delivered
+                let data = event_data_from_json(name, data.clone()?)?;
+
+                SchedulerMsg::Event(UserEvent {
+                    scope_id: *scope,
+                    priority: *priority,
+                    element: *element,
+                    name,
+                    data,
+                })
+            }
+            RecordedMessage::Immediate { scope } => SchedulerMsg::Immediate(*scope),
+            RecordedMessage::NewTask { scope } => SchedulerMsg::NewTask(*scope),
+        })
+    }
+}
+
+#[derive(Default)]
+struct RecorderState {
+    clock: u64,
+    interner: EditInterner,
+    recording: Recording,
+}
+
+impl RecorderState {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn own(&mut self, mutations: &Mutations) -> Option<MutationsOwned> {
+        self.interner.mutations(mutations).ok()
+    }
+}
+
+/// Records everything a VirtualDom does into a [`Recording`].
+///
+/// Messages are recorded as they're processed. To record mutations, call `rebuild` and `work_with_deadline` through
+/// the recorder instead of on the VirtualDom.
+#[derive(Clone, Default)]
+pub struct Recorder {
+    state: Rc<RefCell<RecorderState>>,
+}
+
+impl Recorder {
+    /// Start recording the messages processed by a VirtualDom.
+    ///
+    /// This replaces any observer set with [`VirtualDom::observe_messages`].
+    pub fn attach(dom: &mut VirtualDom) -> Self {
+        let recorder = Self::default();
+
+        let state = recorder.state.clone();
+        dom.observe_messages(move |msg| {
+            let mut state = state.borrow_mut();
+            let time = state.tick();
+            let message = RecordedMessage::new(msg);
+            state
+                .recording
+                .entries
+                .push(RecordedEntry::Message { time, message });
+        });
+
+        recorder
+    }
+
+    /// Call [`VirtualDom::rebuild`] and record the mutations it produces.
+    pub fn rebuild<'a>(&self, dom: &'a mut VirtualDom) -> Mutations<'a> {
+        let mutations = dom.rebuild();
+
+        let mut state = self.state.borrow_mut();
+        let time = state.tick();
+        let mutations_owned = state.own(&mutations);
+        state.recording.entries.push(RecordedEntry::Rebuild {
+            time,
+            mutations: mutations_owned,
+        });
+
+        mutations
+    }
+
+    /// Call [`VirtualDom::work_with_deadline`] and record the mutations it produces.
+    pub fn work_with_deadline<'a>(
+        &self,
+        dom: &'a mut VirtualDom,
+        mut deadline: impl FnMut() -> bool,
+    ) -> Vec<Mutations<'a>> {
+        let mut polls = 0;
+        let mut reached = None;
+        let mutations = dom.work_with_deadline(|| {
+            let done = deadline();
+            if done && reached.is_none() {
+                reached = Some(polls);
+            }
+            polls += 1;
+            done
+        });
+
+        let mut state = self.state.borrow_mut();
+        let time = state.tick();
+        let mutations_owned = mutations.iter().map(|m| state.own(m)).collect();
+        state.recording.entries.push(RecordedEntry::Work {
+            time,
+            deadline: reached,
+            mutations: mutations_owned,
+        });
+
+        mutations
+    }
+
+    /// Get a copy of everything recorded so far.
+    pub fn recording(&self) -> Recording {
+        self.state.borrow().recording.clone()
+    }
+
+    /// Take everything recorded so far, leaving the recorder empty.
+    pub fn take_recording(&self) -> Recording {
+        std::mem::take(&mut self.state.borrow_mut().recording)
+    }
+}
+
+/// An error encountered while replaying a [`Recording`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    /// The VirtualDom produced different mutations than the ones that were recorded
+    Diverged {
+        /// The logical time of the entry that diverged
+        time: u64,
+
+        /// The recorded mutations
+        expected: Vec<MutationsOwned>,
+
+        /// The mutations produced during the replay
+        found: Vec<MutationsOwned>,
+    },
+
+    /// The recording holds an event that isn't in [`crate::on`], or its data is missing or couldn't be deserialized
+    InvalidEvent {
+        /// The logical time of the event
+        time: u64,
+
+        /// The event
+        message: RecordedMessage,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Diverged {
+                time,
+                expected,
+                found,
+            } => write!(
+                f,
+                "replay diverged at time {}: expected {:?}, found {:?}",
+                time, expected, found
+            ),
+            ReplayError::InvalidEvent { time, message } => {
+                write!(f, "could not replay {:?} at time {}", message, time)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Recording {
+    /// Drive a fresh VirtualDom through the recording, checking it produces the same mutations.
+    ///
+    /// The VirtualDom must be built from the same root component and props as the recorded one.
+    pub fn replay(&self, dom: &mut VirtualDom) -> Result<(), ReplayError> {
+        let mut interner = EditInterner::new();
+
+        for entry in &self.entries {
+            match entry {
+                RecordedEntry::Message { time, message } => match message.to_message() {
+                    Some(msg) => dom.process_message(msg),
+                    None => {
+                        return Err(ReplayError::InvalidEvent {
+                            time: *time,
+                            message: message.clone(),
+                        })
+                    }
+                },
+
+                RecordedEntry::Rebuild { time, mutations } => {
+                    let found = interner.mutations(&dom.rebuild()).ok();
+                    if let Some(mutations) = mutations {
+                        check(*time, vec![mutations.clone()], found.map(|m| vec![m]))?;
+                    }
+                }
+
+                RecordedEntry::Work {
+                    time,
+                    deadline,
+                    mutations,
+                } => {
+                    let mut polls = 0;
+                    let found: Option<Vec<_>> = dom
+                        .work_with_deadline(|| {
+                            polls += 1;
+                            Some(polls - 1) == *deadline
+                        })
+                        .iter()
+                        .map(|m| interner.mutations(m).ok())
+                        .collect();
+
+                    if let Some(mutations) = mutations {
+                        check(*time, mutations.clone(), found)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check(
+    time: u64,
+    expected: Vec<MutationsOwned>,
+    found: Option<Vec<MutationsOwned>>,
+) -> Result<(), ReplayError> {
+    let found = found.unwrap_or_default();
+    match expected == found {
+        true => Ok(()),
+        false => Err(ReplayError::Diverged {
+            time,
+            expected,
+            found,
+        }),
+    }
+}
@@ -14,12 +14,18 @@ extern "C" {
     #[wasm_bindgen(method)]
     pub fn SetNode(this: &Interpreter, id: usize, node: Node);
 
+    #[wasm_bindgen(method)]
+    pub fn GetNode(this: &Interpreter, id: usize) -> Node;
+
     #[wasm_bindgen(method)]
     pub fn PushRoot(this: &Interpreter, root: u64);
 
     #[wasm_bindgen(method)]
     pub fn PopRoot(this: &Interpreter);
 
+    #[wasm_bindgen(method)]
+    pub fn PushNamedRoot(this: &Interpreter, name: &str);
+
     #[wasm_bindgen(method)]
     pub fn AppendChildren(this: &Interpreter, many: u32);
 
@@ -47,6 +53,12 @@ extern "C" {
     #[wasm_bindgen(method)]
     pub fn CreatePlaceholder(this: &Interpreter, root: u64);
 
+    #[wasm_bindgen(method)]
+    pub fn SaveTemplate(this: &Interpreter, id: u64);
+
+    #[wasm_bindgen(method)]
+    pub fn CloneTemplate(this: &Interpreter, id: u64, root: u64);
+
     #[wasm_bindgen(method)]
     pub fn NewEventListener(this: &Interpreter, name: &str, root: u64, handler: &Function);
 
@@ -67,4 +79,7 @@ extern "C" {
 
     #[wasm_bindgen(method)]
     pub fn RemoveAttribute(this: &Interpreter, root: u64, field: &str, ns: Option<&str>);
+
+    #[wasm_bindgen(method, js_name = registerAttributeType)]
+    pub fn register_attribute_type(this: &Interpreter, name: &str, decode: &Function);
 }
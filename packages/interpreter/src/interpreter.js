@@ -13,6 +13,10 @@ export class Interpreter {
     this.handlers = {};
     this.lastNodeWasText = false;
     this.nodes = [root];
+    this.templates = [];
+    this.portalRoots = [];
+    this.attributeTypes = {};
+    this.decoder = new EditDecoder();
   }
   top() {
     return this.stack[this.stack.length - 1];
@@ -21,8 +25,12 @@ export class Interpreter {
     return this.stack.pop();
   }
   SetNode(id, node) {
+    node.dioxusId = id;
     this.nodes[id] = node;
   }
+  GetNode(id) {
+    return this.nodes[id];
+  }
   PushRoot(root) {
     const node = this.nodes[root];
     this.stack.push(node);
@@ -30,6 +38,23 @@ export class Interpreter {
   PopRoot() {
     this.stack.pop();
   }
+  PushNamedRoot(name) {
+    let root = document.getElementById(name);
+    if (root === null) {
+      root = document.createElement("div");
+      root.id = name;
+      document.body.appendChild(root);
+    }
+    // events are delegated to the main root, so portals outside of it need the same listeners
+    if (!this.root.contains(root) && !this.portalRoots.includes(root)) {
+      root.setAttribute("data-dioxus-portal", name);
+      for (const event_name in this.handlers) {
+        root.addEventListener(event_name, this.handlers[event_name]);
+      }
+      this.portalRoots.push(root);
+    }
+    this.stack.push(root);
+  }
   AppendChildren(many) {
     let root = this.stack[this.stack.length - (1 + many)];
     let to_add = this.stack.splice(this.stack.length - many);
@@ -65,11 +90,13 @@ export class Interpreter {
   }
   CreateElement(tag, root) {
     const el = document.createElement(tag);
+    el.dioxusId = root;
     this.nodes[root] = el;
     this.stack.push(el);
   }
   CreateElementNs(tag, root, ns) {
     let el = document.createElementNS(ns, tag);
+    el.dioxusId = root;
     this.stack.push(el);
     this.nodes[root] = el;
   }
@@ -79,6 +106,15 @@ export class Interpreter {
     this.stack.push(el);
     this.nodes[root] = el;
   }
+  SaveTemplate(id) {
+    this.templates[id] = this.stack.pop();
+  }
+  CloneTemplate(id, root) {
+    const node = this.templates[id].cloneNode(true);
+    node.dioxusId = root;
+    this.nodes[root] = node;
+    this.stack.push(node);
+  }
   NewEventListener(event_name, root, handler) {
     const element = this.nodes[root];
     element.setAttribute("data-dioxus-id", `${root}`);
@@ -86,6 +122,9 @@ export class Interpreter {
       this.listeners[event_name] = 1;
       this.handlers[event_name] = handler;
       this.root.addEventListener(event_name, handler);
+      for (const portal of this.portalRoots) {
+        portal.addEventListener(event_name, handler);
+      }
     } else {
       this.listeners[event_name]++;
     }
@@ -96,6 +135,9 @@ export class Interpreter {
     this.listeners[event_name]--;
     if (this.listeners[event_name] === 0) {
       this.root.removeEventListener(event_name, this.handlers[event_name]);
+      for (const portal of this.portalRoots) {
+        portal.removeEventListener(event_name, this.handlers[event_name]);
+      }
       delete this.listeners[event_name];
       delete this.handlers[event_name];
     }
@@ -103,9 +145,19 @@ export class Interpreter {
   SetText(root, text) {
     this.nodes[root].textContent = text;
   }
+  // Register a function that turns the payload of a custom attribute value with the given name back into a value
+  registerAttributeType(name, decode) {
+    this.attributeTypes[name] = decode;
+  }
   SetAttribute(root, field, value, ns) {
     const name = field;
     const node = this.nodes[root];
+    if (isCustomValue(value)) {
+      // custom values aren't strings once they're decoded, so they're set as properties
+      const decode = this.attributeTypes[value.name];
+      node[name] = decode ? decode(value.payload) : value.payload;
+      return;
+    }
     if (ns === "style") {
       // @ts-ignore
       node.style[name] = value;
@@ -159,12 +211,23 @@ export class Interpreter {
     for (let edit of edits) {
       this.handleEdit(edit);
     }
+    // the VirtualDom runs its effects once every batch it sent has been applied
+    window.ipc.postMessage(serializeIpcMessage("edits_applied"));
+  }
+  handleBinaryEdits(buffer) {
+    this.handleEdits(this.decoder.decode(buffer));
   }
   handleEdit(edit) {
     switch (edit.type) {
       case "PushRoot":
         this.PushRoot(edit.root);
         break;
+      case "PopRoot":
+        this.PopRoot();
+        break;
+      case "PushNamedRoot":
+        this.PushNamedRoot(edit.name);
+        break;
       case "AppendChildren":
         this.AppendChildren(edit.many);
         break;
@@ -236,6 +299,15 @@ export class Interpreter {
                 return;
               }
 
+              // events from inside a portal bubble up through where the portal was rendered, not its mount point
+              if (
+                target.parentElement.hasAttribute(`data-dioxus-portal`) &&
+                target.dioxusId !== undefined
+              ) {
+                realId = `${target.dioxusId}`;
+                break;
+              }
+
               target = target.parentElement;
               realId = target.getAttribute(`data-dioxus-id`);
             }
@@ -301,6 +373,12 @@ export class Interpreter {
       case "RemoveAttribute":
         this.RemoveAttribute(edit.root, edit.name, edit.ns);
         break;
+      case "SaveTemplate":
+        this.SaveTemplate(edit.id);
+        break;
+      case "CloneTemplate":
+        this.CloneTemplate(edit.id, edit.root);
+        break;
     }
   }
 }
@@ -574,9 +652,206 @@ export function serialize_event(event) {
     }
   }
 }
+function isCustomValue(value) {
+  return (
+    value !== null &&
+    typeof value === "object" &&
+    typeof value.name === "string" &&
+    typeof value.payload === "string"
+  );
+}
 function serializeIpcMessage(method, params = {}) {
   return JSON.stringify({ method, params });
 }
+
+// Decodes the binary edit format produced by `dioxus_core::EditEncoder` into the same objects as its JSON form.
+// The string table is kept between batches, so every batch from an encoder must be decoded, in order.
+export class EditDecoder {
+  constructor() {
+    this.strings = [];
+    this.textDecoder = new TextDecoder();
+  }
+  reset() {
+    this.strings = [];
+  }
+  decode(buffer) {
+    this.bytes = buffer instanceof Uint8Array ? buffer : new Uint8Array(buffer);
+    this.view = new DataView(
+      this.bytes.buffer,
+      this.bytes.byteOffset,
+      this.bytes.byteLength
+    );
+    this.pos = 0;
+
+    const version = this.bytes[this.pos++];
+    if (version !== 1) {
+      throw new Error(`unsupported edit encoding version ${version}`);
+    }
+
+    const newStrings = this.varint();
+    for (let i = 0; i < newStrings; i++) {
+      this.strings.push(this.str());
+    }
+
+    const count = this.varint();
+    const edits = new Array(count);
+    for (let i = 0; i < count; i++) {
+      edits[i] = this.edit();
+    }
+    return edits;
+  }
+  edit() {
+    switch (this.bytes[this.pos++]) {
+      case 0:
+        return { type: "PushRoot", root: this.varint() };
+      case 1:
+        return { type: "AppendChildren", many: this.varint() };
+      case 2:
+        return { type: "ReplaceWith", root: this.varint(), m: this.varint() };
+      case 3:
+        return { type: "InsertAfter", root: this.varint(), n: this.varint() };
+      case 4:
+        return { type: "InsertBefore", root: this.varint(), n: this.varint() };
+      case 5:
+        return { type: "Remove", root: this.varint() };
+      case 6:
+        return { type: "CreateTextNode", root: this.varint(), text: this.str() };
+      case 7:
+        return { type: "CreateElement", root: this.varint(), tag: this.interned() };
+      case 8:
+        return {
+          type: "CreateElementNs",
+          root: this.varint(),
+          tag: this.interned(),
+          ns: this.interned(),
+        };
+      case 9:
+        return { type: "CreatePlaceholder", root: this.varint() };
+      case 10:
+        return {
+          type: "NewEventListener",
+          event_name: this.interned(),
+          scope: this.varint(),
+          root: this.varint(),
+        };
+      case 11:
+        return {
+          type: "RemoveEventListener",
+          root: this.varint(),
+          event: this.interned(),
+        };
+      case 12:
+        return { type: "SetText", root: this.varint(), text: this.str() };
+      case 13:
+        return {
+          type: "SetAttribute",
+          root: this.varint(),
+          field: this.interned(),
+          value: this.value(),
+          ns: this.optionalInterned(),
+        };
+      case 14:
+        return {
+          type: "RemoveAttribute",
+          root: this.varint(),
+          name: this.interned(),
+          ns: this.optionalInterned(),
+        };
+      case 15:
+        return { type: "PopRoot" };
+      case 16:
+        return { type: "SaveTemplate", id: this.varint() };
+      case 17:
+        return { type: "CloneTemplate", id: this.varint(), root: this.varint() };
+      case 18:
+        return { type: "PushNamedRoot", name: this.interned() };
+      default:
+        throw new Error(`unknown edit opcode ${this.bytes[this.pos - 1]}`);
+    }
+  }
+  value() {
+    switch (this.bytes[this.pos++]) {
+      case 0:
+        return this.str();
+      case 1:
+        return this.float32();
+      case 2:
+        return this.float64();
+      case 3:
+      case 4:
+        return this.signed();
+      case 5:
+      case 6:
+        return this.varint();
+      case 7:
+        return this.bytes[this.pos++] === 1;
+      case 8:
+        return [this.float32(), this.float32(), this.float32()];
+      case 9:
+        return [this.signed(), this.signed(), this.signed()];
+      case 10:
+        return [this.varint(), this.varint(), this.varint()];
+      case 11:
+        return [this.float32(), this.float32(), this.float32(), this.float32()];
+      case 12:
+        return [this.signed(), this.signed(), this.signed(), this.signed()];
+      case 13:
+        return [this.varint(), this.varint(), this.varint(), this.varint()];
+      case 14: {
+        const len = this.varint();
+        const bytes = Array.from(this.bytes.subarray(this.pos, this.pos + len));
+        this.pos += len;
+        return bytes;
+      }
+      case 15:
+        return { name: this.str(), payload: this.str() };
+      default:
+        throw new Error(`unknown attribute value tag ${this.bytes[this.pos - 1]}`);
+    }
+  }
+  // numbers past 2^53 lose precision, just like they do when parsed from JSON
+  varint() {
+    let result = 0;
+    let shift = 1;
+    let byte;
+    do {
+      byte = this.bytes[this.pos++];
+      result += (byte & 0x7f) * shift;
+      shift *= 128;
+    } while (byte & 0x80);
+    return result;
+  }
+  signed() {
+    const zigzag = this.varint();
+    return zigzag % 2 === 0 ? zigzag / 2 : -(zigzag + 1) / 2;
+  }
+  float32() {
+    const value = this.view.getFloat32(this.pos, true);
+    this.pos += 4;
+    return value;
+  }
+  float64() {
+    const value = this.view.getFloat64(this.pos, true);
+    this.pos += 8;
+    return value;
+  }
+  str() {
+    const len = this.varint();
+    const text = this.textDecoder.decode(
+      this.bytes.subarray(this.pos, this.pos + len)
+    );
+    this.pos += len;
+    return text;
+  }
+  interned() {
+    return this.strings[this.varint()];
+  }
+  optionalInterned() {
+    const idx = this.varint();
+    return idx === 0 ? null : this.strings[idx - 1];
+  }
+}
+
 const bool_attrs = {
   allowfullscreen: true,
   allowpaymentrequest: true,
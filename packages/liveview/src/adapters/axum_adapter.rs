@@ -33,12 +33,15 @@ pub async fn connect<T>(
     let (event_tx, event_rx) = mpsc::unbounded_channel();
     let (edits_tx, edits_rx) = mpsc::unbounded_channel();
     let mut edits_rx = UnboundedReceiverStream::new(edits_rx);
-    let mut event_rx = UnboundedReceiverStream::new(event_rx);
+    let mut event_rx = events::vdom_messages(UnboundedReceiverStream::new(event_rx));
     let vdom_fut = pool.clone().spawn_pinned(move || async move {
         let mut vdom = VirtualDom::new_with_props(app, props);
+        vdom.enable_templates();
         let edits = vdom.rebuild();
         let serialized = serde_json::to_string(&edits.edits).unwrap();
         edits_tx.send(serialized).unwrap();
+        // batches sent to the client that it hasn't acknowledged yet
+        let mut in_flight = 1;
         loop {
             let new_event = {
                 let vdom_fut = vdom.wait_for_work();
@@ -48,13 +51,32 @@ pub async fn connect<T>(
                     Either::Right((_, _)) => None,
                 }
             };
-            if let Some(new_event) = new_event {
-                vdom.handle_message(dioxus_core::SchedulerMsg::Event(new_event));
-            } else {
-                let mutations = vdom.work_with_deadline(|| false);
-                for mutation in mutations {
-                    let edits = serde_json::to_string(&mutation.edits).unwrap();
-                    edits_tx.send(edits).unwrap();
+            match new_event {
+                Some(events::VdomMessage::Event(new_event)) => {
+                    vdom.handle_message(dioxus_core::SchedulerMsg::Event(new_event));
+                }
+                // effects wait until the client has caught up with every batch
+                Some(events::VdomMessage::EditsApplied) => {
+                    in_flight -= 1;
+                    if in_flight == 0 {
+                        vdom.mutations_applied();
+                    }
+                }
+                #[cfg(feature = "hot-reload")]
+                Some(events::VdomMessage::Template(template)) => {
+                    for mutation in vdom.replace_template(template) {
+                        let edits = serde_json::to_string(&mutation.edits).unwrap();
+                        edits_tx.send(edits).unwrap();
+                        in_flight += 1;
+                    }
+                }
+                None => {
+                    let mutations = vdom.work_with_deadline(|| false);
+                    for mutation in mutations {
+                        let edits = serde_json::to_string(&mutation.edits).unwrap();
+                        edits_tx.send(edits).unwrap();
+                        in_flight += 1;
+                    }
                 }
             }
         }
@@ -64,8 +86,9 @@ pub async fn connect<T>(
             Either::Left((l, _)) => {
                 if let Some(Ok(msg)) = l {
                     if let Ok(Some(msg)) = msg.to_text().map(events::parse_ipc_message) {
-                        let user_event = events::trigger_from_serialized(msg.params);
-                        event_tx.send(user_event).unwrap();
+                        if let Some(message) = events::client_message(msg) {
+                            event_tx.send(message).unwrap();
+                        }
                     } else {
                         break;
                     }
@@ -39,16 +39,20 @@ pub async fn connect<T>(
     let (edits_tx, edits_rx) = mpsc::unbounded_channel();
 
     let mut edits_rx = UnboundedReceiverStream::new(edits_rx);
-    let mut event_rx = UnboundedReceiverStream::new(event_rx);
+    let mut event_rx = events::vdom_messages(UnboundedReceiverStream::new(event_rx));
 
     let vdom_fut = pool.spawn_pinned(move || async move {
         let mut vdom = VirtualDom::new_with_props(app, props);
+        vdom.enable_templates();
 
         let edits = vdom.rebuild();
 
         let serialized = serde_json::to_string(&edits.edits).unwrap();
         edits_tx.send(serialized).unwrap();
 
+        // batches sent to the client that it hasn't acknowledged yet
+        let mut in_flight = 1;
+
         loop {
             use futures_util::future::{select, Either};
 
@@ -63,13 +67,32 @@ pub async fn connect<T>(
                 }
             };
 
-            if let Some(new_event) = new_event {
-                vdom.handle_message(dioxus_core::SchedulerMsg::Event(new_event));
-            } else {
-                let mutations = vdom.work_with_deadline(|| false);
-                for mutation in mutations {
-                    let edits = serde_json::to_string(&mutation.edits).unwrap();
-                    edits_tx.send(edits).unwrap();
+            match new_event {
+                Some(events::VdomMessage::Event(new_event)) => {
+                    vdom.handle_message(dioxus_core::SchedulerMsg::Event(new_event));
+                }
+                // effects wait until the client has caught up with every batch
+                Some(events::VdomMessage::EditsApplied) => {
+                    in_flight -= 1;
+                    if in_flight == 0 {
+                        vdom.mutations_applied();
+                    }
+                }
+                #[cfg(feature = "hot-reload")]
+                Some(events::VdomMessage::Template(template)) => {
+                    for mutation in vdom.replace_template(template) {
+                        let edits = serde_json::to_string(&mutation.edits).unwrap();
+                        edits_tx.send(edits).unwrap();
+                        in_flight += 1;
+                    }
+                }
+                None => {
+                    let mutations = vdom.work_with_deadline(|| false);
+                    for mutation in mutations {
+                        let edits = serde_json::to_string(&mutation.edits).unwrap();
+                        edits_tx.send(edits).unwrap();
+                        in_flight += 1;
+                    }
                 }
             }
         }
@@ -82,9 +105,8 @@ pub async fn connect<T>(
             Either::Left((l, _)) => {
                 if let Some(Ok(msg)) = l {
                     if let Ok(Some(msg)) = msg.to_str().map(events::parse_ipc_message) {
-                        if msg.method == "user_event" {
-                            let user_event = events::trigger_from_serialized(msg.params);
-                            event_tx.send(user_event).unwrap();
+                        if let Some(message) = events::client_message(msg) {
+                            event_tx.send(message).unwrap();
                         }
                     } else {
                         break;
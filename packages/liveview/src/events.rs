@@ -203,3 +203,41 @@ fn event_name_from_type(typ: &str) -> &'static str {
         }
     }
 }
+
+/// A message for the VirtualDom of a connection
+pub(crate) enum VdomMessage {
+    Event(UserEvent),
+
+    /// The client applied a batch of edits
+    EditsApplied,
+
+    /// A new template for an `rsx!` call, sent by the hot reloader
+    #[cfg(feature = "hot-reload")]
+    Template(dioxus_core::RsxTemplate),
+}
+
+/// Turn a message from the client into a message for its VirtualDom
+pub(crate) fn client_message(message: IpcMessage) -> Option<VdomMessage> {
+    match message.method.as_str() {
+        "user_event" => Some(VdomMessage::Event(trigger_from_serialized(message.params))),
+        "edits_applied" => Some(VdomMessage::EditsApplied),
+        _ => None,
+    }
+}
+
+/// Merge the messages from the client with the templates from the hot reloader
+pub(crate) fn vdom_messages(
+    messages: impl futures_util::Stream<Item = VdomMessage> + Unpin,
+) -> impl futures_util::Stream<Item = VdomMessage> + Unpin {
+    #[cfg(feature = "hot-reload")]
+    let messages = {
+        use futures_util::StreamExt;
+
+        futures_util::stream::select(
+            messages,
+            dioxus_rsx::hot_reload::subscribe().map(VdomMessage::Template),
+        )
+    };
+
+    messages
+}
@@ -27,6 +27,8 @@ class IPC {
     this.ws.onmessage = (event) => {
       let edits = JSON.parse(event.data);
       window.interpreter.handleEdits(edits);
+      // the server runs its effects once every batch it sent has been applied
+      this.send(serializeIpcMessage("edits_applied"));
     };
   }
 
@@ -43,6 +45,9 @@ class Interpreter {
     this.handlers = {};
     this.lastNodeWasText = false;
     this.nodes = [root];
+    this.templates = [];
+    this.portalRoots = [];
+    this.attributeTypes = {};
   }
   top() {
     return this.stack[this.stack.length - 1];
@@ -54,6 +59,26 @@ class Interpreter {
     const node = this.nodes[root];
     this.stack.push(node);
   }
+  PopRoot() {
+    this.stack.pop();
+  }
+  PushNamedRoot(name) {
+    let root = document.getElementById(name);
+    if (root === null) {
+      root = document.createElement("div");
+      root.id = name;
+      document.body.appendChild(root);
+    }
+    // events are delegated to the main root, so portals outside of it need the same listeners
+    if (!this.root.contains(root) && !this.portalRoots.includes(root)) {
+      root.setAttribute("data-dioxus-portal", name);
+      for (const event_name in this.handlers) {
+        root.addEventListener(event_name, this.handlers[event_name]);
+      }
+      this.portalRoots.push(root);
+    }
+    this.stack.push(root);
+  }
   AppendChildren(many) {
     let root = this.stack[this.stack.length - (1 + many)];
     let to_add = this.stack.splice(this.stack.length - many);
@@ -91,11 +116,13 @@ class Interpreter {
   CreateElement(tag, root) {
     const el = document.createElement(tag);
     // el.setAttribute("data-dioxus-id", `${root}`);
+    el.dioxusId = root;
     this.nodes[root] = el;
     this.stack.push(el);
   }
   CreateElementNs(tag, root, ns) {
     let el = document.createElementNS(ns, tag);
+    el.dioxusId = root;
     this.stack.push(el);
     this.nodes[root] = el;
   }
@@ -105,6 +132,15 @@ class Interpreter {
     this.stack.push(el);
     this.nodes[root] = el;
   }
+  SaveTemplate(id) {
+    this.templates[id] = this.stack.pop();
+  }
+  CloneTemplate(id, root) {
+    const node = this.templates[id].cloneNode(true);
+    node.dioxusId = root;
+    this.nodes[root] = node;
+    this.stack.push(node);
+  }
   NewEventListener(event_name, root, handler) {
     const element = this.nodes[root];
     element.setAttribute("data-dioxus-id", `${root}`);
@@ -112,6 +148,9 @@ class Interpreter {
       this.listeners[event_name] = 0;
       this.handlers[event_name] = handler;
       this.root.addEventListener(event_name, handler);
+      for (const portal of this.portalRoots) {
+        portal.addEventListener(event_name, handler);
+      }
     } else {
       this.listeners[event_name]++;
     }
@@ -122,6 +161,9 @@ class Interpreter {
     this.listeners[event_name]--;
     if (this.listeners[event_name] === 0) {
       this.root.removeEventListener(event_name, this.handlers[event_name]);
+      for (const portal of this.portalRoots) {
+        portal.removeEventListener(event_name, this.handlers[event_name]);
+      }
       delete this.listeners[event_name];
       delete this.handlers[event_name];
     }
@@ -129,9 +171,19 @@ class Interpreter {
   SetText(root, text) {
     this.nodes[root].textContent = text;
   }
+  // Register a function that turns the payload of a custom attribute value with the given name back into a value
+  registerAttributeType(name, decode) {
+    this.attributeTypes[name] = decode;
+  }
   SetAttribute(root, field, value, ns) {
     const name = field;
     const node = this.nodes[root];
+    if (isCustomValue(value)) {
+      // custom values aren't strings once they're decoded, so they're set as properties
+      const decode = this.attributeTypes[value.name];
+      node[name] = decode ? decode(value.payload) : value.payload;
+      return;
+    }
     if (ns === "style") {
       // @ts-ignore
       node.style[name] = value;
@@ -189,6 +241,12 @@ class Interpreter {
       case "PushRoot":
         this.PushRoot(edit.root);
         break;
+      case "PopRoot":
+        this.PopRoot();
+        break;
+      case "PushNamedRoot":
+        this.PushNamedRoot(edit.name);
+        break;
       case "AppendChildren":
         this.AppendChildren(edit.many);
         break;
@@ -256,6 +314,15 @@ class Interpreter {
                 return;
               }
 
+              // events from inside a portal bubble up through where the portal was rendered, not its mount point
+              if (
+                target.parentElement.hasAttribute(`data-dioxus-portal`) &&
+                target.dioxusId !== undefined
+              ) {
+                realId = `${target.dioxusId}`;
+                break;
+              }
+
               target = target.parentElement;
               realId = target.getAttribute(`data-dioxus-id`);
             }
@@ -313,6 +380,12 @@ class Interpreter {
       case "RemoveAttribute":
         this.RemoveAttribute(edit.root, edit.name);
         break;
+      case "SaveTemplate":
+        this.SaveTemplate(edit.id);
+        break;
+      case "CloneTemplate":
+        this.CloneTemplate(edit.id, edit.root);
+        break;
     }
   }
 }
@@ -583,6 +656,14 @@ function serialize_event(event) {
     }
   }
 }
+function isCustomValue(value) {
+  return (
+    value !== null &&
+    typeof value === "object" &&
+    typeof value.name === "string" &&
+    typeof value.payload === "string"
+  );
+}
 function serializeIpcMessage(method, params = {}) {
   return JSON.stringify({ method, params });
 }
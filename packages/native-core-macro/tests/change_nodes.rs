@@ -2,7 +2,7 @@ use dioxus_core::VNode;
 use dioxus_core::*;
 use dioxus_core_macro::*;
 use dioxus_html as dioxus_elements;
-use dioxus_native_core::real_dom::RealDom;
+use dioxus_native_core::real_dom::{RealDom, UnsupportedEdit};
 use dioxus_native_core::state::State;
 use dioxus_native_core_macro::State;
 use std::cell::Cell;
@@ -27,10 +27,11 @@ fn remove_node() {
 
     let mut dom: RealDom<Empty> = RealDom::new();
 
-    let _to_update = dom.apply_mutations(vec![mutations]);
+    let _to_update = dom.apply_mutations(vec![mutations]).unwrap();
     let child_div = VElement {
         id: Cell::new(Some(ElementId(2))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(1))),
@@ -42,6 +43,7 @@ fn remove_node() {
     let root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -66,11 +68,12 @@ fn remove_node() {
             div{}
         },
     );
-    dom.apply_mutations(vec![mutations.1]);
+    dom.apply_mutations(vec![mutations.1]).unwrap();
 
     let new_root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -99,11 +102,12 @@ fn add_node() {
 
     let mut dom: RealDom<Empty> = RealDom::new();
 
-    let _to_update = dom.apply_mutations(vec![mutations]);
+    let _to_update = dom.apply_mutations(vec![mutations]).unwrap();
 
     let root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -127,11 +131,12 @@ fn add_node() {
             }
         },
     );
-    dom.apply_mutations(vec![mutations.1]);
+    dom.apply_mutations(vec![mutations.1]).unwrap();
 
     let child_div = VElement {
         id: Cell::new(Some(ElementId(2))),
         key: None,
+        node_ref: None,
         tag: "p",
         namespace: None,
         parent: Cell::new(Some(ElementId(1))),
@@ -143,6 +148,7 @@ fn add_node() {
     let new_root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -156,3 +162,24 @@ fn add_node() {
     assert_eq!(dom[1].height, 1);
     assert_eq!(dom[2].height, 2);
 }
+
+#[test]
+fn template_edits_are_rejected() {
+    let mut dom: RealDom<Empty> = RealDom::new();
+
+    let result = dom.apply_mutations(vec![Mutations {
+        edits: vec![
+            DomEdit::CreateElement {
+                root: 1,
+                tag: "div",
+            },
+            DomEdit::SaveTemplate { id: 0 },
+        ],
+        dirty_scopes: fxhash::FxHashSet::default(),
+        refs: Vec::new(),
+    }]);
+
+    // none of the batch is applied
+    assert_eq!(result.unwrap_err(), UnsupportedEdit::Template);
+    assert_eq!(dom.size(), 0);
+}
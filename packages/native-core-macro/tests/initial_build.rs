@@ -28,10 +28,11 @@ fn initial_build_simple() {
 
     let mut dom: RealDom<Empty> = RealDom::new();
 
-    let _to_update = dom.apply_mutations(vec![mutations]);
+    let _to_update = dom.apply_mutations(vec![mutations]).unwrap();
     let root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -67,7 +68,7 @@ fn initial_build_with_children() {
 
     let mut dom: RealDom<Empty> = RealDom::new();
 
-    let _to_update = dom.apply_mutations(vec![mutations]);
+    let _to_update = dom.apply_mutations(vec![mutations]).unwrap();
     let first_text = VText {
         id: Cell::new(Some(ElementId(3))),
         text: "hello",
@@ -83,6 +84,7 @@ fn initial_build_with_children() {
     let child_p_el = VElement {
         id: Cell::new(Some(ElementId(4))),
         key: None,
+        node_ref: None,
         tag: "p",
         namespace: None,
         parent: Cell::new(Some(ElementId(2))),
@@ -100,6 +102,7 @@ fn initial_build_with_children() {
     let child_div_el = VElement {
         id: Cell::new(Some(ElementId(2))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(1))),
@@ -111,6 +114,7 @@ fn initial_build_with_children() {
     let root_div = VElement {
         id: Cell::new(Some(ElementId(1))),
         key: None,
+        node_ref: None,
         tag: "div",
         namespace: None,
         parent: Cell::new(Some(ElementId(0))),
@@ -183,7 +183,7 @@ fn state_initial() {
 
     let mut dom: RealDom<StateTester> = RealDom::new();
 
-    let nodes_updated = dom.apply_mutations(vec![mutations]);
+    let nodes_updated = dom.apply_mutations(vec![mutations]).unwrap();
     let mut ctx = AnyMap::new();
     ctx.insert(42u32);
     let _to_rerender = dom.update_state(&vdom, nodes_updated, ctx);
@@ -282,7 +282,7 @@ fn state_reduce_initally_called_minimally() {
 
     let mut dom: RealDom<CallCounterState> = RealDom::new();
 
-    let nodes_updated = dom.apply_mutations(vec![mutations]);
+    let nodes_updated = dom.apply_mutations(vec![mutations]).unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
 
     dom.traverse_depth_first(|n| {
@@ -342,18 +342,20 @@ fn state_reduce_parent_called_minimally_on_update() {
 
     let mut dom: RealDom<CallCounterState> = RealDom::new();
 
-    let nodes_updated = dom.apply_mutations(vec![mutations]);
+    let nodes_updated = dom.apply_mutations(vec![mutations]).unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
-    let nodes_updated = dom.apply_mutations(vec![Mutations {
-        edits: vec![DomEdit::SetAttribute {
-            root: 1,
-            field: "width",
-            value: AttributeValue::Text("99%"),
-            ns: Some("style"),
-        }],
-        dirty_scopes: fxhash::FxHashSet::default(),
-        refs: Vec::new(),
-    }]);
+    let nodes_updated = dom
+        .apply_mutations(vec![Mutations {
+            edits: vec![DomEdit::SetAttribute {
+                root: 1,
+                field: "width",
+                value: AttributeValue::Text("99%"),
+                ns: Some("style"),
+            }],
+            dirty_scopes: fxhash::FxHashSet::default(),
+            refs: Vec::new(),
+        }])
+        .unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
 
     dom.traverse_depth_first(|n| {
@@ -411,18 +413,20 @@ fn state_reduce_child_called_minimally_on_update() {
 
     let mut dom: RealDom<CallCounterState> = RealDom::new();
 
-    let nodes_updated = dom.apply_mutations(vec![mutations]);
+    let nodes_updated = dom.apply_mutations(vec![mutations]).unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
-    let nodes_updated = dom.apply_mutations(vec![Mutations {
-        edits: vec![DomEdit::SetAttribute {
-            root: 4,
-            field: "width",
-            value: AttributeValue::Text("99%"),
-            ns: Some("style"),
-        }],
-        dirty_scopes: fxhash::FxHashSet::default(),
-        refs: Vec::new(),
-    }]);
+    let nodes_updated = dom
+        .apply_mutations(vec![Mutations {
+            edits: vec![DomEdit::SetAttribute {
+                root: 4,
+                field: "width",
+                value: AttributeValue::Text("99%"),
+                ns: Some("style"),
+            }],
+            dirty_scopes: fxhash::FxHashSet::default(),
+            refs: Vec::new(),
+        }])
+        .unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
 
     dom.traverse_depth_first(|n| {
@@ -508,7 +512,7 @@ fn dependancies_order_independant() {
 
     let mut dom: RealDom<UnorderedDependanciesState> = RealDom::new();
 
-    let nodes_updated = dom.apply_mutations(vec![mutations]);
+    let nodes_updated = dom.apply_mutations(vec![mutations]).unwrap();
     let _to_rerender = dom.update_state(&vdom, nodes_updated, AnyMap::new());
 
     let c = CDepCallCounter(1);
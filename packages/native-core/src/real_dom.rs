@@ -5,7 +5,7 @@ use std::{
     ops::{Index, IndexMut},
 };
 
-use dioxus_core::{ElementId, Mutations, VNode, VirtualDom};
+use dioxus_core::{AttributeValue, ElementId, Mutations, VNode, VirtualDom};
 
 use crate::state::{union_ordered_iter, State};
 use crate::{
@@ -22,8 +22,30 @@ pub struct RealDom<S: State> {
     nodes: Vec<Option<Node<S>>>,
     nodes_listening: FxHashMap<&'static str, FxHashSet<usize>>,
     node_stack: smallvec::SmallVec<[usize; 10]>,
+    /// The nodes portals can render into, by their `id` attribute.
+    named_roots: FxHashMap<String, usize>,
 }
 
+/// An edit [RealDom::apply_mutations] can't apply.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsupportedEdit {
+    /// The nodes inside a template don't have ids, so they can't be tracked by the dom.
+    /// Renderers built on [RealDom] should leave [VirtualDom::enable_templates] off.
+    Template,
+}
+
+impl std::fmt::Display for UnsupportedEdit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnsupportedEdit::Template => {
+                write!(f, "templates are not supported by the native renderer")
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnsupportedEdit {}
+
 impl<S: State> Default for RealDom<S> {
     fn default() -> Self {
         Self::new()
@@ -47,11 +69,27 @@ impl<S: State> RealDom<S> {
             },
             nodes_listening: FxHashMap::default(),
             node_stack: smallvec::SmallVec::new(),
+            named_roots: FxHashMap::default(),
         }
     }
 
     /// Updates the dom, up and down state and return a set of nodes that were updated pass this to update_state.
-    pub fn apply_mutations(&mut self, mutations_vec: Vec<Mutations>) -> Vec<(usize, NodeMask)> {
+    ///
+    /// Returns an error without applying any edits if the mutations contain edits the dom can't track.
+    pub fn apply_mutations(
+        &mut self,
+        mutations_vec: Vec<Mutations>,
+    ) -> Result<Vec<(usize, NodeMask)>, UnsupportedEdit> {
+        // check everything up front so a rejected batch doesn't leave the dom half updated
+        for mutations in &mutations_vec {
+            for e in &mutations.edits {
+                use dioxus_core::DomEdit::*;
+                if let SaveTemplate { .. } | CloneTemplate { .. } = e {
+                    return Err(UnsupportedEdit::Template);
+                }
+            }
+        }
+
         let mut nodes_updated = Vec::new();
         for mutations in mutations_vec {
             for e in mutations.edits {
@@ -179,7 +217,13 @@ impl<S: State> RealDom<S> {
                             _ => unreachable!(),
                         }
                     }
-                    SetAttribute { root, field, .. } => {
+                    SetAttribute {
+                        root, field, value, ..
+                    } => {
+                        if let ("id", AttributeValue::Text(name)) = (field, value) {
+                            self.named_roots.retain(|_, id| *id != root as usize);
+                            self.named_roots.insert(name.to_string(), root as usize);
+                        }
                         nodes_updated.push((
                             root as usize,
                             NodeMask::new_with_attrs(AttributeMask::single(field)),
@@ -188,6 +232,9 @@ impl<S: State> RealDom<S> {
                     RemoveAttribute {
                         root, name: field, ..
                     } => {
+                        if field == "id" {
+                            self.named_roots.retain(|_, id| *id != root as usize);
+                        }
                         nodes_updated.push((
                             root as usize,
                             NodeMask::new_with_attrs(AttributeMask::single(field)),
@@ -196,11 +243,21 @@ impl<S: State> RealDom<S> {
                     PopRoot {} => {
                         self.node_stack.pop();
                     }
+                    PushNamedRoot { name } => {
+                        // portals into a node that doesn't exist are appended to the root instead
+                        let target = match self.named_roots.get(name) {
+                            Some(&id) if matches!(self.nodes.get(id), Some(Some(_))) => id,
+                            _ => self.root,
+                        };
+                        self.node_stack.push(target);
+                    }
+                    // rejected before any edits were applied
+                    SaveTemplate { .. } | CloneTemplate { .. } => unreachable!(),
                 }
             }
         }
 
-        nodes_updated
+        Ok(nodes_updated)
     }
 
     /// Seperated from apply_mutations because Mutations require a mutable reference to the VirtualDom.
@@ -621,7 +678,10 @@ impl<S: State> RealDom<S> {
                 }
             }
             VNode::Fragment(f) => f.children.iter().all(|c| self.contains_node(c)),
+            VNode::Portal(p) => p.children.iter().all(|c| self.contains_node(c)),
             VNode::Placeholder(_) => true,
+            // templates are never applied to the dom, see [UnsupportedEdit]
+            VNode::Template(_) => false,
             VNode::Text(t) => {
                 if let Some(id) = t.id.get() {
                     let dom_node = &self[id];
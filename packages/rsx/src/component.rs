@@ -27,10 +27,14 @@ pub struct Component {
     pub body: Vec<ComponentField>,
     pub children: Vec<BodyNode>,
     pub manual_props: Option<Expr>,
+
+    /// The tokens the component was parsed from, which identify it when hot reloading
+    pub source: TokenStream2,
 }
 
 impl Parse for Component {
     fn parse(stream: ParseStream) -> Result<Self> {
+        let start = stream.cursor();
         let name = syn::Path::parse_mod_style(stream)?;
 
         let content: ParseBuffer;
@@ -68,6 +72,7 @@ impl Parse for Component {
             body,
             children,
             manual_props,
+            source: tokens_between(start, stream.cursor()),
         })
     }
 }
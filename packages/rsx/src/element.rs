@@ -13,9 +13,10 @@ use syn::{
 pub struct Element {
     pub name: Ident,
     pub key: Option<LitStr>,
+    pub node_ref: Option<Expr>,
     pub attributes: Vec<ElementAttrNamed>,
     pub children: Vec<BodyNode>,
-    pub _is_static: bool,
+    pub is_static: bool,
 }
 
 impl Parse for Element {
@@ -29,7 +30,7 @@ impl Parse for Element {
         let mut attributes: Vec<ElementAttrNamed> = vec![];
         let mut children: Vec<BodyNode> = vec![];
         let mut key = None;
-        let mut _el_ref = None;
+        let mut el_ref = None;
 
         // parse fields with commas
         // break when we don't get this pattern anymore
@@ -64,7 +65,7 @@ impl Parse for Element {
                 }
 
                 if content.parse::<Token![,]>().is_err() {
-                    missing_trailing_comma!(ident);
+                    missing_trailing_comma!(ident.span());
                 }
                 continue;
             }
@@ -92,7 +93,7 @@ impl Parse for Element {
                         "classes" => todo!("custom class list not supported yet"),
                         // "namespace" => todo!("custom namespace not supported yet"),
                         "node_ref" => {
-                            _el_ref = Some(content.parse::<Expr>()?);
+                            el_ref = Some(content.parse::<Expr>()?);
                         }
                         _ => {
                             if content.peek(LitStr) {
@@ -122,7 +123,7 @@ impl Parse for Element {
 
                 // todo: add a message saying you need to include commas between fields
                 if content.parse::<Token![,]>().is_err() {
-                    missing_trailing_comma!(ident);
+                    missing_trailing_comma!(ident.span());
                 }
                 continue;
             }
@@ -147,20 +148,41 @@ impl Parse for Element {
             }
         }
 
+        // an element is static if it's made of nothing but literals, so it can be hoisted into a template
+        let is_static = key.is_none()
+            && el_ref.is_none()
+            && attributes.iter().all(|attr| match &attr.attr {
+                ElementAttr::AttrText { value, .. } | ElementAttr::CustomAttrText { value, .. } => {
+                    is_literal(value)
+                }
+                _ => false,
+            })
+            && children.iter().all(|child| match child {
+                BodyNode::Element(el) => el.is_static,
+                BodyNode::Text(text) => is_literal(text),
+                _ => false,
+            });
+
         Ok(Self {
             key,
+            node_ref: el_ref,
             name: el_name,
             attributes,
             children,
-            _is_static: false,
+            is_static,
         })
     }
 }
 
-impl ToTokens for Element {
-    fn to_tokens(&self, tokens: &mut TokenStream2) {
+/// Check if a string doesn't need any formatting
+fn is_literal(text: &LitStr) -> bool {
+    !text.value().contains(&['{', '}'][..])
+}
+
+impl Element {
+    /// Write out the element as a regular [`VNode::Element`], without hoisting it into a template
+    fn to_element_tokens(&self, tokens: &mut TokenStream2) {
         let name = &self.name;
-        let children = &self.children;
 
         let key = match &self.key {
             Some(ty) => quote! { Some(format_args_f!(#ty)) },
@@ -177,14 +199,52 @@ impl ToTokens for Element {
             .iter()
             .filter(|f| !matches!(f.attr, ElementAttr::EventTokens { .. }));
 
+        // the children of a static element are static too, so they belong to the same template
+        let children = self.children.iter().map(|child| match child {
+            BodyNode::Element(el) if self.is_static => {
+                let mut tokens = TokenStream2::new();
+                el.to_element_tokens(&mut tokens);
+                tokens
+            }
+            child => child.to_token_stream(),
+        });
+
+        match &self.node_ref {
+            Some(node_ref) => tokens.append_all(quote! {
+                __cx.element_with_ref(
+                    dioxus_elements::#name,
+                    __cx.bump().alloc([ #(#listeners),* ]),
+                    __cx.bump().alloc([ #(#attr),* ]),
+                    __cx.bump().alloc([ #(#children),* ]),
+                    #key,
+                    #node_ref,
+                )
+            }),
+            None => tokens.append_all(quote! {
+                __cx.element(
+                    dioxus_elements::#name,
+                    __cx.bump().alloc([ #(#listeners),* ]),
+                    __cx.bump().alloc([ #(#attr),* ]),
+                    __cx.bump().alloc([ #(#children),* ]),
+                    #key,
+                )
+            }),
+        }
+    }
+}
+
+impl ToTokens for Element {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        // an empty element is cheaper to create than to clone
+        if !self.is_static || (self.attributes.is_empty() && self.children.is_empty()) {
+            return self.to_element_tokens(tokens);
+        }
+
+        let mut element = TokenStream2::new();
+        self.to_element_tokens(&mut element);
+
         tokens.append_all(quote! {
-            __cx.element(
-                dioxus_elements::#name,
-                __cx.bump().alloc([ #(#listeners),* ]),
-                __cx.bump().alloc([ #(#attr),* ]),
-                __cx.bump().alloc([ #(#children),* ]),
-                #key,
-            )
+            __cx.template(|__cx| #element)
         });
     }
 }
@@ -0,0 +1,17 @@
+// These return from the parser instead of emitting through proc-macro-error, so the hot reloader can use the parser
+// outside of a macro
+
+macro_rules! missing_trailing_comma {
+    ($span:expr) => {
+        return Err(syn::Error::new($span, "missing trailing comma"))
+    };
+}
+
+macro_rules! attr_after_element {
+    ($span:expr) => {
+        return Err(syn::Error::new(
+            $span,
+            "expected element\n\nhelp: move the attribute above all the children and text elements",
+        ))
+    };
+}
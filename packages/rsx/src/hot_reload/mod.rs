@@ -0,0 +1,378 @@
+//! Hot reloading `rsx!` calls
+//! ==========================
+//!
+//! With the `hot-reload` feature of `dioxus-core-macro`, `rsx!` generates code with [`CallBody::to_hot_reload_tokens`]
+//! instead of [`ToTokens`]. Along with the compiled nodes, the call records:
+//! - its location, from the position of its first token
+//! - the source code of its dynamic parts (components, expressions, attribute expressions, and event listeners),
+//!   which identifies them in a template
+//! - the values of the arguments of its formatted strings, like `name` in `"hello {name}"`
+//!
+//! The watcher in this module (enabled with the `hot-reload` feature of this crate) parses the source files of the app
+//! the same way. When a file changes, every `rsx!` call in it is matched up with the compiled call at the same index
+//! and the calls that changed are sent to the app as templates. Changes to code outside of `rsx!`, or to the number of
+//! calls in a file, need a rebuild.
+//!
+//! Locations come from the line and column of tokens, which are only available to proc macros since Rust 1.88. On older
+//! compilers `rsx!` generates the regular code.
+
+#[cfg(feature = "hot-reload")]
+mod watcher;
+#[cfg(feature = "hot-reload")]
+pub use watcher::*;
+
+use super::*;
+use proc_macro2::{Delimiter, Group, Spacing, Span, TokenTree};
+use syn::{ext::IdentExt, Expr, LitStr};
+
+/// The line and column of an `rsx!` call: the start of its first token, or the start of the call if it's empty.
+///
+/// Returns `None` if the compiler doesn't provide locations.
+pub fn call_location(body: &TokenStream2, call_site: Span) -> Option<(u32, u32)> {
+    let span = body
+        .clone()
+        .into_iter()
+        .next()
+        .map(|tt| tt.span())
+        .unwrap_or(call_site);
+
+    let start = span.start();
+    match start.line {
+        0 => None,
+        line => Some((line as u32, start.column as u32)),
+    }
+}
+
+/// Empty out every `rsx!` call in the tokens.
+///
+/// The code around an `rsx!` call, like an iterator that maps items to `rsx!`, shouldn't change when only the call
+/// does. Nested calls are hot reloaded on their own.
+pub fn strip_rsx_calls(tokens: TokenStream2) -> TokenStream2 {
+    let mut out = TokenStream2::new();
+    visit_rsx_calls(tokens, &mut out, &mut |_, _| {});
+    out
+}
+
+/// Copy the tokens to `out` with the bodies of `rsx!` calls emptied out, calling `f` with the `rsx` ident and the body
+/// of each call in the order they appear.
+pub(crate) fn visit_rsx_calls(
+    tokens: TokenStream2,
+    out: &mut TokenStream2,
+    f: &mut impl FnMut(&Ident, TokenStream2),
+) {
+    let mut prev: Vec<TokenTree> = Vec::with_capacity(2);
+
+    for tt in tokens {
+        match tt {
+            TokenTree::Group(group) => {
+                let is_call = matches!(
+                    &prev[..],
+                    [TokenTree::Ident(ident), TokenTree::Punct(bang)] if ident == "rsx" && bang.as_char() == '!'
+                );
+
+                let stream = match is_call {
+                    true => {
+                        if let TokenTree::Ident(ident) = &prev[0] {
+                            f(ident, group.stream());
+                        }
+                        // visit the nested calls, but leave them out of the stripped tokens
+                        visit_rsx_calls(group.stream(), &mut TokenStream2::new(), f);
+                        TokenStream2::new()
+                    }
+                    false => {
+                        let mut inner = TokenStream2::new();
+                        visit_rsx_calls(group.stream(), &mut inner, f);
+                        inner
+                    }
+                };
+
+                let mut stripped = Group::new(group.delimiter(), stream);
+                stripped.set_span(group.span());
+                out.append(stripped);
+                prev.clear();
+            }
+            tt => {
+                if prev.len() == 2 {
+                    prev.remove(0);
+                }
+                prev.push(tt.clone());
+                out.append(tt);
+            }
+        }
+    }
+}
+
+/// Identify some code in a template by its tokens, with the bodies of `rsx!` calls emptied out
+pub(crate) fn code_key(tokens: TokenStream2) -> String {
+    print_tokens(strip_rsx_calls(tokens))
+}
+
+/// Print tokens the same way inside and outside of proc macros.
+///
+/// `TokenStream::to_string` is implemented by the compiler inside of proc macros and by `proc_macro2` outside of them,
+/// and they don't agree on the spacing, ie `Child {}` and `Child { }`.
+pub fn print_tokens(tokens: TokenStream2) -> String {
+    let mut out = String::new();
+    write_tokens(tokens, &mut out);
+    out
+}
+
+fn write_tokens(tokens: TokenStream2, out: &mut String) {
+    let mut joint = true;
+    for tt in tokens {
+        if !joint {
+            out.push(' ');
+        }
+        joint = false;
+
+        match tt {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                write_tokens(group.stream(), out);
+                out.push_str(close);
+            }
+            TokenTree::Punct(punct) => {
+                out.push(punct.as_char());
+                joint = punct.spacing() == Spacing::Joint;
+            }
+            tt => out.push_str(&tt.to_string()),
+        }
+    }
+}
+
+/// The key of a component or an expression in a template
+pub(crate) fn node_key(node: &BodyNode) -> Option<String> {
+    match node {
+        BodyNode::Component(comp) => Some(code_key(comp.source.clone())),
+        BodyNode::RawExpr(expr) => Some(code_key(expr.to_token_stream())),
+        BodyNode::Element(_) | BodyNode::Text(_) => None,
+    }
+}
+
+/// The key of an attribute set to an expression or an event listener in a template
+pub(crate) fn attribute_key(attr: &ElementAttr) -> Option<String> {
+    let (name, value) = match attr {
+        ElementAttr::AttrExpression { name, value } => (ident_name(name), value.to_token_stream()),
+        ElementAttr::CustomAttrExpression { name, value } => {
+            (format!("{:?}", name.value()), value.to_token_stream())
+        }
+        ElementAttr::EventTokens { name, tokens } => (ident_name(name), tokens.to_token_stream()),
+        ElementAttr::AttrText { .. } | ElementAttr::CustomAttrText { .. } => return None,
+    };
+
+    Some(format!("{}: {}", name, code_key(value)))
+}
+
+/// The key of a `node_ref` in a template
+pub(crate) fn node_ref_key(node_ref: &Expr) -> String {
+    code_key(node_ref.to_token_stream())
+}
+
+/// The name of an element or an attribute, without the `r#` of raw identifiers
+pub(crate) fn ident_name(ident: &Ident) -> String {
+    ident.unraw().to_string()
+}
+
+/// The arguments of a formatted string, like `name` and `count:?` in `"{name}: {count:?}"`.
+///
+/// Returns `None` if the string isn't a valid format string.
+pub(crate) fn format_arguments(text: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' | '}' if matches!(chars.peek(), Some((_, next)) if *next == c) => {
+                chars.next();
+            }
+            '{' => {
+                let (end, _) = chars.find(|(_, c)| *c == '}')?;
+                args.push(text[idx + 1..end].trim().to_string());
+            }
+            '}' => return None,
+            _ => {}
+        }
+    }
+
+    Some(args)
+}
+
+/// Everything in a call that a template can refer to
+#[derive(Default)]
+pub(crate) struct DynamicParts<'a> {
+    pub elements: Vec<&'a Ident>,
+    pub attribute_names: Vec<(&'a Ident, &'a Ident)>,
+    pub formatted: Vec<String>,
+    pub nodes: Vec<(String, &'a BodyNode)>,
+    pub attributes: Vec<(String, &'a ElementAttrNamed)>,
+    pub listeners: Vec<(String, &'a ElementAttrNamed)>,
+    pub node_refs: Vec<(String, &'a Expr)>,
+}
+
+impl<'a> DynamicParts<'a> {
+    pub fn new(body: &'a CallBody) -> Self {
+        let mut parts = Self::default();
+        for root in &body.roots {
+            parts.visit(root);
+        }
+        parts
+    }
+
+    fn visit(&mut self, node: &'a BodyNode) {
+        match node {
+            BodyNode::Text(text) => self.visit_text(text),
+            BodyNode::Component(_) | BodyNode::RawExpr(_) => {
+                self.nodes.extend(node_key(node).map(|key| (key, node)));
+            }
+            BodyNode::Element(el) => {
+                if !self.elements.iter().any(|name| **name == el.name) {
+                    self.elements.push(&el.name);
+                }
+
+                if let Some(key) = &el.key {
+                    self.visit_text(key);
+                }
+
+                if let Some(node_ref) = &el.node_ref {
+                    self.node_refs.push((node_ref_key(node_ref), node_ref));
+                }
+
+                for attr in &el.attributes {
+                    match &attr.attr {
+                        ElementAttr::AttrText { name, value } => {
+                            self.visit_attribute_name(&el.name, name);
+                            self.visit_text(value);
+                        }
+                        ElementAttr::AttrExpression { name, .. } => {
+                            self.visit_attribute_name(&el.name, name);
+                            self.attributes
+                                .extend(attribute_key(&attr.attr).map(|key| (key, attr)));
+                        }
+                        ElementAttr::CustomAttrText { value, .. } => self.visit_text(value),
+                        ElementAttr::CustomAttrExpression { .. } => {
+                            self.attributes
+                                .extend(attribute_key(&attr.attr).map(|key| (key, attr)));
+                        }
+                        ElementAttr::EventTokens { .. } => {
+                            self.listeners
+                                .extend(attribute_key(&attr.attr).map(|key| (key, attr)));
+                        }
+                    }
+                }
+
+                for child in &el.children {
+                    self.visit(child);
+                }
+            }
+        }
+    }
+
+    fn visit_attribute_name(&mut self, el: &'a Ident, attr: &'a Ident) {
+        if !self
+            .attribute_names
+            .iter()
+            .any(|(e, a)| *e == el && *a == attr)
+        {
+            self.attribute_names.push((el, attr));
+        }
+    }
+
+    fn visit_text(&mut self, text: &LitStr) {
+        for arg in format_arguments(&text.value()).unwrap_or_default() {
+            if !self.formatted.contains(&arg) {
+                self.formatted.push(arg);
+            }
+        }
+    }
+}
+
+impl CallBody {
+    /// Generate code that can swap the static parts of the call for a template sent by the hot reloader.
+    ///
+    /// `location` comes from [`call_location`]. Without one, this generates the regular code.
+    pub fn to_hot_reload_tokens(&self, location: Option<(u32, u32)>) -> TokenStream2 {
+        let mut out_tokens = TokenStream2::new();
+        let (line, column) = match location {
+            Some(location) => location,
+            None => {
+                self.to_tokens(&mut out_tokens);
+                return out_tokens;
+            }
+        };
+
+        let parts = DynamicParts::new(self);
+        let inner = self.inner_tokens();
+
+        let keys = |keys: Vec<&String>| quote! { &[ #(#keys),* ] };
+        let node_keys = keys(parts.nodes.iter().map(|(key, _)| key).collect());
+        let attribute_keys = keys(parts.attributes.iter().map(|(key, _)| key).collect());
+        let listener_keys = keys(parts.listeners.iter().map(|(key, _)| key).collect());
+        let node_ref_keys = keys(parts.node_refs.iter().map(|(key, _)| key).collect());
+        let formatted_keys = keys(parts.formatted.iter().collect());
+
+        let elements = parts.elements.iter().map(|el| {
+            let name = ident_name(el);
+            quote! { (#name, dioxus_elements::#el.tag_name(), dioxus_elements::#el.namespace()) }
+        });
+
+        let attribute_names = parts.attribute_names.iter().map(|(el, attr)| {
+            let (el_name, attr_name) = (ident_name(el), ident_name(attr));
+            quote! { (#el_name, #attr_name, dioxus_elements::#el.#attr(__cx, format_args!(""))) }
+        });
+
+        let formatted = parts.formatted.iter().map(|arg| {
+            let format = LitStr::new(&format!("{{{}}}", arg), Span::call_site());
+            quote! { (#arg, __cx.raw_text(format_args_f!(#format)).0) }
+        });
+
+        let nodes = parts
+            .nodes
+            .iter()
+            .map(|(key, node)| quote! { (#key, #node) });
+        let attributes = parts
+            .attributes
+            .iter()
+            .map(|(key, attr)| quote! { (#key, #attr) });
+        let listeners = parts
+            .listeners
+            .iter()
+            .map(|(key, attr)| quote! { (#key, #attr) });
+        let node_refs = parts
+            .node_refs
+            .iter()
+            .map(|(key, expr)| quote! { (#key, #expr) });
+
+        let hot = quote! {
+            static __COMPILED: CompiledTemplate = CompiledTemplate {
+                location: CodeLocation::new(file!(), #line, #column),
+                nodes: #node_keys,
+                attributes: #attribute_keys,
+                listeners: #listener_keys,
+                node_refs: #node_ref_keys,
+                formatted: #formatted_keys,
+            };
+
+            match __cx.hot_template(&__COMPILED) {
+                Some(__template) => __template.render(__cx, CapturedContext {
+                    elements: vec![ #(#elements),* ],
+                    attribute_names: vec![ #(#attribute_names),* ],
+                    formatted: vec![ #(#formatted),* ],
+                    nodes: vec![ #(#nodes),* ],
+                    attributes: vec![ #(#attributes),* ],
+                    listeners: vec![ #(#listeners),* ],
+                    node_refs: vec![ #(#node_refs),* ],
+                }),
+                None => #inner,
+            }
+        };
+
+        self.lazy_nodes(hot, &mut out_tokens);
+        out_tokens
+    }
+}
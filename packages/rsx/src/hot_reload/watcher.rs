@@ -0,0 +1,406 @@
+use super::*;
+use dioxus_core::{
+    exports::futures_channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+    CodeLocation, RsxTemplate, TemplateAttribute, TemplateElement, TemplateNode,
+};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    result::Result,
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+
+/// How often the watcher checks the source files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+/// Why a change to a source file couldn't be hot reloaded
+#[derive(Debug, thiserror::Error)]
+pub enum HotReloadError {
+    /// The file doesn't parse, usually because it's in the middle of being edited.
+    #[error("failed to parse the file: {0}")]
+    Parse(#[from] syn::Error),
+
+    /// The change can't be made without compiling the app again.
+    #[error("{0}")]
+    NeedsRebuild(String),
+}
+
+/// A source file as the app was compiled with it, and the `rsx!` calls that have been hot reloaded since.
+pub struct SourceFile {
+    path: PathBuf,
+
+    /// The code outside of `rsx!` calls, which can't change without a rebuild
+    rest: String,
+
+    calls: Vec<CompiledCall>,
+}
+
+struct CompiledCall {
+    location: CodeLocation,
+    keys: DynamicKeys,
+
+    /// The body of the call in the last version of the file
+    body: String,
+}
+
+impl SourceFile {
+    /// Snapshot a source file as it was compiled.
+    ///
+    /// `path` is used for the locations of the templates, so it should be absolute.
+    pub fn new(path: impl Into<PathBuf>, source: &str) -> Result<Self, HotReloadError> {
+        let path = path.into();
+        let file = &path.to_string_lossy().into_owned();
+        let (rest, calls) = find_rsx_calls(source)?;
+
+        let calls = calls
+            .into_iter()
+            .map(|call| {
+                let body = syn::parse2::<CallBody>(call.body.clone())?;
+                Ok(CompiledCall {
+                    location: CodeLocation {
+                        file: file.clone().into(),
+                        line: call.line,
+                        column: call.column,
+                    },
+                    keys: DynamicKeys::new(&body),
+                    body: print_tokens(call.body),
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+
+        Ok(Self { path, rest, calls })
+    }
+
+    /// The path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Compare a new version of the file with the last one, returning the templates of the `rsx!` calls that changed.
+    ///
+    /// Calls are matched up with the compiled calls by their order in the file. The templates keep the locations of
+    /// the compiled calls, even if the code before them moved.
+    pub fn update(&mut self, source: &str) -> Result<Vec<RsxTemplate>, HotReloadError> {
+        let (rest, calls) = find_rsx_calls(source)?;
+
+        if rest != self.rest {
+            return Err(HotReloadError::NeedsRebuild(
+                "code outside of rsx! changed".to_string(),
+            ));
+        }
+
+        if calls.len() != self.calls.len() {
+            return Err(HotReloadError::NeedsRebuild(
+                "rsx! calls were added or removed".to_string(),
+            ));
+        }
+
+        let mut changed = Vec::new();
+        for (compiled, call) in self.calls.iter().zip(calls) {
+            let body = print_tokens(call.body.clone());
+            if body == compiled.body {
+                continue;
+            }
+
+            let parsed = syn::parse2::<CallBody>(call.body)?;
+            if !compiled.keys.contains(&DynamicKeys::new(&parsed)) {
+                return Err(HotReloadError::NeedsRebuild(format!(
+                    "the rsx! call at line {} uses code the app wasn't compiled with",
+                    compiled.location.line
+                )));
+            }
+
+            changed.push((body, template(&parsed, compiled.location.clone())));
+        }
+
+        // only remember the new version once every call in it can be hot reloaded
+        let mut templates = Vec::new();
+        for (body, template) in changed {
+            let idx = self
+                .calls
+                .iter()
+                .position(|call| call.location == template.location)
+                .unwrap();
+            self.calls[idx].body = body;
+            templates.push(template);
+        }
+
+        Ok(templates)
+    }
+}
+
+/// An `rsx!` call found in a source file
+struct FoundCall {
+    line: u32,
+    column: u32,
+    body: TokenStream2,
+}
+
+/// Find every `rsx!` call in a file, including nested ones, and the code around them.
+fn find_rsx_calls(source: &str) -> Result<(String, Vec<FoundCall>), HotReloadError> {
+    let parsed = source
+        .parse::<TokenStream2>()
+        .map_err(|err| HotReloadError::Parse(syn::Error::new(Span::call_site(), err.to_string())));
+
+    let result = parsed.map(|tokens| {
+        let mut calls = Vec::new();
+        let mut rest = TokenStream2::new();
+        visit_rsx_calls(tokens, &mut rest, &mut |rsx, body| {
+            if let Some((line, column)) = call_location(&body, rsx.span()) {
+                calls.push(FoundCall { line, column, body });
+            }
+        });
+        (print_tokens(rest), calls)
+    });
+
+    // Parsing outside of a proc macro records the source of every token for as long as the thread lives. The watcher
+    // never holds on to tokens, so the files it parsed can be forgotten.
+    proc_macro2::extra::invalidate_current_thread_spans();
+
+    result
+}
+
+/// The keys of the dynamic parts of a call, and the arguments of its formatted strings
+#[derive(Default)]
+struct DynamicKeys {
+    nodes: Vec<String>,
+    attributes: Vec<String>,
+    listeners: Vec<String>,
+    node_refs: Vec<String>,
+    formatted: Vec<String>,
+}
+
+impl DynamicKeys {
+    fn new(body: &CallBody) -> Self {
+        fn keys<T>(parts: Vec<(String, T)>) -> Vec<String> {
+            parts.into_iter().map(|(key, _)| key).collect()
+        }
+
+        let parts = DynamicParts::new(body);
+
+        Self {
+            nodes: keys(parts.nodes),
+            attributes: keys(parts.attributes),
+            listeners: keys(parts.listeners),
+            node_refs: keys(parts.node_refs),
+            formatted: parts.formatted,
+        }
+    }
+
+    /// Check if a compiled call with these keys can fill in a call with the other keys
+    fn contains(&self, other: &DynamicKeys) -> bool {
+        fn contains_all(compiled: &[String], used: &[String]) -> bool {
+            let mut remaining = compiled.iter().collect::<Vec<_>>();
+            used.iter()
+                .all(|key| match remaining.iter().position(|k| *k == key) {
+                    Some(idx) => {
+                        remaining.swap_remove(idx);
+                        true
+                    }
+                    None => false,
+                })
+        }
+
+        contains_all(&self.nodes, &other.nodes)
+            && contains_all(&self.attributes, &other.attributes)
+            && contains_all(&self.listeners, &other.listeners)
+            && contains_all(&self.node_refs, &other.node_refs)
+            && other
+                .formatted
+                .iter()
+                .all(|arg| self.formatted.contains(arg))
+    }
+}
+
+/// Build the template of a call
+pub fn template(body: &CallBody, location: CodeLocation) -> RsxTemplate {
+    RsxTemplate {
+        location,
+        roots: body.roots.iter().map(template_node).collect(),
+    }
+}
+
+fn template_node(node: &BodyNode) -> TemplateNode {
+    match node {
+        BodyNode::Text(text) => TemplateNode::Text(text.value()),
+        BodyNode::Component(_) | BodyNode::RawExpr(_) => {
+            TemplateNode::Dynamic(node_key(node).unwrap_or_default())
+        }
+        BodyNode::Element(el) => {
+            let mut attributes = Vec::new();
+            let mut listeners = Vec::new();
+
+            for attr in &el.attributes {
+                match &attr.attr {
+                    ElementAttr::AttrText { name, value } => {
+                        attributes.push(TemplateAttribute::Text {
+                            name: ident_name(name),
+                            custom: false,
+                            value: value.value(),
+                        })
+                    }
+                    ElementAttr::CustomAttrText { name, value } => {
+                        attributes.push(TemplateAttribute::Text {
+                            name: name.value(),
+                            custom: true,
+                            value: value.value(),
+                        })
+                    }
+                    ElementAttr::AttrExpression { .. }
+                    | ElementAttr::CustomAttrExpression { .. } => {
+                        attributes.extend(attribute_key(&attr.attr).map(TemplateAttribute::Dynamic))
+                    }
+                    ElementAttr::EventTokens { .. } => listeners.extend(attribute_key(&attr.attr)),
+                }
+            }
+
+            TemplateNode::Element(TemplateElement {
+                tag: ident_name(&el.name),
+                key: el.key.as_ref().map(LitStr::value),
+                node_ref: el.node_ref.as_ref().map(node_ref_key),
+                attributes,
+                listeners,
+                children: el.children.iter().map(template_node).collect(),
+            })
+        }
+    }
+}
+
+/// Receive the templates of the `rsx!` calls edited while the app is running.
+///
+/// The first call starts a thread that watches the `.rs` files of the crate the app was started from with `cargo run`
+/// (or the current directory). New subscribers first receive the latest template of every call that was already
+/// edited. Changes that need a rebuild are logged as warnings.
+pub fn subscribe() -> UnboundedReceiver<RsxTemplate> {
+    let (tx, rx) = unbounded();
+
+    let mut watcher = WATCHER.lock().unwrap();
+    let watcher = watcher.get_or_insert_with(|| {
+        let root = std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_default();
+
+        let files = watch(&root);
+        std::thread::spawn(move || poll(files));
+
+        Watcher::default()
+    });
+
+    for template in &watcher.latest {
+        let _ = tx.unbounded_send(template.clone());
+    }
+    watcher.subscribers.push(tx);
+
+    rx
+}
+
+static WATCHER: Mutex<Option<Watcher>> = Mutex::new(None);
+
+#[derive(Default)]
+struct Watcher {
+    subscribers: Vec<UnboundedSender<RsxTemplate>>,
+
+    /// The latest template of every call that was edited
+    latest: Vec<RsxTemplate>,
+}
+
+struct WatchedFile {
+    modified: Option<SystemTime>,
+
+    /// `None` if the file didn't parse when the watcher started
+    source: Option<SourceFile>,
+}
+
+/// Snapshot every source file under the root
+fn watch(root: &Path) -> HashMap<PathBuf, WatchedFile> {
+    let mut paths = Vec::new();
+    find_source_files(root, &mut paths);
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let path = path.canonicalize().ok()?;
+            let modified = modified(&path);
+            let source = fs::read_to_string(&path)
+                .ok()
+                .and_then(|source| SourceFile::new(path.clone(), &source).ok());
+            Some((path, WatchedFile { modified, source }))
+        })
+        .collect()
+}
+
+fn poll(mut files: HashMap<PathBuf, WatchedFile>) {
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        for (path, file) in files.iter_mut() {
+            let modified = modified(path);
+            if modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+
+            let (source, contents) = match (&mut file.source, fs::read_to_string(path)) {
+                (Some(source), Ok(contents)) => (source, contents),
+                _ => continue,
+            };
+
+            match source.update(&contents) {
+                Ok(templates) => send(templates),
+                // half-written code is expected while editing
+                Err(HotReloadError::Parse(_)) => {}
+                Err(err @ HotReloadError::NeedsRebuild(_)) => {
+                    log::warn!("Can't hot reload {}: {}", path.display(), err);
+                }
+            }
+        }
+    }
+}
+
+fn send(templates: Vec<RsxTemplate>) {
+    let mut watcher = WATCHER.lock().unwrap();
+    let watcher = match watcher.as_mut() {
+        Some(watcher) => watcher,
+        None => return,
+    };
+
+    for template in templates {
+        watcher
+            .subscribers
+            .retain(|tx| tx.unbounded_send(template.clone()).is_ok());
+
+        watcher
+            .latest
+            .retain(|latest| latest.location != template.location);
+        watcher.latest.push(template);
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Collect the `.rs` files under a directory, skipping build output and hidden directories
+fn find_source_files(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if path.is_dir() {
+            if name != "target" && !name.starts_with('.') {
+                find_source_files(&path, paths);
+            }
+        } else if name.ends_with(".rs") {
+            paths.push(path);
+        }
+    }
+}
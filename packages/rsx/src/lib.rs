@@ -1,6 +1,9 @@
 //! Parse the root tokens in the rsx!{} macro
 //! =========================================
 //!
+//! The parser lives outside of `dioxus-core-macro` so the hot reloader can parse `rsx!` calls exactly the way the
+//! macro does - see the [`hot_reload`] module.
+//!
 //! This parsing path emerges directly from the macro call, with `RsxRender` being the primary entrance into parsing.
 //! This feature must support:
 //! - [x] Optionally rendering if the `in XYZ` pattern is present
@@ -16,6 +19,7 @@ mod errors;
 
 mod component;
 mod element;
+pub mod hot_reload;
 mod node;
 
 pub mod pretty;
@@ -29,6 +33,7 @@ pub use node::*;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens, TokenStreamExt};
 use syn::{
+    buffer::Cursor,
     parse::{Parse, ParseStream},
     Ident, Result, Token,
 };
@@ -71,14 +76,24 @@ impl Parse for CallBody {
 /// Serialize the same way, regardless of flavor
 impl ToTokens for CallBody {
     fn to_tokens(&self, out_tokens: &mut TokenStream2) {
-        let inner = if self.roots.len() == 1 {
+        self.lazy_nodes(self.inner_tokens(), out_tokens)
+    }
+}
+
+impl CallBody {
+    /// Build the roots of the call
+    fn inner_tokens(&self) -> TokenStream2 {
+        if self.roots.len() == 1 {
             let inner = &self.roots[0];
             quote! { #inner }
         } else {
             let childs = &self.roots;
             quote! { __cx.fragment_root([ #(#childs),* ]) }
-        };
+        }
+    }
 
+    /// Wrap the code that builds the nodes in a `LazyNodes`
+    fn lazy_nodes(&self, inner: TokenStream2, out_tokens: &mut TokenStream2) {
         match &self.custom_context {
             // The `in cx` pattern allows directly rendering
             Some(ident) => out_tokens.append_all(quote! {
@@ -98,3 +113,19 @@ impl ToTokens for CallBody {
         };
     }
 }
+
+/// Collect the tokens between two cursors into the same buffer
+fn tokens_between(start: Cursor, end: Cursor) -> TokenStream2 {
+    let mut tokens = TokenStream2::new();
+    let mut cursor = start;
+    while cursor != end {
+        match cursor.token_tree() {
+            Some((tt, next)) => {
+                tokens.append(tt);
+                cursor = next;
+            }
+            None => break,
+        }
+    }
+    tokens
+}
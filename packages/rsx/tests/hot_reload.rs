@@ -0,0 +1,96 @@
+#![allow(non_snake_case)]
+
+//! Hot reload the rsx! calls in this file by editing its source code in memory.
+
+use dioxus_core::prelude::*;
+use dioxus_core_macro::*;
+use dioxus_html as dioxus_elements;
+use dioxus_rsx::hot_reload::{HotReloadError, SourceFile};
+use std::path::PathBuf;
+
+fn app(cx: Scope) -> Element {
+    let name = "world";
+
+    cx.render(rsx!(
+        div { class: "greeting",
+            "hello {name}"
+            Child {}
+        }
+    ))
+}
+
+fn Child(cx: Scope) -> Element {
+    cx.render(rsx!(span { "child" }))
+}
+
+/// This file, as the tests were compiled with it
+fn source_file() -> (SourceFile, String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/hot_reload.rs");
+    let source = std::fs::read_to_string(&path).unwrap();
+    (SourceFile::new(path, &source).unwrap(), source)
+}
+
+fn new_dom() -> VirtualDom {
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom
+}
+
+#[test]
+fn edited_calls_are_rendered_from_templates() {
+    let mut dom = new_dom();
+    assert_eq!(
+        dioxus_ssr::render_vdom(&dom),
+        "<div class=\"greeting\">hello world<span>child</span></div>"
+    );
+
+    let (mut file, source) = source_file();
+    let edited = source.replacen(
+        "\"hello {name}\"\n            Child {}",
+        "Child {}\n            h1 { id: \"title\", \"goodbye {name}!\" }",
+        1,
+    );
+    let templates = file.update(&edited).unwrap();
+    assert_eq!(templates.len(), 1);
+
+    let edits = dom.replace_template(templates.into_iter().next().unwrap());
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        dioxus_ssr::render_vdom(&dom),
+        "<div class=\"greeting\"><span>child</span><h1 id=\"title\">goodbye world!</h1></div>"
+    );
+}
+
+#[test]
+fn unchanged_calls_are_not_sent() {
+    let (mut file, source) = source_file();
+    assert!(file.update(&source).unwrap().is_empty());
+
+    let edited = source.replacen("span { \"child\" }", "span { \"edited\" }", 1);
+    assert_eq!(file.update(&edited).unwrap().len(), 1);
+    assert!(file.update(&edited).unwrap().is_empty());
+}
+
+#[test]
+fn changes_outside_of_rsx_need_a_rebuild() {
+    let (mut file, source) = source_file();
+
+    let edited = source.replacen("let name = \"world\";", "let name = \"there\";", 1);
+    assert!(matches!(
+        file.update(&edited),
+        Err(HotReloadError::NeedsRebuild(_))
+    ));
+
+    // the call uses an expression it wasn't compiled with
+    let edited = source.replacen("\"hello {name}\"", "\"hello {name.len()}\"", 1);
+    assert!(matches!(
+        file.update(&edited),
+        Err(HotReloadError::NeedsRebuild(_))
+    ));
+
+    let edited = source.replacen("span { \"child\" }", "span { \"child\"", 1);
+    assert!(matches!(
+        file.update(&edited),
+        Err(HotReloadError::Parse(_))
+    ));
+}
@@ -71,6 +71,12 @@ pub fn render_vdom(dom: &VirtualDom) -> String {
     format!("{:}", TextRenderer::from_vdom(dom, SsrConfig::default()))
 }
 
+/// Render the VirtualDom once every suspense boundary has resolved, instead of rendering their fallbacks.
+pub async fn render_vdom_resolved(dom: &mut VirtualDom) -> String {
+    dom.wait_for_suspense().await;
+    render_vdom(dom)
+}
+
 pub fn pre_render_vdom(dom: &VirtualDom) -> String {
     format!(
         "{:}",
@@ -169,6 +175,17 @@ impl<'a> TextRenderer<'a, '_> {
                 }
                 write!(f, "<!--placeholder-->")?;
             }
+            // portal content belongs to a different mount point, so only the placeholder left in place is rendered
+            VNode::Portal(_portal) => {
+                *last_node_was_text = false;
+
+                if self.cfg.indent {
+                    for _ in 0..il {
+                        write!(f, "    ")?;
+                    }
+                }
+                write!(f, "<!--placeholder-->")?;
+            }
             VNode::Element(el) => {
                 *last_node_was_text = false;
 
@@ -274,11 +291,23 @@ impl<'a> TextRenderer<'a, '_> {
                     self.html_render(child, f, il + 1, last_node_was_text)?;
                 }
             }
+            VNode::Template(template) => {
+                // templates only exist once they've been enabled on a VirtualDom
+                if let Some(vdom) = self.vdom {
+                    self.html_render(vdom.get_template(template), f, il, last_node_was_text)?;
+                }
+            }
             VNode::Component(vcomp) => {
                 let idx = vcomp.scope.get().unwrap();
 
                 if let (Some(vdom), false) = (self.vdom, self.cfg.skip_components) {
-                    let new_node = vdom.get_scope(idx).unwrap().root_node();
+                    // suspended boundaries render their fallback
+                    let scope = vdom.get_scope(idx).unwrap();
+                    let scope = match scope.suspense_fallback() {
+                        Some(fallback) => vdom.get_scope(fallback).unwrap(),
+                        None => scope,
+                    };
+                    let new_node = scope.root_node();
                     self.html_render(new_node, f, il + 1, last_node_was_text)?;
                 } else {
                 }
@@ -0,0 +1,311 @@
+//! An in-memory renderer for unit testing Dioxus components.
+//!
+//! Comparing the raw [`DomEdit`](dioxus_core::DomEdit)s a component produces breaks whenever the diffing algorithm
+//! changes. A [`TestDom`] applies the edits to a native-core [`RealDom`] instead, so tests can look at the tree a user
+//! would see: find nodes by their tag, text, or attributes, fire events at them, and snapshot the whole tree as HTML.
+//!
+//! Async tasks are only polled when the test calls [`TestDom::advance`], so tests never depend on timing.
+//!
+//! ```rust, ignore
+//! fn app(cx: Scope) -> Element {
+//!     let count = use_state(&cx, || 0);
+//!
+//!     cx.render(rsx!(
+//!         button { onclick: move |_| count += 1, "clicked {count} times" }
+//!     ))
+//! }
+//!
+//! let mut dom = TestDom::new(app);
+//!
+//! let button = dom.get_by_tag("button").id();
+//! dom.click(button);
+//!
+//! assert_eq!(dom.get_by_tag("button").text(), "clicked 1 times");
+//! ```
+
+use dioxus_core::*;
+use dioxus_html::{
+    geometry::{ClientPoint, Coordinates, ElementPoint, PagePoint, ScreenPoint},
+    input_data::{keyboard_types::Modifiers, MouseButton, MouseButtonSet},
+    on::{FormData, MouseData},
+};
+use dioxus_native_core::{
+    real_dom::{NodeType, RealDom},
+    state::*,
+};
+use dioxus_native_core_macro::State;
+use futures_util::FutureExt;
+use std::{any::Any, collections::HashMap, sync::Arc};
+
+/// Build the event a renderer would send when `name` fires on a node.
+///
+/// Use this to drive a [`VirtualDom`] directly; [`TestDom::fire_event`] sends the same event and renders the result.
+pub fn user_event(id: ElementId, name: &'static str, data: impl Any + Send + Sync) -> UserEvent {
+    UserEvent {
+        scope_id: None,
+        priority: EventPriority::Medium,
+        element: Some(id),
+        name,
+        data: Arc::new(data),
+    }
+}
+
+/// Build the `"input"` event a renderer would send when `value` is typed into a node
+pub fn input_event(id: ElementId, value: &str) -> UserEvent {
+    let data = FormData {
+        value: value.to_string(),
+        values: HashMap::new(),
+    };
+    user_event(id, "input", data)
+}
+
+/// The renderer doesn't lay anything out, so nodes don't need any state
+#[derive(Debug, Clone, Default, State)]
+struct NoState {}
+
+type Dom = RealDom<NoState>;
+
+/// A VirtualDom rendered into memory.
+///
+/// Every change is applied as soon as the VirtualDom produces it, and [`VirtualDom::mutations_applied`] is called
+/// right after, so effects run the way they would in a real renderer.
+pub struct TestDom {
+    vdom: VirtualDom,
+    rdom: Dom,
+}
+
+impl TestDom {
+    /// Render a component without any props
+    pub fn new(app: Component<()>) -> Self {
+        Self::new_with_props(app, ())
+    }
+
+    /// Render a component with props
+    pub fn new_with_props<P: 'static>(app: Component<P>, props: P) -> Self {
+        let mut vdom = VirtualDom::new_with_props(app, props);
+        let mut rdom = Dom::new();
+
+        rdom.apply_mutations(vec![vdom.rebuild()]).unwrap();
+        vdom.mutations_applied();
+
+        Self { vdom, rdom }
+    }
+
+    /// The VirtualDom being rendered
+    pub fn vdom(&self) -> &VirtualDom {
+        &self.vdom
+    }
+
+    /// The VirtualDom being rendered, to send it messages directly.
+    ///
+    /// Call [`TestDom::update`] to render the changes.
+    pub fn vdom_mut(&mut self) -> &mut VirtualDom {
+        &mut self.vdom
+    }
+
+    /// Render everything that's waiting to be rendered
+    pub fn update(&mut self) {
+        let mutations = self.vdom.work_with_deadline(|| false);
+        self.rdom.apply_mutations(mutations).unwrap();
+        self.vdom.mutations_applied();
+    }
+
+    /// Poll every pending task once, then render the updates they caused.
+    ///
+    /// Returns `false` if there was nothing to do. Tasks are polled without a real waker, so anything waiting on a
+    /// timer or IO stays pending.
+    pub fn advance(&mut self) -> bool {
+        let progressed = self.vdom.wait_for_work().now_or_never().is_some();
+        self.update();
+        progressed
+    }
+
+    /// Fire an event at a node and render the result.
+    ///
+    /// The data must be the type the event's listeners expect, ie [`MouseData`] for `"click"`. The event bubbles up
+    /// from the node the same way it would in a real renderer.
+    pub fn fire_event(&mut self, id: ElementId, name: &'static str, data: impl Any + Send + Sync) {
+        self.vdom
+            .handle_message(SchedulerMsg::Event(user_event(id, name, data)));
+        self.update();
+    }
+
+    /// Click a node with the primary mouse button
+    pub fn click(&mut self, id: ElementId) {
+        let coordinates = Coordinates::new(
+            ScreenPoint::origin(),
+            ClientPoint::origin(),
+            ElementPoint::origin(),
+            PagePoint::origin(),
+        );
+        let data = MouseData::new(
+            coordinates,
+            Some(MouseButton::Primary),
+            MouseButtonSet::empty(),
+            Modifiers::empty(),
+        );
+        self.fire_event(id, "click", data);
+    }
+
+    /// Type a value into a node, firing an `"input"` event
+    pub fn input(&mut self, id: ElementId, value: &str) {
+        self.vdom
+            .handle_message(SchedulerMsg::Event(input_event(id, value)));
+        self.update();
+    }
+
+    /// Get a node by its id
+    pub fn node(&self, id: ElementId) -> Option<TestNode<'_>> {
+        match id.0 == self.rdom.root_id() {
+            true => None,
+            false => self.rdom.get(id.0).map(|_| TestNode { dom: self, id }),
+        }
+    }
+
+    /// Every node in the tree, in document order
+    pub fn nodes(&self) -> Vec<TestNode<'_>> {
+        let mut nodes = Vec::new();
+        self.rdom.traverse_depth_first(|node| {
+            nodes.push(TestNode {
+                dom: self,
+                id: node.id,
+            });
+        });
+        nodes
+    }
+
+    /// Every element with the given tag, in document order
+    pub fn find_by_tag(&self, tag: &str) -> Vec<TestNode<'_>> {
+        self.find(|node| node.tag() == Some(tag))
+    }
+
+    /// Every element with a text node that matches `text` exactly, in document order
+    pub fn find_by_text(&self, text: &str) -> Vec<TestNode<'_>> {
+        self.find(|node| {
+            node.children()
+                .iter()
+                .any(|child| child.tag().is_none() && child.text() == text)
+        })
+    }
+
+    /// Every element with the given attribute value, in document order
+    pub fn find_by_attribute(&self, name: &str, value: &str) -> Vec<TestNode<'_>> {
+        self.find(|node| node.attribute(name).as_deref() == Some(value))
+    }
+
+    /// Get the only element with the given tag.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one element with the tag.
+    pub fn get_by_tag(&self, tag: &str) -> TestNode<'_> {
+        only(self.find_by_tag(tag), || format!("with the tag {:?}", tag))
+    }
+
+    /// Get the only element with a text node that matches `text` exactly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one element with the text.
+    pub fn get_by_text(&self, text: &str) -> TestNode<'_> {
+        only(self.find_by_text(text), || {
+            format!("with the text {:?}", text)
+        })
+    }
+
+    /// Get the only element with the given attribute value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there isn't exactly one element with the attribute value.
+    pub fn get_by_attribute(&self, name: &str, value: &str) -> TestNode<'_> {
+        only(self.find_by_attribute(name, value), || {
+            format!("with {}={:?}", name, value)
+        })
+    }
+
+    /// Render the tree as indented HTML, for snapshot tests
+    pub fn to_html(&self) -> String {
+        dioxus_ssr::render_vdom_cfg(&self.vdom, |cfg| cfg.indent(true).newline(true))
+    }
+
+    fn find(&self, f: impl Fn(&TestNode) -> bool) -> Vec<TestNode<'_>> {
+        self.nodes().into_iter().filter(|node| f(node)).collect()
+    }
+}
+
+fn only<'a>(mut nodes: Vec<TestNode<'a>>, describe: impl FnOnce() -> String) -> TestNode<'a> {
+    match nodes.len() {
+        1 => nodes.remove(0),
+        n => panic!("expected one element {}, found {}", describe(), n),
+    }
+}
+
+/// A node in a [`TestDom`].
+#[derive(Clone, Copy)]
+pub struct TestNode<'a> {
+    dom: &'a TestDom,
+    id: ElementId,
+}
+
+impl<'a> TestNode<'a> {
+    /// The id of the node, to fire events at it
+    pub fn id(&self) -> ElementId {
+        self.id
+    }
+
+    /// The tag of the node, if it's an element
+    pub fn tag(&self) -> Option<&'a str> {
+        match &self.dom.rdom[self.id].node_type {
+            NodeType::Element { tag, .. } => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// The text of the node and all of its descendants
+    pub fn text(&self) -> String {
+        match &self.dom.rdom[self.id].node_type {
+            NodeType::Text { text } => text.clone(),
+            NodeType::Element { .. } => self.children().iter().map(|c| c.text()).collect(),
+            NodeType::Placeholder => String::new(),
+        }
+    }
+
+    /// The value of one of the node's attributes
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        match self.dom.vdom.get_element(self.id)? {
+            VNode::Element(el) => el
+                .attributes
+                .iter()
+                .find(|attr| attr.name == name)
+                .map(|attr| attr.value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// The parent of the node, unless it's at the root of the tree
+    pub fn parent(&self) -> Option<TestNode<'a>> {
+        self.dom.node(self.dom.rdom[self.id].parent?)
+    }
+
+    /// The children of the node
+    pub fn children(&self) -> Vec<TestNode<'a>> {
+        match &self.dom.rdom[self.id].node_type {
+            NodeType::Element { children, .. } => children
+                .iter()
+                .map(|&id| TestNode { dom: self.dom, id })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TestNode<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestNode")
+            .field("id", &self.id)
+            .field("tag", &self.tag())
+            .field("text", &self.text())
+            .finish()
+    }
+}
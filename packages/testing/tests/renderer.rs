@@ -0,0 +1,131 @@
+#![allow(non_snake_case)]
+
+use dioxus_core::prelude::*;
+use dioxus_core_macro::*;
+use dioxus_hooks::*;
+use dioxus_html as dioxus_elements;
+use dioxus_testing::TestDom;
+
+fn Counter(cx: Scope) -> Element {
+    let count = use_state(&cx, || 0);
+
+    cx.render(rsx!(
+        div { class: "counter",
+            h1 { "Count" }
+            p { "{count}" }
+            button { onclick: move |_| count.modify(|c| c + 1), "increment" }
+            button { onclick: move |_| count.set(0), "reset" }
+        }
+    ))
+}
+
+#[test]
+fn nodes_can_be_queried() {
+    let dom = TestDom::new(Counter);
+
+    assert_eq!(dom.find_by_tag("button").len(), 2);
+    assert!(dom.find_by_tag("span").is_empty());
+
+    let title = dom.get_by_text("Count");
+    assert_eq!(title.tag(), Some("h1"));
+
+    let counter = dom.get_by_attribute("class", "counter");
+    assert_eq!(counter.tag(), Some("div"));
+    assert_eq!(counter.text(), "Count0incrementreset");
+    assert_eq!(counter.children().len(), 4);
+    assert!(counter.parent().is_none());
+
+    assert_eq!(title.parent().unwrap().id(), counter.id());
+}
+
+#[test]
+#[should_panic(expected = "expected one element with the tag \"button\", found 2")]
+fn getting_an_ambiguous_node_panics() {
+    TestDom::new(Counter).get_by_tag("button");
+}
+
+#[test]
+fn events_rerender_the_tree() {
+    let mut dom = TestDom::new(Counter);
+
+    let increment = dom.get_by_text("increment").id();
+    dom.click(increment);
+    dom.click(increment);
+    assert_eq!(dom.get_by_tag("p").text(), "2");
+
+    let reset = dom.get_by_text("reset").id();
+    dom.click(reset);
+    assert_eq!(dom.get_by_tag("p").text(), "0");
+}
+
+#[test]
+fn inputs_update_their_values() {
+    fn app(cx: Scope) -> Element {
+        let name = use_state(&cx, || "".to_string());
+
+        cx.render(rsx!(
+            div {
+                input { value: "{name}", oninput: move |evt| name.set(evt.value.clone()) }
+                (!name.is_empty()).then(|| rsx!(p { "hello {name}" }))
+            }
+        ))
+    }
+
+    let mut dom = TestDom::new(app);
+    assert!(dom.find_by_tag("p").is_empty());
+
+    let input = dom.get_by_tag("input").id();
+    dom.input(input, "world");
+
+    assert_eq!(dom.get_by_tag("input").attribute("value").unwrap(), "world");
+    assert_eq!(dom.get_by_tag("p").text(), "hello world");
+}
+
+#[test]
+fn tasks_only_run_when_advanced() {
+    fn app(cx: Scope) -> Element {
+        let data = use_future(&cx, (), |_| async { "loaded" });
+
+        match data.value() {
+            Some(data) => cx.render(rsx!(p { "{data}" })),
+            None => cx.render(rsx!(p { "loading" })),
+        }
+    }
+
+    let mut dom = TestDom::new(app);
+    assert_eq!(dom.get_by_tag("p").text(), "loading");
+
+    assert!(dom.advance());
+    assert_eq!(dom.get_by_tag("p").text(), "loaded");
+
+    // nothing is left to run
+    assert!(!dom.advance());
+}
+
+#[test]
+fn trees_can_be_snapshotted() {
+    let mut dom = TestDom::new(Counter);
+
+    let increment = dom.get_by_text("increment").id();
+    dom.click(increment);
+
+    assert_eq!(
+        dom.to_html(),
+        "<div class=\"counter\">
+    <h1>
+        Count
+    </h1>
+    <p>
+        1
+    </p>
+    <button>
+        increment
+    </button>
+    <button>
+        reset
+    </button>
+
+</div>
+"
+    );
+}
@@ -54,6 +54,53 @@ impl TuiContext {
     }
 }
 
+/// A handle to an element rendered in the terminal, provided to the [`NodeRef`]s attached to it.
+#[derive(Clone)]
+pub struct TuiNode {
+    id: ElementId,
+    layout: stretch2::node::Node,
+    stretch: Rc<RefCell<Stretch>>,
+}
+impl TuiNode {
+    /// The id of the element
+    pub fn id(&self) -> ElementId {
+        self.id
+    }
+
+    /// The area the element was last laid out in, measured in terminal cells
+    pub fn layout(&self) -> Option<Rect> {
+        let stretch = self.stretch.borrow();
+        let layout = stretch.layout(self.layout).ok()?;
+        Some(Rect {
+            x: layout.location.x as u16,
+            y: layout.location.y as u16,
+            width: layout.size.width as u16,
+            height: layout.size.height as u16,
+        })
+    }
+}
+
+/// Give each of the refs a handle to its node, once the node's layout state has been created
+fn set_node_refs(refs: Vec<(NodeRef, ElementId)>, rdom: &Dom, stretch: &Rc<RefCell<Stretch>>) {
+    for (node_ref, id) in refs {
+        if let Some(node) = rdom.get(id.0) {
+            node_ref.set(TuiNode {
+                id,
+                layout: node.state.layout.node.unwrap(),
+                stretch: stretch.clone(),
+            });
+        }
+    }
+}
+
+fn take_node_refs(mutations: &[Mutations]) -> Vec<(NodeRef, ElementId)> {
+    mutations
+        .iter()
+        .flat_map(|m| &m.refs)
+        .map(|r| (r.node_ref.clone(), r.element_id))
+        .collect()
+}
+
 pub fn launch(app: Component<()>) {
     launch_cfg(app, Config::default())
 }
@@ -86,12 +133,15 @@ pub fn launch_cfg(app: Component<()>, cfg: Config) {
     cx.provide_root_context(TuiContext { tx: event_tx_clone });
 
     let mut rdom: Dom = RealDom::new();
-    let mutations = dom.rebuild();
-    let to_update = rdom.apply_mutations(vec![mutations]);
+    let mutations = vec![dom.rebuild()];
+    let refs = take_node_refs(&mutations);
+    let to_update = rdom.apply_mutations(mutations).unwrap();
+    dom.mutations_applied();
     let stretch = Rc::new(RefCell::new(Stretch::new()));
     let mut any_map = AnyMap::new();
     any_map.insert(stretch.clone());
     let _to_rerender = rdom.update_state(&dom, to_update, any_map).unwrap();
+    set_node_refs(refs, &rdom, &stretch);
 
     render_vdom(
         &mut dom,
@@ -221,12 +271,15 @@ fn render_vdom(
                         vdom.handle_message(SchedulerMsg::Event(e));
                     }
                     let mutations = vdom.work_with_deadline(|| false);
+                    let refs = take_node_refs(&mutations);
                     // updates the dom's nodes
-                    let to_update = rdom.apply_mutations(mutations);
+                    let to_update = rdom.apply_mutations(mutations)?;
+                    vdom.mutations_applied();
                     // update the style and layout
                     let mut any_map = AnyMap::new();
                     any_map.insert(stretch.clone());
                     to_rerender = rdom.update_state(vdom, to_update, any_map).unwrap();
+                    set_node_refs(refs, &rdom, &stretch);
                 }
             }
 
@@ -7,11 +7,11 @@
 //! - tests to ensure dyn_into works for various event types.
 //! - Partial delegation?>
 
-use dioxus_core::{DomEdit, ElementId, SchedulerMsg, UserEvent};
+use dioxus_core::{DomEdit, ElementId, NodeRef, SchedulerMsg, UserEvent};
 use dioxus_interpreter_js::Interpreter;
 use js_sys::Function;
 use std::{any::Any, rc::Rc, sync::Arc};
-use wasm_bindgen::{closure::Closure, JsCast};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
 use web_sys::{Document, Element, Event, HtmlElement};
 
 use crate::WebConfig;
@@ -51,6 +51,20 @@ impl WebsysDom {
                         break Err(e.into());
                     }
                     None => {
+                        // events from inside a portal bubble up through where the portal was rendered, not its mount point
+                        if let Some(id) = portal_child_id(&target) {
+                            break Ok(UserEvent {
+                                name: event_name_from_typ(&typ),
+                                data: virtual_event_from_websys_event(
+                                    event.clone(),
+                                    target.clone(),
+                                ),
+                                element: Some(ElementId(id)),
+                                scope_id: None,
+                                priority: dioxus_core::EventPriority::Medium,
+                            });
+                        }
+
                         // walk the tree upwards until we actually find an event target
                         if let Some(parent) = target.parent_element() {
                             target = parent;
@@ -101,11 +115,19 @@ impl WebsysDom {
         }
     }
 
+    /// Give a [`NodeRef`] a handle to the `web_sys::Element` it was mounted as
+    pub fn set_node_ref(&self, node_ref: &NodeRef, id: ElementId) {
+        if let Ok(element) = self.interpreter.GetNode(id.0).dyn_into::<Element>() {
+            node_ref.set(element);
+        }
+    }
+
     pub fn apply_edits(&mut self, mut edits: Vec<DomEdit>) {
         for edit in edits.drain(..) {
             match edit {
                 DomEdit::PushRoot { root } => self.interpreter.PushRoot(root),
                 DomEdit::PopRoot {} => self.interpreter.PopRoot(),
+                DomEdit::PushNamedRoot { name } => self.interpreter.PushNamedRoot(name),
                 DomEdit::AppendChildren { many } => self.interpreter.AppendChildren(many),
                 DomEdit::ReplaceWith { root, m } => self.interpreter.ReplaceWith(root, m),
                 DomEdit::InsertAfter { root, n } => self.interpreter.InsertAfter(root, n),
@@ -117,6 +139,8 @@ impl WebsysDom {
                     self.interpreter.CreateElementNs(tag, root, ns)
                 }
                 DomEdit::CreatePlaceholder { root } => self.interpreter.CreatePlaceholder(root),
+                DomEdit::SaveTemplate { id } => self.interpreter.SaveTemplate(id),
+                DomEdit::CloneTemplate { id, root } => self.interpreter.CloneTemplate(id, root),
                 DomEdit::NewEventListener {
                     event_name, root, ..
                 } => {
@@ -154,6 +178,17 @@ impl WebsysDom {
     }
 }
 
+/// The id of an element appended directly to a portal's mount point by the interpreter, if `target` is one.
+fn portal_child_id(target: &Element) -> Option<usize> {
+    let parent = target.parent_element()?;
+    if !parent.has_attribute("data-dioxus-portal") {
+        return None;
+    }
+
+    let id = js_sys::Reflect::get(target, &JsValue::from_str("dioxusId")).ok()?;
+    id.as_f64().map(|id| id as usize)
+}
+
 pub struct DioxusWebsysEvent(web_sys::Event);
 
 // safety: currently the web is not multithreaded and our VirtualDom exists on the same thread
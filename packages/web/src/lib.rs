@@ -187,6 +187,10 @@ pub async fn run_with_props<T: 'static + Send>(root: Component<T>, root_props: T
 
     let should_hydrate = cfg.hydrate;
 
+    if !should_hydrate {
+        dom.enable_templates();
+    }
+
     let mut websys_dom = dom::WebsysDom::new(cfg, sender_callback);
 
     log::trace!("rebuilding app");
@@ -194,7 +198,9 @@ pub async fn run_with_props<T: 'static + Send>(root: Component<T>, root_props: T
     if should_hydrate {
         // todo: we need to split rebuild and initialize into two phases
         // it's a waste to produce edits just to get the vdom loaded
-        let _ = dom.rebuild();
+        let refs: Vec<_> = (dom.rebuild().refs.iter())
+            .map(|r| (r.node_ref.clone(), r.element_id))
+            .collect();
 
         if let Err(err) = websys_dom.rehydrate(&dom) {
             log::error!(
@@ -209,12 +215,24 @@ pub async fn run_with_props<T: 'static + Send>(root: Component<T>, root_props: T
             let edits = dom.rebuild();
 
             websys_dom.apply_edits(edits.edits);
+            for r in &edits.refs {
+                websys_dom.set_node_ref(r.node_ref, r.element_id);
+            }
+        } else {
+            for (node_ref, id) in refs {
+                websys_dom.set_node_ref(&node_ref, id);
+            }
         }
     } else {
         let edits = dom.rebuild();
         websys_dom.apply_edits(edits.edits);
+        for r in &edits.refs {
+            websys_dom.set_node_ref(r.node_ref, r.element_id);
+        }
     }
 
+    dom.mutations_applied();
+
     let mut work_loop = ric_raf::RafLoop::new();
 
     loop {
@@ -237,6 +255,11 @@ pub async fn run_with_props<T: 'static + Send>(root: Component<T>, root_props: T
         for edit in mutations {
             // actually apply our changes during the animation frame
             websys_dom.apply_edits(edit.edits);
+            for r in &edit.refs {
+                websys_dom.set_node_ref(r.node_ref, r.element_id);
+            }
         }
+
+        dom.mutations_applied();
     }
 }
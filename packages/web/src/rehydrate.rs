@@ -8,6 +8,7 @@ pub enum RehydrationError {
     NodeTypeMismatch,
     NodeNotFound,
     VNodeNotInitialized,
+    PortalNotSupported,
 }
 use RehydrationError::*;
 
@@ -158,6 +159,12 @@ impl WebsysDom {
                 }
             }
 
+            // the edits of the first rebuild are thrown away, so the interpreter never gets the templates
+            VNode::Template(_) => unreachable!("templates are disabled while hydrating"),
+
+            // portal content isn't prerendered, so there is nothing to hydrate it from
+            VNode::Portal(_) => return Err(PortalNotSupported),
+
             VNode::Component(el) => {
                 let scope = dom.get_scope(el.scope.get().unwrap()).unwrap();
                 let node = scope.root_node();
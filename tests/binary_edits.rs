@@ -0,0 +1,318 @@
+#![allow(non_snake_case)]
+
+//! Round-trip tests for the binary edit encoding.
+//!
+//! The decoder here mirrors `EditDecoder` in `interpreter.js`, producing the same values as the serde representation.
+
+use dioxus::prelude::*;
+use dioxus_core::{
+    encode_edits, register_attribute_value, ArbitraryAttributeValue, AttributeValue,
+    CustomAttributeValue, DomEdit, DomEdit::*, EditEncoder, SchedulerMsg, ENCODING_VERSION,
+};
+use serde_json::{json, Value};
+
+struct Decoder {
+    strings: Vec<String>,
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn byte(&mut self) -> u8 {
+        self.pos += 1;
+        self.bytes[self.pos - 1]
+    }
+
+    fn take(&mut self, len: usize) -> &[u8] {
+        self.pos += len;
+        &self.bytes[self.pos - len..self.pos]
+    }
+
+    fn varint(&mut self) -> u64 {
+        let (mut result, mut shift) = (0, 0);
+        loop {
+            let byte = self.byte();
+            result |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return result;
+            }
+        }
+    }
+
+    fn signed(&mut self) -> i64 {
+        let zigzag = self.varint();
+        ((zigzag >> 1) as i64) ^ -((zigzag & 1) as i64)
+    }
+
+    fn f32(&mut self) -> f32 {
+        f32::from_le_bytes(self.take(4).try_into().unwrap())
+    }
+
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.take(8).try_into().unwrap())
+    }
+
+    fn str(&mut self) -> String {
+        let len = self.varint() as usize;
+        String::from_utf8(self.take(len).to_vec()).unwrap()
+    }
+}
+
+impl Decoder {
+    fn new() -> Self {
+        Self { strings: vec![] }
+    }
+
+    fn decode(&mut self, bytes: &[u8]) -> Value {
+        let mut c = Cursor { bytes, pos: 0 };
+        assert_eq!(c.byte(), ENCODING_VERSION);
+
+        for _ in 0..c.varint() {
+            let string = c.str();
+            self.strings.push(string);
+        }
+
+        let edits = (0..c.varint()).map(|_| self.edit(&mut c)).collect();
+        assert_eq!(c.pos, bytes.len());
+        Value::Array(edits)
+    }
+
+    fn interned(&self, c: &mut Cursor) -> Value {
+        json!(self.strings[c.varint() as usize])
+    }
+
+    fn optional(&self, c: &mut Cursor) -> Value {
+        match c.varint() {
+            0 => Value::Null,
+            idx => json!(self.strings[idx as usize - 1]),
+        }
+    }
+
+    fn edit(&self, c: &mut Cursor) -> Value {
+        match c.byte() {
+            0 => json!({ "type": "PushRoot", "root": c.varint() }),
+            1 => json!({ "type": "AppendChildren", "many": c.varint() }),
+            2 => json!({ "type": "ReplaceWith", "root": c.varint(), "m": c.varint() }),
+            3 => json!({ "type": "InsertAfter", "root": c.varint(), "n": c.varint() }),
+            4 => json!({ "type": "InsertBefore", "root": c.varint(), "n": c.varint() }),
+            5 => json!({ "type": "Remove", "root": c.varint() }),
+            6 => json!({ "type": "CreateTextNode", "root": c.varint(), "text": c.str() }),
+            7 => json!({ "type": "CreateElement", "root": c.varint(), "tag": self.interned(c) }),
+            8 => json!({
+                "type": "CreateElementNs",
+                "root": c.varint(),
+                "tag": self.interned(c),
+                "ns": self.interned(c),
+            }),
+            9 => json!({ "type": "CreatePlaceholder", "root": c.varint() }),
+            10 => json!({
+                "type": "NewEventListener",
+                "event_name": self.interned(c),
+                "scope": c.varint(),
+                "root": c.varint(),
+            }),
+            11 => json!({
+                "type": "RemoveEventListener",
+                "root": c.varint(),
+                "event": self.interned(c),
+            }),
+            12 => json!({ "type": "SetText", "root": c.varint(), "text": c.str() }),
+            13 => json!({
+                "type": "SetAttribute",
+                "root": c.varint(),
+                "field": self.interned(c),
+                "value": self.value(c),
+                "ns": self.optional(c),
+            }),
+            14 => json!({
+                "type": "RemoveAttribute",
+                "root": c.varint(),
+                "name": self.interned(c),
+                "ns": self.optional(c),
+            }),
+            15 => json!({ "type": "PopRoot" }),
+            16 => json!({ "type": "SaveTemplate", "id": c.varint() }),
+            17 => json!({ "type": "CloneTemplate", "id": c.varint(), "root": c.varint() }),
+            18 => json!({ "type": "PushNamedRoot", "name": self.interned(c) }),
+            op => panic!("unknown opcode {}", op),
+        }
+    }
+
+    fn value(&self, c: &mut Cursor) -> Value {
+        match c.byte() {
+            0 => json!(c.str()),
+            1 => json!(c.f32()),
+            2 => json!(c.f64()),
+            3 | 4 => json!(c.signed()),
+            5 | 6 => json!(c.varint()),
+            7 => json!(c.byte() == 1),
+            8 => json!([c.f32(), c.f32(), c.f32()]),
+            9 => json!([c.signed(), c.signed(), c.signed()]),
+            10 => json!([c.varint(), c.varint(), c.varint()]),
+            11 => json!([c.f32(), c.f32(), c.f32(), c.f32()]),
+            12 => json!([c.signed(), c.signed(), c.signed(), c.signed()]),
+            13 => json!([c.varint(), c.varint(), c.varint(), c.varint()]),
+            14 => {
+                let len = c.varint() as usize;
+                json!(c.take(len))
+            }
+            15 => {
+                let name = c.str();
+                json!({ "name": name, "payload": c.str() })
+            }
+            tag => panic!("unknown value tag {}", tag),
+        }
+    }
+}
+
+fn assert_round_trip(decoder: &mut Decoder, encoder: &mut EditEncoder, edits: &[DomEdit]) {
+    let bytes = encoder.encode(edits).unwrap();
+    assert_eq!(decoder.decode(&bytes), serde_json::to_value(edits).unwrap());
+}
+
+#[test]
+fn every_edit_round_trips() {
+    let edits = [
+        PushRoot { root: 1 },
+        AppendChildren { many: 3 },
+        ReplaceWith { root: 300, m: 2 },
+        InsertAfter { root: 4, n: 1 },
+        InsertBefore { root: u64::MAX >> 12, n: 128 },
+        Remove { root: 5 },
+        CreateTextNode { text: "héllo wörld", root: 6 },
+        CreateElement { tag: "div", root: 7 },
+        CreateElementNs { tag: "svg", root: 8, ns: "http://www.w3.org/2000/svg" },
+        CreatePlaceholder { root: 9 },
+        NewEventListener { event_name: "click", scope: ScopeId(12), root: 7 },
+        RemoveEventListener { root: 7, event: "click" },
+        SetText { root: 6, text: "" },
+        RemoveAttribute { root: 7, name: "class", ns: None },
+        RemoveAttribute { root: 7, name: "color", ns: Some("style") },
+        PopRoot {},
+        SaveTemplate { id: 2 },
+        CloneTemplate { id: 2, root: 300 },
+        PushNamedRoot { name: "modals" },
+    ];
+
+    let mut decoder = Decoder::new();
+    assert_round_trip(&mut decoder, &mut EditEncoder::new(), &edits);
+}
+
+#[test]
+fn attribute_values_round_trip() {
+    let values = [
+        AttributeValue::Text("red"),
+        AttributeValue::Float32(1.5),
+        AttributeValue::Float64(-0.1),
+        AttributeValue::Int32(-7),
+        AttributeValue::Int64(i64::MIN >> 12),
+        AttributeValue::Uint32(u32::MAX),
+        AttributeValue::Uint64(1 << 40),
+        AttributeValue::Bool(true),
+        AttributeValue::Bool(false),
+        AttributeValue::Vec3Float(0.5, 1.0, -2.0),
+        AttributeValue::Vec3Int(-1, 0, 1),
+        AttributeValue::Vec3Uint(1, 2, 3),
+        AttributeValue::Vec4Float(0.25, 0.5, 0.75, 1.0),
+        AttributeValue::Vec4Int(i32::MIN, -1, 1, i32::MAX),
+        AttributeValue::Vec4Uint(0, 1, 2, u32::MAX),
+        AttributeValue::Bytes(&[0, 1, 255]),
+    ];
+
+    let edits: Vec<_> = values
+        .into_iter()
+        .map(|value| SetAttribute { root: 1, field: "data", value, ns: Some("style") })
+        .collect();
+
+    let mut decoder = Decoder::new();
+    assert_round_trip(&mut decoder, &mut EditEncoder::new(), &edits);
+}
+
+#[derive(PartialEq)]
+struct Point(i32, i32);
+
+impl CustomAttributeValue for Point {
+    const NAME: &'static str = "point";
+
+    fn to_payload(&self) -> Option<String> {
+        Some(format!("[{},{}]", self.0, self.1))
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        let (x, y) = serde_json::from_str(payload).ok()?;
+        Some(Point(x, y))
+    }
+}
+
+#[test]
+fn custom_values_round_trip() {
+    register_attribute_value::<Point>();
+
+    let point = Point(3, -4);
+    let edits = [SetAttribute {
+        root: 1,
+        field: "origin",
+        value: AttributeValue::Any(ArbitraryAttributeValue::new(&point)),
+        ns: None,
+    }];
+
+    let mut decoder = Decoder::new();
+    assert_round_trip(&mut decoder, &mut EditEncoder::new(), &edits);
+}
+
+#[test]
+fn vdom_edits_round_trip() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(
+            div { class: "counter", onclick: move |_| {},
+                "count: {count}"
+                (0..*count % 3).map(|i| rsx!(li { key: "{i}", "{i}" }))
+            }
+            svg { width: "10", circle { r: "5" } }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mut encoder = EditEncoder::new();
+    let mut decoder = Decoder::new();
+
+    assert_round_trip(&mut decoder, &mut encoder, &dom.rebuild().edits);
+
+    for _ in 0..3 {
+        dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+        for mutations in dom.work_with_deadline(|| false) {
+            assert_round_trip(&mut decoder, &mut encoder, &mutations.edits);
+        }
+    }
+}
+
+#[test]
+fn strings_are_interned_across_batches() {
+    let edits = [
+        CreateElement { tag: "div", root: 1 },
+        CreateElement { tag: "div", root: 2 },
+        SetAttribute { root: 2, field: "class", value: AttributeValue::Text("a"), ns: None },
+    ];
+
+    let mut encoder = EditEncoder::new();
+    let first = encoder.encode(&edits).unwrap();
+    let second = encoder.encode(&edits).unwrap();
+
+    // version, 2 new strings ("div", "class"), 3 edits
+    assert_eq!(&first[..2], [ENCODING_VERSION, 2]);
+    assert_eq!(&second[..3], [ENCODING_VERSION, 0, 3]);
+    assert_eq!(first.len() - second.len(), "\x03div\x05class".len());
+
+    // a fresh encoder starts over
+    assert_eq!(encode_edits(&edits).unwrap(), first);
+
+    let json = serde_json::to_vec(&edits).unwrap();
+    assert!(second.len() * 5 < json.len());
+}
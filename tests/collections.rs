@@ -0,0 +1,187 @@
+#![allow(non_snake_case)]
+
+//! Tests for the keyed collection hooks: entries re-render on their own, and moves become DOM moves.
+
+use dioxus::prelude::*;
+use dioxus_core::DomEdit::*;
+use std::cell::{Cell, RefCell};
+
+mod test_utils;
+use test_utils::update;
+
+thread_local! {
+    static TODOS: Cell<Option<UseVec<&'static str>>> = Cell::new(None);
+    static SCORES: Cell<Option<UseMap<&'static str, u32>>> = Cell::new(None);
+    static RENDERS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+fn todos() -> UseVec<&'static str> {
+    TODOS.with(|todos| todos.get()).unwrap()
+}
+
+fn scores() -> UseMap<&'static str, u32> {
+    SCORES.with(|scores| scores.get()).unwrap()
+}
+
+fn rendered(name: String) {
+    RENDERS.with(|renders| renders.borrow_mut().push(name));
+}
+
+fn take_renders() -> Vec<String> {
+    RENDERS.with(|renders| renders.borrow_mut().drain(..).collect())
+}
+
+fn todo_app(cx: Scope) -> Element {
+    rendered("list".to_string());
+    let todos = use_vec(&cx, || vec!["a", "b", "c"]);
+    TODOS.with(|cell| cell.set(Some(todos)));
+
+    cx.render(rsx!(
+        ul {
+            todos.entries().into_iter().map(|todo| {
+                let key = *todo.key();
+                rsx!(Todo { key: "{key}", todo: todo })
+            })
+        }
+    ))
+}
+
+#[inline_props]
+fn Todo(cx: Scope, todo: Entry<usize, &'static str>) -> Element {
+    rendered(todo.get().to_string());
+    cx.render(rsx!(li { "{todo}" }))
+}
+
+#[test]
+fn updating_an_entry_rerenders_only_its_component() {
+    let mut dom = VirtualDom::new(todo_app);
+    dom.rebuild();
+    assert_eq!(take_renders(), ["list", "a", "b", "c"]);
+
+    assert!(todos().update(1, |todo| *todo = "B"));
+    update(&mut dom);
+    assert_eq!(take_renders(), ["B"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>a</li><li>B</li><li>c</li></ul>"
+    );
+
+    // out of bounds updates do nothing
+    assert!(!todos().update(3, |todo| *todo = "d"));
+    update(&mut dom);
+    assert!(take_renders().is_empty());
+}
+
+#[test]
+fn moving_entries_moves_their_nodes() {
+    let mut dom = VirtualDom::new(todo_app);
+    dom.rebuild();
+    take_renders();
+
+    todos().move_item(0, 2);
+    let mutations = update(&mut dom);
+    let edits: Vec<_> = mutations.into_iter().flat_map(|m| m.edits).collect();
+
+    // only the list re-renders, and the moved item is moved rather than recreated
+    assert_eq!(take_renders(), ["list"]);
+    assert_eq!(edits, [PushRoot { root: 2 }, InsertAfter { n: 1, root: 6 }]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>b</li><li>c</li><li>a</li></ul>"
+    );
+
+    todos().swap(0, 2);
+    update(&mut dom);
+    assert_eq!(take_renders(), ["list"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>a</li><li>c</li><li>b</li></ul>"
+    );
+}
+
+#[test]
+fn adding_and_removing_entries() {
+    let mut dom = VirtualDom::new(todo_app);
+    dom.rebuild();
+    take_renders();
+
+    todos().push("d");
+    todos().insert(0, "z");
+    update(&mut dom);
+    let mut renders = take_renders();
+    renders.sort();
+    assert_eq!(renders, ["d", "list", "z"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>z</li><li>a</li><li>b</li><li>c</li><li>d</li></ul>"
+    );
+
+    assert_eq!(todos().remove(1), "a");
+    assert_eq!(todos().pop(), Some("d"));
+    todos().retain(|todo| *todo != "b");
+    update(&mut dom);
+    assert_eq!(take_renders(), ["list"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>z</li><li>c</li></ul>"
+    );
+
+    todos().clear();
+    update(&mut dom);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><!--placeholder--></ul>"
+    );
+}
+
+#[test]
+fn maps_keep_their_insertion_order() {
+    fn app(cx: Scope) -> Element {
+        let scores = use_map(&cx, || vec![("ann", 1), ("bob", 2)]);
+        SCORES.with(|cell| cell.set(Some(scores)));
+
+        cx.render(rsx!(
+            ul {
+                scores.entries().into_iter().map(|score| {
+                    let name = *score.key();
+                    rsx!(Score { key: "{name}", score: score })
+                })
+            }
+        ))
+    }
+
+    #[inline_props]
+    fn Score(cx: Scope, score: Entry<&'static str, u32>) -> Element {
+        rendered(score.key().to_string());
+        let name = *score.key();
+        cx.render(rsx!(li { "{name}: {score}" }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    take_renders();
+
+    // replacing a value keeps the entry in place
+    assert_eq!(scores().insert("ann", 10), Some(1));
+    assert_eq!(scores().insert("cat", 3), None);
+    update(&mut dom);
+    let mut renders = take_renders();
+    renders.sort();
+    assert_eq!(renders, ["ann", "cat"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>ann: 10</li><li>bob: 2</li><li>cat: 3</li></ul>"
+    );
+
+    assert!(scores().move_to(&"cat", 0));
+    assert!(!scores().move_to(&"dan", 0));
+    assert!(scores().update(&"bob", |score| *score += 1));
+    assert_eq!(scores().remove(&"ann"), Some(10));
+    update(&mut dom);
+    assert_eq!(take_renders(), ["bob"]);
+    assert_eq!(scores().keys(), ["cat", "bob"]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>cat: 3</li><li>bob: 3</li></ul>"
+    );
+}
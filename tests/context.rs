@@ -0,0 +1,157 @@
+#![allow(non_snake_case)]
+
+//! Tests for typed context keys, shadowing, and updating contexts.
+
+use dioxus::prelude::*;
+use dioxus_core::MissingContext;
+
+const PRIMARY: ContextKey<&'static str> = ContextKey::new("primary");
+const SECONDARY: ContextKey<&'static str> = ContextKey::new("secondary");
+
+fn render(dom: &VirtualDom) -> String {
+    dioxus::ssr::render_vdom(dom)
+}
+
+#[test]
+fn keys_provide_several_values_of_the_same_type() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|_| {
+            cx.provide_context("unnamed");
+            PRIMARY.provide(&cx, "red");
+            SECONDARY.provide(&cx, "blue");
+        });
+
+        cx.render(rsx!(Child {}))
+    }
+
+    fn Child(cx: Scope) -> Element {
+        let unnamed = cx.consume_context::<&'static str>()?;
+        let primary = PRIMARY.consume(&cx)?;
+        let secondary = SECONDARY.consume(&cx)?;
+
+        cx.render(rsx!("{unnamed} {primary} {secondary}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(render(&dom), "unnamed red blue");
+}
+
+#[test]
+fn contexts_shadow_the_contexts_of_ancestors() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|_| {
+            PRIMARY.provide(&cx, "root primary");
+            SECONDARY.provide(&cx, "root secondary");
+        });
+
+        cx.render(rsx!(
+            div {
+                Shadowing {}
+                Leaf {}
+            }
+        ))
+    }
+
+    fn Shadowing(cx: Scope) -> Element {
+        let before = PRIMARY.consume(&cx)?;
+        PRIMARY.provide(&cx, "shadowed primary");
+        let after = PRIMARY.consume(&cx)?;
+
+        cx.render(rsx!(
+            p { "{before} -> {after}" }
+            Leaf {}
+        ))
+    }
+
+    fn Leaf(cx: Scope) -> Element {
+        let primary = PRIMARY.consume(&cx)?;
+        let secondary = SECONDARY.consume(&cx)?;
+
+        cx.render(rsx!(span { "{primary}, {secondary}" }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(
+        render(&dom),
+        concat!(
+            "<div>",
+            "<p>root primary -> shadowed primary</p>",
+            // only the shadowed key changes beneath the shadowing component
+            "<span>shadowed primary, root secondary</span>",
+            // siblings keep seeing the ancestor's values
+            "<span>root primary, root secondary</span>",
+            "</div>"
+        )
+    );
+}
+
+#[test]
+fn updating_a_context_rerenders_its_consumers() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|_| {
+            cx.provide_context(0);
+            PRIMARY.provide(&cx, "light");
+        });
+
+        cx.render(rsx!(
+            div {
+                Counter {}
+                Theme {}
+            }
+        ))
+    }
+
+    fn Counter(cx: Scope) -> Element {
+        let count = cx.consume_context::<i32>()?;
+        cx.render(rsx!(p { "{count}" }))
+    }
+
+    fn Theme(cx: Scope) -> Element {
+        let theme = PRIMARY.consume(&cx)?;
+        cx.render(rsx!(p { "{theme}" }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(render(&dom), "<div><p>0</p><p>light</p></div>");
+
+    // the children are memoized, so only the update re-renders them
+    dom.base_scope().update_context(1);
+    dom.process_all_messages();
+    dom.work_with_deadline(|| false);
+    assert_eq!(render(&dom), "<div><p>1</p><p>light</p></div>");
+
+    PRIMARY.update(dom.base_scope(), "dark");
+    dom.process_all_messages();
+    dom.work_with_deadline(|| false);
+    assert_eq!(render(&dom), "<div><p>1</p><p>dark</p></div>");
+
+    // providing a context again doesn't notify anyone
+    dom.base_scope().provide_context(2);
+    dom.process_all_messages();
+    dom.work_with_deadline(|| false);
+    assert_eq!(render(&dom), "<div><p>1</p><p>dark</p></div>");
+}
+
+#[test]
+fn missing_contexts_can_fail_or_fall_back() {
+    fn app(cx: Scope) -> Element {
+        let missing = cx.try_consume_context::<i32>().unwrap_err();
+        assert_eq!(missing, MissingContext { type_name: "i32", key: None });
+        assert_eq!(
+            PRIMARY.try_consume(&cx).unwrap_err().to_string(),
+            "no context of type &str was provided for the key \"primary\""
+        );
+
+        let count = cx.consume_context_or_default::<i32>();
+        let theme = PRIMARY.consume_or_default(&cx);
+
+        cx.render(rsx!("{count} '{theme}'"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(render(&dom), "0 ''");
+}
@@ -0,0 +1,113 @@
+//! Tests for sending custom attribute values to renderers that don't read the VirtualDom directly.
+
+use dioxus_core::{
+    register_attribute_value, ArbitraryAttributeValue, AttributeValue, AttributeValueOwned,
+    CustomAttributeValue, CustomValue, DomEdit, DomEditOwned, EditInterner, ToOwnedError,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ClassList(Vec<String>);
+
+impl CustomAttributeValue for ClassList {
+    const NAME: &'static str = "class-list";
+
+    fn to_payload(&self) -> Option<String> {
+        serde_json::to_string(self).ok()
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        serde_json::from_str(payload).ok()
+    }
+}
+
+#[derive(PartialEq)]
+struct Unregistered;
+
+fn classes() -> ClassList {
+    ClassList(vec!["card".to_string(), "active".to_string()])
+}
+
+fn set_attribute(value: AttributeValue) -> DomEdit {
+    DomEdit::SetAttribute { root: 1, field: "classes", value, ns: None }
+}
+
+#[test]
+fn registered_values_have_an_owned_form() {
+    register_attribute_value::<ClassList>();
+
+    let classes = classes();
+    let owned = EditInterner::new()
+        .edit(&set_attribute(AttributeValue::Any(
+            ArbitraryAttributeValue::new(&classes),
+        )))
+        .unwrap();
+
+    let custom = match &owned {
+        DomEditOwned::SetAttribute { value: AttributeValueOwned::Custom(custom), .. } => custom,
+        edit => panic!("expected a custom value, found {:?}", edit),
+    };
+    assert_eq!(
+        *custom,
+        CustomValue { name: Arc::from("class-list"), payload: Arc::from(r#"["card","active"]"#) }
+    );
+
+    // renderers can decode the value with or without knowing its type
+    assert_eq!(custom.decode::<ClassList>().as_ref(), Some(&classes));
+    let any = custom.deserialize().unwrap();
+    assert_eq!(any.downcast_ref::<ClassList>(), Some(&classes));
+
+    // converting back and forth doesn't change the value
+    let borrowed = match &owned {
+        DomEditOwned::SetAttribute { value, .. } => value.as_borrowed(),
+        _ => unreachable!(),
+    };
+    assert_eq!(
+        borrowed.as_any().unwrap().to_custom().as_ref(),
+        Some(custom)
+    );
+    assert_eq!(borrowed.to_string(), r#"["card","active"]"#);
+}
+
+#[test]
+fn registered_values_are_serialized_by_name() {
+    register_attribute_value::<ClassList>();
+
+    let classes = classes();
+    let edit = set_attribute(AttributeValue::Any(ArbitraryAttributeValue::new(&classes)));
+    let json = serde_json::to_value(&edit).unwrap();
+    assert_eq!(
+        json["value"],
+        serde_json::json!({ "name": "class-list", "payload": r#"["card","active"]"# })
+    );
+
+    let owned: DomEditOwned = serde_json::from_value(json).unwrap();
+    assert_eq!(owned, EditInterner::new().edit(&edit).unwrap());
+}
+
+#[test]
+fn unregistered_values_are_rejected() {
+    let edit = set_attribute(AttributeValue::Any(ArbitraryAttributeValue::new(
+        &Unregistered,
+    )));
+
+    assert_eq!(
+        EditInterner::new().edit(&edit).unwrap_err(),
+        ToOwnedError::UnsupportedValue { root: 1, field: "classes" }
+    );
+    assert!(serde_json::to_string(&edit).is_err());
+
+    // there's no text to show for them
+    assert_eq!(
+        AttributeValue::Any(ArbitraryAttributeValue::new(&Unregistered)).to_string(),
+        ""
+    );
+}
+
+#[test]
+fn values_of_another_type_are_not_decoded() {
+    let custom = CustomValue { name: Arc::from("point"), payload: Arc::from("[1,2]") };
+    assert_eq!(custom.decode::<ClassList>(), None);
+    assert!(custom.deserialize().is_none());
+}
@@ -0,0 +1,136 @@
+#![allow(non_snake_case)]
+
+//! Tests for effect cleanups and layout effects.
+
+use dioxus::prelude::*;
+use std::{cell::RefCell, time::Duration};
+
+mod test_utils;
+use test_utils::rerender;
+
+thread_local! {
+    static LOG: RefCell<Vec<String>> = RefCell::new(Vec::new());
+}
+
+fn log(entry: String) {
+    LOG.with(|log| log.borrow_mut().push(entry));
+}
+
+fn take_log() -> Vec<String> {
+    LOG.with(|log| log.take())
+}
+
+/// The root renders `Child { value }` for the first three renders, with `value` changing after the first one
+fn app(cx: Scope<Component<ChildProps>>) -> Element {
+    let renders = cx.use_hook(|_| 0);
+    *renders += 1;
+
+    let value = (*renders > 1) as u32;
+    let Child = *cx.props;
+
+    cx.render(rsx!((*renders <= 3).then(|| rsx!(Child { value: value }))))
+}
+
+#[derive(Props, PartialEq)]
+struct ChildProps {
+    value: u32,
+}
+
+/// Poll the tasks the effects spawned
+async fn run_tasks(dom: &mut VirtualDom) {
+    let _ = tokio::time::timeout(Duration::from_millis(20), dom.wait_for_work()).await;
+}
+
+#[test]
+fn layout_effects_run_once_mutations_are_applied() {
+    fn Child(cx: Scope<ChildProps>) -> Element {
+        use_layout_effect(&cx, (&cx.props.value,), |(value,)| {
+            log(format!("effect {}", value));
+            move || log(format!("cleanup {}", value))
+        });
+
+        cx.render(rsx!("{cx.props.value}"))
+    }
+
+    let mut dom = VirtualDom::new_with_props(app, Child);
+    dom.rebuild();
+    assert!(take_log().is_empty());
+
+    dom.mutations_applied();
+    assert_eq!(take_log(), ["effect 0"]);
+
+    // the value changes
+    rerender(&mut dom, ScopeId(0));
+    assert!(take_log().is_empty());
+    dom.mutations_applied();
+    assert_eq!(take_log(), ["cleanup 0", "effect 1"]);
+
+    // the value stays the same
+    rerender(&mut dom, ScopeId(0));
+    dom.mutations_applied();
+    assert!(take_log().is_empty());
+
+    // the child is removed
+    rerender(&mut dom, ScopeId(0));
+    assert_eq!(take_log(), ["cleanup 1"]);
+}
+
+#[tokio::test]
+async fn effects_are_cleaned_up_before_running_again() {
+    fn Child(cx: Scope<ChildProps>) -> Element {
+        use_effect(&cx, (&cx.props.value,), |(value,)| async move {
+            log(format!("effect {}", value));
+            move || log(format!("cleanup {}", value))
+        });
+
+        cx.render(rsx!("{cx.props.value}"))
+    }
+
+    let mut dom = VirtualDom::new_with_props(app, Child);
+    dom.rebuild();
+    run_tasks(&mut dom).await;
+    assert_eq!(take_log(), ["effect 0"]);
+
+    rerender(&mut dom, ScopeId(0));
+    assert_eq!(take_log(), ["cleanup 0"]);
+    run_tasks(&mut dom).await;
+    assert_eq!(take_log(), ["effect 1"]);
+
+    rerender(&mut dom, ScopeId(0));
+    rerender(&mut dom, ScopeId(0));
+    assert_eq!(take_log(), ["cleanup 1"]);
+}
+
+#[tokio::test]
+async fn pending_effects_can_be_cancelled() {
+    struct Guard(u32);
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            log(format!("dropped {}", self.0));
+        }
+    }
+
+    fn Child(cx: Scope<ChildProps>) -> Element {
+        use_effect_with_mode(
+            &cx,
+            EffectMode::Cancel,
+            (&cx.props.value,),
+            |(value,)| async move {
+                let _guard = Guard(value);
+                log(format!("effect {}", value));
+                std::future::pending::<()>().await;
+            },
+        );
+
+        cx.render(rsx!("{cx.props.value}"))
+    }
+
+    let mut dom = VirtualDom::new_with_props(app, Child);
+    dom.rebuild();
+    run_tasks(&mut dom).await;
+    assert_eq!(take_log(), ["effect 0"]);
+
+    rerender(&mut dom, ScopeId(0));
+    run_tasks(&mut dom).await;
+    assert_eq!(take_log(), ["dropped 0", "effect 1"]);
+}
@@ -0,0 +1,282 @@
+#![allow(non_snake_case)]
+
+//! Tests for error boundaries catching panics and thrown errors from their descendants.
+
+use dioxus::prelude::*;
+use dioxus_core::{DomEdit::*, ElementId, MissingValue, RenderPanic, SchedulerMsg};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[inline_props]
+fn Boundary<'a>(cx: Scope, children: Element<'a>) -> Element {
+    let boundary = cx.provide_error_boundary();
+
+    let error = boundary.error().map(|error| error.to_string());
+
+    match error {
+        Some(error) => cx.render(rsx!("caught {error}")),
+        None => cx.render(rsx!(div { children })),
+    }
+}
+
+#[test]
+fn panic_is_caught_by_boundary() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Boundary { Panics {} }))
+    }
+
+    fn Panics(_cx: Scope) -> Element {
+        panic!("boom");
+    }
+
+    let mut dom = VirtualDom::new(app);
+
+    // the failed component renders as a placeholder until the boundary catches up
+    let edits = dom.rebuild();
+    assert_eq!(
+        edits.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            CreatePlaceholder { root: 2 },
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+        ]
+    );
+
+    dom.process_all_messages();
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "caught RenderPanic { message: \"boom\" }", root: 3 },
+            ReplaceWith { root: 1, m: 1 },
+        ]
+    );
+
+    // the failed scope is cleaned up with the rest of the subtree
+    assert!(dom.get_scope(ScopeId(2)).is_none());
+    assert!(dom.get_element(ElementId(1)).is_none());
+    assert!(dom.get_element(ElementId(2)).is_none());
+}
+
+#[test]
+fn thrown_errors_are_typed() {
+    #[derive(Debug, PartialEq)]
+    struct MyError(u32);
+
+    fn app(cx: Scope) -> Element {
+        let boundary = cx.provide_error_boundary();
+
+        if let Some(error) = boundary.error() {
+            assert_eq!(error.downcast_ref::<MyError>(), Some(&MyError(42)));
+            assert_eq!(error.scope_id(), ScopeId(1));
+            return cx.render(rsx!("caught"));
+        }
+
+        cx.render(rsx!(Throws {}))
+    }
+
+    fn Throws(cx: Scope) -> Element {
+        Err(MyError(42)).throw(&cx)?;
+        cx.render(rsx!("unreachable"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.process_all_messages();
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "caught", root: 2 },
+            ReplaceWith { root: 1, m: 1 },
+        ]
+    );
+}
+
+#[test]
+fn missing_context_is_thrown() {
+    #[derive(Clone)]
+    struct Theme;
+
+    fn app(cx: Scope) -> Element {
+        let boundary = cx.provide_error_boundary();
+
+        let error = boundary.error().map(|error| {
+            error
+                .downcast_ref::<MissingValue>()
+                .map(|missing| missing.type_name)
+        });
+
+        match error {
+            Some(name) => {
+                assert!(name.unwrap().ends_with("Theme"));
+                cx.render(rsx!("no theme"))
+            }
+            None => cx.render(rsx!(Themed {})),
+        }
+    }
+
+    fn Themed(cx: Scope) -> Element {
+        let _theme = cx.consume_context::<Theme>().throw(&cx)?;
+        cx.render(rsx!("themed"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.process_all_messages();
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "no theme", root: 2 },
+            ReplaceWith { root: 1, m: 1 },
+        ]
+    );
+}
+
+#[test]
+fn retry_renders_children_again() {
+    static SHOULD_PANIC: AtomicBool = AtomicBool::new(true);
+
+    fn app(cx: Scope) -> Element {
+        let boundary = cx.provide_error_boundary();
+
+        if let Some(error) = boundary.error() {
+            assert_eq!(
+                error.downcast_ref::<RenderPanic>().unwrap().message,
+                "not yet"
+            );
+            return cx.render(rsx!("fallback"));
+        }
+
+        cx.render(rsx!(Flaky {}))
+    }
+
+    fn Flaky(cx: Scope) -> Element {
+        if SHOULD_PANIC.load(Ordering::SeqCst) {
+            panic!("not yet");
+        }
+        cx.render(rsx!("recovered"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.process_all_messages();
+    dom.work_with_deadline(|| false);
+
+    SHOULD_PANIC.store(false, Ordering::SeqCst);
+
+    let boundary = dom.base_scope().provide_error_boundary();
+    assert!(boundary.has_error());
+    boundary.retry();
+    assert!(!boundary.has_error());
+
+    dom.process_all_messages();
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "recovered", root: 1 },
+            ReplaceWith { root: 2, m: 1 },
+        ]
+    );
+}
+
+#[test]
+fn nearest_boundary_wins() {
+    fn app(cx: Scope) -> Element {
+        let boundary = cx.provide_error_boundary();
+        assert!(!boundary.has_error());
+        cx.render(rsx!(Boundary { Boundary { Panics {} } }))
+    }
+
+    fn Panics(_cx: Scope) -> Element {
+        panic!("inner");
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.process_all_messages();
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "caught RenderPanic { message: \"inner\" }", root: 4 },
+            ReplaceWith { root: 2, m: 1 },
+        ]
+    );
+
+    // the outer boundary never saw the error
+    let outer = dom.get_scope(ScopeId(1)).unwrap().provide_error_boundary();
+    assert!(!outer.has_error());
+}
+
+#[test]
+#[should_panic(expected = "no boundary")]
+fn panics_without_boundary_propagate() {
+    fn app(_cx: Scope) -> Element {
+        panic!("no boundary");
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+}
+
+#[test]
+fn fallback_replaces_the_whole_failed_subtree() {
+    static SHOULD_PANIC: AtomicBool = AtomicBool::new(false);
+
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Boundary { Flaky {} }))
+    }
+
+    fn Flaky(cx: Scope) -> Element {
+        if SHOULD_PANIC.load(Ordering::SeqCst) {
+            panic!("later");
+        }
+        cx.render(rsx!(ul { li { "a" } Leaf {} }))
+    }
+
+    fn Leaf(cx: Scope) -> Element {
+        cx.render(rsx!(li { "b" }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<div><ul><li>a</li><li>b</li></ul></div>"
+    );
+
+    // the component fails on a later render, after it has mounted nodes and children of its own
+    SHOULD_PANIC.store(true, Ordering::SeqCst);
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(2)));
+    let edits = dom.work_with_deadline(|| false);
+
+    // its nodes stay mounted until the boundary replaces them, in the same batch
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateTextNode { text: "caught RenderPanic { message: \"later\" }", root: 7 },
+            ReplaceWith { root: 1, m: 1 },
+        ]
+    );
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "caught RenderPanic { message: \"later\" }"
+    );
+
+    // nothing from the failed subtree is left behind
+    assert!(dom.get_scope(ScopeId(2)).is_none());
+    assert!(dom.get_scope(ScopeId(3)).is_none());
+    for id in 1..7 {
+        assert!(dom.get_element(ElementId(id)).is_none());
+    }
+
+    dom.process_all_messages();
+    assert!(dom.work_with_deadline(|| false).is_empty());
+}
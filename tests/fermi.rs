@@ -3,6 +3,9 @@
 use dioxus::prelude::*;
 use fermi::*;
 
+mod test_utils;
+use test_utils::{run, update};
+
 #[test]
 fn test_fermi() {
     let mut app = VirtualDom::new(App);
@@ -44,3 +47,562 @@ fn Leaf(cx: Scope<LeafProps>) -> Element {
         }
     })
 }
+
+mod selectors {
+    use super::*;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::Arc,
+    };
+
+    thread_local! {
+        static COMPUTED: Cell<usize> = Cell::new(0);
+        static RENDERS: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+        static ROOT: RefCell<Option<Rc<AtomRoot>>> = RefCell::new(None);
+    }
+
+    static NUMBER: Atom<u32> = |_| 2;
+    static IS_EVEN: Selector<bool> = |s| s.get(NUMBER) % 2 == 0;
+    static DOUBLED: Selector<u32> = |s| {
+        COMPUTED.with(|computed| computed.set(computed.get() + 1));
+        s.get(NUMBER) * 2
+    };
+    static QUADRUPLED: Selector<u32> = |s| s.get(DOUBLED) * 2;
+    static TIMES: SelectorFamily<u32, u32> = |s, by| s.get(NUMBER) * by;
+
+    fn computed() -> usize {
+        COMPUTED.with(|computed| computed.take())
+    }
+
+    fn take_renders() -> Vec<&'static str> {
+        RENDERS.with(|renders| renders.borrow_mut().drain(..).collect())
+    }
+
+    fn set_number(dom: &mut VirtualDom, number: u32) {
+        ROOT.with(|root| {
+            let root = root.borrow();
+            root.as_ref().unwrap().set(NUMBER.unique_id(), number)
+        });
+        update(dom);
+    }
+
+    #[test]
+    fn selectors_are_computed_lazily() {
+        let root = AtomRoot::new(Arc::new(|_| {}));
+
+        root.initialize(QUADRUPLED);
+        assert_eq!(computed(), 0);
+
+        assert_eq!(*root.read(QUADRUPLED), 8);
+        assert_eq!(computed(), 1);
+
+        // nothing reads the selectors, so they're only recomputed once they're read again
+        root.set(NUMBER.unique_id(), 3u32);
+        root.set(NUMBER.unique_id(), 4u32);
+        assert_eq!(computed(), 0);
+        assert_eq!(*root.read(QUADRUPLED), 16);
+        assert_eq!(*root.read(DOUBLED), 8);
+        assert_eq!(computed(), 1);
+    }
+
+    #[test]
+    fn selectors_can_be_read_directly() {
+        let root = AtomRoot::new(Arc::new(|_| {}));
+
+        assert_eq!(QUADRUPLED.read(&root), Some(8));
+        assert_eq!((TIMES, 3).read(&root), Some(6));
+
+        // a stale value is recomputed rather than returned
+        root.set(NUMBER.unique_id(), 3u32);
+        assert_eq!(QUADRUPLED.read(&root), Some(12));
+        assert_eq!((TIMES, 3).read(&root), Some(9));
+    }
+
+    #[test]
+    fn only_changed_selectors_rerender() {
+        fn app(cx: Scope) -> Element {
+            let root = use_atom_root(&cx);
+            ROOT.with(|cell| *cell.borrow_mut() = Some(root.clone()));
+            cx.render(rsx!(Even {} Doubled {}))
+        }
+
+        fn Even(cx: Scope) -> Element {
+            RENDERS.with(|renders| renders.borrow_mut().push("even"));
+            let even = use_read(&cx, IS_EVEN);
+            cx.render(rsx!("{even}"))
+        }
+
+        fn Doubled(cx: Scope) -> Element {
+            RENDERS.with(|renders| renders.borrow_mut().push("doubled"));
+            let doubled = use_read(&cx, DOUBLED);
+            cx.render(rsx!("{doubled}"))
+        }
+
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(take_renders(), ["even", "doubled"]);
+
+        set_number(&mut dom, 4);
+        assert_eq!(take_renders(), ["doubled"]);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "true<!--spacer-->8");
+
+        set_number(&mut dom, 5);
+        let mut renders = take_renders();
+        renders.sort_unstable();
+        assert_eq!(renders, ["doubled", "even"]);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "false<!--spacer-->10");
+
+        // setting the same value again doesn't change either selector
+        set_number(&mut dom, 5);
+        assert!(take_renders().is_empty());
+    }
+
+    #[test]
+    fn selector_families_are_keyed() {
+        fn app(cx: Scope) -> Element {
+            let root = use_atom_root(&cx);
+            ROOT.with(|cell| *cell.borrow_mut() = Some(root.clone()));
+
+            let double = use_read(&cx, (TIMES, 2));
+            let triple = use_read(&cx, (TIMES, 3));
+            cx.render(rsx!("{double} {triple}"))
+        }
+
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "4 6");
+
+        set_number(&mut dom, 5);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "10 15");
+    }
+}
+
+mod families {
+    use super::*;
+    use im_rc::HashMap as ImMap;
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+    static NAMES: AtomFamily<u32, &'static str> = |_| ImMap::unit(0, "ann").update(1, "bob");
+
+    thread_local! {
+        static RENDERS: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+        static ROOT: RefCell<Option<Rc<AtomRoot>>> = RefCell::new(None);
+    }
+
+    fn root() -> Rc<AtomRoot> {
+        ROOT.with(|root| root.borrow().clone().unwrap())
+    }
+
+    fn take_renders() -> Vec<u32> {
+        RENDERS.with(|renders| {
+            let mut renders = renders.take();
+            renders.sort_unstable();
+            renders
+        })
+    }
+
+    fn app(cx: Scope) -> Element {
+        let root = use_atom_root(&cx);
+        ROOT.with(|cell| *cell.borrow_mut() = Some(root.clone()));
+        cx.render(rsx!(Name { id: 0 } Name { id: 1 }))
+    }
+
+    #[inline_props]
+    fn Name(cx: Scope, id: u32) -> Element {
+        RENDERS.with(|renders| renders.borrow_mut().push(*id));
+        let name = use_atom_family(&cx, NAMES, *id);
+        let name = name.get().copied().unwrap_or("nobody");
+        cx.render(rsx!("{name}"))
+    }
+
+    #[test]
+    fn writing_a_key_rerenders_only_its_readers() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(take_renders(), [0, 1]);
+
+        root().set_member(NAMES, 0, "amy");
+        update(&mut dom);
+        assert_eq!(take_renders(), [0]);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "amy<!--spacer-->bob");
+
+        // removed keys read as missing
+        assert_eq!(root().remove_member(NAMES, &1), Some("bob"));
+        update(&mut dom);
+        assert_eq!(take_renders(), [1]);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "amy<!--spacer-->nobody");
+
+        // replacing the whole family re-renders every key
+        NAMES.write(&root(), ImMap::unit(1, "cat"));
+        update(&mut dom);
+        assert_eq!(take_renders(), [0, 1]);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "nobody<!--spacer-->cat");
+    }
+
+    #[test]
+    fn removed_keys_forget_their_subscribers() {
+        let updated = Rc::new(RefCell::new(Vec::new()));
+        let root = AtomRoot::new(Arc::new({
+            let updated = updated.clone();
+            move |scope| updated.borrow_mut().push(scope)
+        }));
+        let member = |key: u32| AtomId::with_key(NAMES as *const (), key);
+
+        assert_eq!(root.register_member(NAMES, &0, ScopeId(1)), Some("ann"));
+        assert_eq!(root.register_member(NAMES, &1, ScopeId(2)), Some("bob"));
+
+        // the subscribers of the key are notified one last time
+        assert_eq!(root.remove_member(NAMES, &0), Some("ann"));
+        assert_eq!(root.remove_member(NAMES, &0), None);
+        assert_eq!(updated.take(), [ScopeId(1)]);
+        assert!(!root.atoms.borrow().contains_key(&member(0)));
+
+        root.set_member(NAMES, 0, "amy");
+        assert!(updated.take().is_empty());
+
+        // keys nobody reads anymore are dropped too
+        root.unsubscribe(member(1), ScopeId(2));
+        assert!(!root.atoms.borrow().contains_key(&member(1)));
+        assert_eq!(root.read_member(NAMES, &0), Some("amy"));
+    }
+}
+
+mod async_selectors {
+    use super::*;
+    use dioxus_core::exports::futures_channel::oneshot;
+    use std::{cell::RefCell, rc::Rc};
+
+    type Reply = oneshot::Sender<Result<String, String>>;
+
+    thread_local! {
+        static REQUESTS: RefCell<Vec<(u32, Reply)>> = RefCell::new(Vec::new());
+        static ROOT: RefCell<Option<Rc<AtomRoot>>> = RefCell::new(None);
+    }
+
+    static USER_ID: Atom<u32> = |_| 0;
+    static USER: AsyncSelector<String, String> = |s| {
+        let id = s.get(USER_ID);
+        let (tx, rx) = oneshot::channel();
+        REQUESTS.with(|requests| requests.borrow_mut().push((id, tx)));
+        Box::pin(async move { rx.await.unwrap() })
+    };
+
+    fn take_requests() -> Vec<(u32, Reply)> {
+        REQUESTS.with(|requests| requests.take())
+    }
+
+    fn app(cx: Scope) -> Element {
+        let root = use_atom_root(&cx);
+        ROOT.with(|cell| *cell.borrow_mut() = Some(root.clone()));
+
+        let user = match use_read(&cx, USER).state() {
+            UseFutureState::Pending => "loading".to_string(),
+            UseFutureState::Complete(Ok(name)) => name.clone(),
+            UseFutureState::Reloading(Ok(name)) => format!("{} (reloading)", name),
+            UseFutureState::Complete(Err(error)) | UseFutureState::Reloading(Err(error)) => {
+                format!("error: {}", error)
+            }
+        };
+
+        cx.render(rsx!("{user}"))
+    }
+
+    fn set_user_id(dom: &mut VirtualDom, id: u32) {
+        ROOT.with(|root| root.borrow().as_ref().unwrap().set(USER_ID.unique_id(), id));
+        update(dom);
+    }
+
+    #[tokio::test]
+    async fn async_selectors_resolve_in_tasks() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "loading");
+
+        let root = ROOT.with(|root| root.borrow().clone().unwrap());
+        assert!(USER.read(&root).is_none());
+
+        let (id, reply) = take_requests().pop().unwrap();
+        assert_eq!(id, 0);
+        reply.send(Ok("ann".to_string())).unwrap();
+        run(&mut dom).await;
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "ann");
+        assert_eq!(USER.read(&root).unwrap().value().unwrap(), "ann");
+
+        // failures are kept as the selector's error
+        set_user_id(&mut dom, 1);
+        let (_, reply) = take_requests().pop().unwrap();
+        reply.send(Err("offline".to_string())).unwrap();
+        run(&mut dom).await;
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "error: offline");
+    }
+
+    #[tokio::test]
+    async fn async_selectors_restart_when_dependencies_change() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        let (_, reply) = take_requests().pop().unwrap();
+        reply.send(Ok("ann".to_string())).unwrap();
+        run(&mut dom).await;
+
+        // the last value is kept while the new request runs
+        set_user_id(&mut dom, 1);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "ann (reloading)");
+
+        // changing the dependency again cancels the running request
+        set_user_id(&mut dom, 2);
+        let mut requests = take_requests();
+        let (id, reply) = requests.pop().unwrap();
+        let (_, cancelled) = requests.pop().unwrap();
+        assert_eq!(id, 2);
+        assert!(cancelled.is_canceled());
+
+        reply.send(Ok("cat".to_string())).unwrap();
+        run(&mut dom).await;
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "cat");
+    }
+}
+
+mod callbacks {
+    use super::*;
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        sync::Arc,
+    };
+
+    static FIRST: Atom<&'static str> = |_| "ann";
+    static LAST: Atom<&'static str> = |_| "smith";
+    static FULL: Selector<String> = |s| {
+        COMPUTED.with(|computed| computed.set(computed.get() + 1));
+        format!("{} {}", s.get(FIRST), s.get(LAST))
+    };
+
+    thread_local! {
+        static COMPUTED: Cell<usize> = Cell::new(0);
+        static API: RefCell<Option<CallbackApi>> = RefCell::new(None);
+        static RENDERS: RefCell<Vec<&'static str>> = RefCell::new(Vec::new());
+    }
+
+    fn api() -> CallbackApi {
+        API.with(|api| api.borrow().clone().unwrap())
+    }
+
+    fn take_renders() -> Vec<&'static str> {
+        RENDERS.with(|renders| renders.take())
+    }
+
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Writer {} Reader {}))
+    }
+
+    fn Writer(cx: Scope) -> Element {
+        RENDERS.with(|renders| renders.borrow_mut().push("writer"));
+        let api = use_atom_context(&cx);
+        API.with(|cell| *cell.borrow_mut() = Some(api.clone()));
+        None
+    }
+
+    fn Reader(cx: Scope) -> Element {
+        RENDERS.with(|renders| renders.borrow_mut().push("reader"));
+        let full = use_read(&cx, FULL);
+        cx.render(rsx!("{full}"))
+    }
+
+    #[test]
+    fn reading_through_the_api_does_not_subscribe() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(take_renders(), ["writer", "reader"]);
+
+        assert_eq!(api().get(FIRST), "ann");
+        assert_eq!(*api().get_rc(FULL), "ann smith");
+
+        api().set(FIRST, "bob");
+        update(&mut dom);
+        assert_eq!(take_renders(), ["reader"]);
+        assert_eq!(
+            dioxus::ssr::render_vdom(&dom),
+            "<!--placeholder-->bob smith"
+        );
+    }
+
+    #[test]
+    fn transactions_notify_once() {
+        let updated = Rc::new(RefCell::new(Vec::new()));
+        let root = AtomRoot::new(Arc::new({
+            let updated = updated.clone();
+            move |scope| updated.borrow_mut().push(scope)
+        }));
+
+        root.register(FIRST, ScopeId(1));
+        root.register(LAST, ScopeId(1));
+        root.register(FULL, ScopeId(1));
+        COMPUTED.with(|computed| computed.take());
+
+        let full = root.transaction(|| {
+            root.set(FIRST.unique_id(), "cat");
+            root.set(LAST.unique_id(), "jones");
+            assert!(updated.borrow().is_empty());
+
+            // reads see the writes made so far
+            root.read(FULL)
+        });
+
+        assert_eq!(*full, "cat jones");
+        assert_eq!(updated.take(), [ScopeId(1)]);
+        assert_eq!(COMPUTED.with(|computed| computed.take()), 1);
+    }
+
+    #[tokio::test]
+    async fn the_api_can_be_used_from_tasks() {
+        fn app(cx: Scope) -> Element {
+            let api = use_atom_context(&cx);
+            cx.use_hook(|_| {
+                let api = api.clone();
+                cx.spawn(async move {
+                    std::future::ready(()).await;
+                    api.transaction(|api| {
+                        api.set(FIRST, "dan");
+                        api.set(LAST, "brown");
+                    });
+                })
+            });
+
+            let full = use_read(&cx, FULL);
+            cx.render(rsx!("{full}"))
+        }
+
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "ann smith");
+
+        run(&mut dom).await;
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "dan brown");
+    }
+}
+
+mod persistence {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+    static NAME: Atom<String> = |_| "ann".to_string();
+    static COUNT: Atom<u32> = |_| 0;
+    static SCRATCH: Atom<u32> = |_| 0;
+
+    thread_local! {
+        static ROOT: RefCell<Option<Rc<AtomRoot>>> = RefCell::new(None);
+    }
+
+    fn root() -> Rc<AtomRoot> {
+        ROOT.with(|root| root.borrow().clone().unwrap())
+    }
+
+    fn detached_root() -> AtomRoot {
+        let root = AtomRoot::new(Arc::new(|_| {}));
+        root.persist(NAME, "name");
+        root.persist(COUNT, "count");
+        root
+    }
+
+    fn app(cx: Scope) -> Element {
+        let root = use_init_atom_root(&cx);
+        cx.use_hook(|_| {
+            root.persist(NAME, "name");
+            root.persist(COUNT, "count");
+            let server = Snapshot::from_json(r#"{"name":"bob","count":3}"#).unwrap();
+            root.restore(&server).unwrap();
+            ROOT.with(|cell| *cell.borrow_mut() = Some(root.clone()));
+        });
+
+        let name = use_read(&cx, NAME);
+        let count = use_read(&cx, COUNT);
+        cx.render(rsx!("{name}: {count}"))
+    }
+
+    #[test]
+    fn snapshots_only_include_persisted_atoms() {
+        let root = detached_root();
+        root.initialize(SCRATCH);
+        root.set(COUNT.unique_id(), 2u32);
+        root.set(SCRATCH.unique_id(), 5u32);
+
+        let snapshot = root.snapshot().unwrap();
+        assert_eq!(snapshot.keys().collect::<Vec<_>>(), ["count", "name"]);
+        assert_eq!(snapshot.to_json().unwrap(), r#"{"count":2,"name":"ann"}"#);
+        assert_eq!(
+            Snapshot::from_json(&snapshot.to_json().unwrap()).unwrap(),
+            snapshot
+        );
+    }
+
+    #[test]
+    fn invalid_snapshots_restore_nothing() {
+        let root = detached_root();
+        let snapshot = Snapshot::from_json(r#"{"name":"bob","count":"three"}"#).unwrap();
+
+        assert!(matches!(
+            root.restore(&snapshot),
+            Err(PersistError::Json(_))
+        ));
+        assert_eq!(*root.read(NAME), "ann");
+    }
+
+    #[test]
+    fn restoring_hydrates_and_travels_back_in_time() {
+        let mut dom = VirtualDom::new(app);
+        dom.rebuild();
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "bob: 3");
+
+        let before = root().snapshot().unwrap();
+        root().set(NAME.unique_id(), "cat".to_string());
+        root().set(COUNT.unique_id(), 4u32);
+        update(&mut dom);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "cat: 4");
+
+        root().restore(&before).unwrap();
+        update(&mut dom);
+        assert_eq!(dioxus::ssr::render_vdom(&dom), "bob: 3");
+    }
+
+    #[test]
+    fn writes_are_saved_to_the_storage() {
+        let storage = MemoryStorage::new();
+        let root = detached_root();
+        root.persist_to(storage.clone()).unwrap();
+        assert_eq!(storage.snapshot(), None);
+
+        root.set(COUNT.unique_id(), 1u32);
+        assert_eq!(storage.snapshot().unwrap().get("count"), Some(&1.into()));
+
+        // transactions save once they're done
+        root.transaction(|| {
+            root.set(NAME.unique_id(), "dan".to_string());
+            root.set(COUNT.unique_id(), 2u32);
+            assert_eq!(storage.snapshot().unwrap().get("count"), Some(&1.into()));
+        });
+        assert_eq!(
+            storage.snapshot().unwrap().to_json().unwrap(),
+            r#"{"count":2,"name":"dan"}"#
+        );
+
+        // a new session starts from the saved state
+        let next = detached_root();
+        next.persist_to(storage).unwrap();
+        assert_eq!(*next.read(NAME), "dan");
+    }
+
+    #[test]
+    fn files_keep_state_between_sessions() {
+        let path = std::env::temp_dir().join(format!("fermi-{}.json", std::process::id()));
+
+        let root = detached_root();
+        root.persist_to(FileStorage::new(&path)).unwrap();
+        root.set(COUNT.unique_id(), 7u32);
+
+        let next = detached_root();
+        next.persist_to(FileStorage::new(&path)).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(*next.read(COUNT), 7);
+    }
+}
@@ -3,7 +3,7 @@
 
 //! Tests for the lifecycle of components.
 use dioxus::prelude::*;
-use dioxus_core::DomEdit::*;
+use dioxus_core::{DomEdit::*, SchedulerMsg};
 use std::sync::{Arc, Mutex};
 
 mod test_logging;
@@ -262,3 +262,141 @@ fn component_swap() {
     let edits = dom.work_with_deadline(|| false);
     dbg!(&edits);
 }
+
+thread_local! {
+    static LOG: std::cell::RefCell<Vec<&'static str>> = Default::default();
+    static SHOW: std::cell::Cell<bool> = std::cell::Cell::new(true);
+}
+
+fn log(event: &'static str) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+fn take_log() -> Vec<&'static str> {
+    LOG.with(|log| log.take())
+}
+
+/// Logs when the hooks of a component are dropped
+struct DropLogger(&'static str);
+impl Drop for DropLogger {
+    fn drop(&mut self) {
+        log(self.0);
+    }
+}
+
+fn lifecycle_app(cx: Scope) -> Element {
+    use_on_mount(&cx, || log("app mounted"));
+    use_on_destroy(&cx, || log("app destroyed"));
+
+    let show = SHOW.with(|show| show.get());
+    cx.render(rsx!(
+        div {
+            show.then(|| rsx!(LifecycleParent {}))
+        }
+    ))
+}
+
+fn LifecycleParent(cx: Scope) -> Element {
+    use_on_mount(&cx, || log("parent mounted"));
+    use_on_destroy(&cx, || log("parent destroyed"));
+    cx.use_hook(|_| DropLogger("parent dropped"));
+
+    cx.render(rsx!(
+        div { LifecycleChild {} }
+    ))
+}
+
+fn LifecycleChild(cx: Scope) -> Element {
+    use_on_mount(&cx, || log("child mounted"));
+    use_on_destroy(&cx, || log("child destroyed"));
+    cx.use_hook(|_| DropLogger("child dropped"));
+
+    cx.render(rsx!("child"))
+}
+
+fn set_show(dom: &mut VirtualDom, show: bool) {
+    SHOW.with(|cell| cell.set(show));
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+}
+
+#[test]
+fn mount_callbacks_run_after_edits_are_applied() {
+    let mut dom = VirtualDom::new(lifecycle_app);
+
+    dom.rebuild();
+    assert!(take_log().is_empty());
+
+    dom.mutations_applied();
+    assert_eq!(
+        take_log(),
+        ["child mounted", "parent mounted", "app mounted"]
+    );
+
+    // mount callbacks only run once
+    set_show(&mut dom, true);
+    dom.work_with_deadline(|| false);
+    dom.mutations_applied();
+    assert!(take_log().is_empty());
+}
+
+#[test]
+fn teardown_runs_children_before_parents() {
+    let mut dom = VirtualDom::new(lifecycle_app);
+    dom.rebuild();
+    dom.mutations_applied();
+    take_log();
+
+    // the callbacks run before the renderer gets the edits that remove the nodes
+    set_show(&mut dom, false);
+    let edits = dom.work_with_deadline(|| false);
+    assert!(matches!(
+        edits[0].edits[..],
+        [CreatePlaceholder { .. }, ReplaceWith { .. }]
+    ));
+    drop(edits);
+
+    assert_eq!(
+        take_log(),
+        [
+            "child destroyed",
+            "child dropped",
+            "parent destroyed",
+            "parent dropped"
+        ]
+    );
+
+    drop(dom);
+    assert_eq!(take_log(), ["app destroyed"]);
+}
+
+#[test]
+fn removed_components_never_mount() {
+    let mut dom = VirtualDom::new(lifecycle_app);
+    dom.rebuild();
+
+    // the subtree is removed before the renderer applied the edits creating it
+    set_show(&mut dom, false);
+    dom.work_with_deadline(|| false);
+    take_log();
+
+    dom.mutations_applied();
+    assert_eq!(take_log(), ["app mounted"]);
+}
+
+#[test]
+fn dropping_the_dom_destroys_every_component() {
+    let mut dom = VirtualDom::new(lifecycle_app);
+    dom.rebuild();
+
+    drop(dom);
+    assert_eq!(
+        take_log(),
+        [
+            "child destroyed",
+            "child dropped",
+            "parent destroyed",
+            "parent dropped",
+            "app destroyed"
+        ]
+    );
+}
@@ -0,0 +1,190 @@
+#![allow(non_snake_case)]
+
+//! Tests for tuning how props are memoized.
+//!
+//! The parent re-renders on every update, so each test counts how many of the child's renders were skipped.
+
+use dioxus::prelude::*;
+use std::{cell::Cell, rc::Rc};
+
+mod test_utils;
+use test_utils::rerender;
+
+thread_local! {
+    static CHILD_RENDERS: Cell<usize> = Cell::new(0);
+}
+
+/// Count a render of the child under test
+fn rendered() {
+    CHILD_RENDERS.with(|renders| renders.set(renders.get() + 1));
+}
+
+/// Re-render the root `times` times and return how many of the child's renders were skipped by memoization
+fn skipped_renders(dom: &mut VirtualDom, times: usize) -> usize {
+    CHILD_RENDERS.with(|renders| renders.set(0));
+    for _ in 0..times {
+        rerender(dom, ScopeId(0));
+    }
+    times - CHILD_RENDERS.with(|renders| renders.get())
+}
+
+#[test]
+fn equal_props_skip_renders() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(Child { value: *count / 2 }))
+    }
+
+    #[inline_props]
+    fn Child(cx: Scope, value: i32) -> Element {
+        rendered();
+        cx.render(rsx!("{value}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    // the value only changes every other render
+    assert_eq!(skipped_renders(&mut dom, 4), 2);
+}
+
+#[derive(Props, PartialEq)]
+struct SkippedProps {
+    title: &'static str,
+
+    #[props(skip_compare)]
+    renders: u32,
+}
+
+fn Skipped(cx: Scope<SkippedProps>) -> Element {
+    rendered();
+    cx.render(rsx!("{cx.props.title}"))
+}
+
+#[test]
+fn skipped_fields_are_not_compared() {
+    fn app(cx: Scope) -> Element {
+        let renders = cx.use_hook(|_| 0);
+        *renders += 1;
+
+        cx.render(rsx!(Skipped { title: "hello", renders: *renders }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    assert_eq!(skipped_renders(&mut dom, 3), 3);
+}
+
+#[derive(Props)]
+#[props(memo = same_id)]
+struct CustomProps {
+    id: u32,
+    label: String,
+}
+
+fn same_id(old: &CustomProps, new: &CustomProps) -> bool {
+    old.id == new.id
+}
+
+fn Custom(cx: Scope<CustomProps>) -> Element {
+    rendered();
+    cx.render(rsx!("{cx.props.label}"))
+}
+
+#[test]
+fn custom_comparisons_are_used() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(Custom {
+            id: *count / 3,
+            label: format!("render {}", count)
+        }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    // the id changes on the 3rd and 6th render
+    assert_eq!(skipped_renders(&mut dom, 6), 4);
+}
+
+#[derive(Props, PartialEq)]
+#[props(no_memo)]
+struct AlwaysProps {
+    value: i32,
+}
+
+fn Always(cx: Scope<AlwaysProps>) -> Element {
+    rendered();
+    cx.render(rsx!("{cx.props.value}"))
+}
+
+#[test]
+fn no_memo_always_renders() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Always { value: 0 }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    assert_eq!(skipped_renders(&mut dom, 3), 0);
+
+    // the child renders exactly once for each render of the root
+    CHILD_RENDERS.with(|renders| renders.set(0));
+    rerender(&mut dom, ScopeId(0));
+    assert_eq!(CHILD_RENDERS.with(|renders| renders.get()), 1);
+}
+
+#[derive(Props, PartialEq)]
+struct ButtonProps {
+    press: Memo<dyn Fn(u32)>,
+}
+
+fn Button(cx: Scope<ButtonProps>) -> Element {
+    rendered();
+    cx.props.press.call(1);
+    cx.render(rsx!("button"))
+}
+
+#[test]
+fn callbacks_are_compared_by_identity() {
+    thread_local! {
+        static PRESSES: Rc<Cell<u32>> = Rc::new(Cell::new(0));
+    }
+
+    fn app(cx: Scope) -> Element {
+        let press = cx.use_hook(|_| {
+            Memo::callback(|n| PRESSES.with(|presses| presses.set(presses.get() + n)))
+        });
+
+        cx.render(rsx!(Button { press: press.clone() }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    assert_eq!(skipped_renders(&mut dom, 3), 3);
+    assert_eq!(PRESSES.with(|presses| presses.get()), 1);
+}
+
+#[test]
+fn new_callbacks_are_not_equal() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Button { press: Memo::callback(|_| {}) }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    assert_eq!(skipped_renders(&mut dom, 3), 0);
+
+    let memo = Memo::new(10);
+    assert_eq!(memo, memo.clone());
+    assert_ne!(memo, Memo::new(10));
+}
@@ -0,0 +1,185 @@
+#![allow(non_snake_case)]
+
+//! Tests for use_model: tasks that write to the model, their statuses, and cancelling them on unmount.
+
+use dioxus::prelude::*;
+use futures_util::StreamExt;
+use std::{
+    cell::Cell,
+    future::{pending, ready},
+};
+
+mod test_utils;
+use test_utils::{rerender, run};
+
+#[derive(Default)]
+struct Form {
+    email: String,
+    available: Option<bool>,
+}
+
+#[tokio::test]
+async fn tasks_write_to_the_model() {
+    fn app(cx: Scope) -> Element {
+        let form = use_model(&cx, || Form {
+            email: "me@example.com".to_string(),
+            ..Default::default()
+        });
+
+        cx.use_hook(|_| {
+            form.start(|form| async move {
+                let available = ready(form.read().email.ends_with(".com")).await;
+                form.write().available = Some(available);
+            })
+        });
+
+        let status = match (form.is_loading(), form.read().available) {
+            (true, _) => "checking",
+            (false, Some(true)) => "available",
+            (false, _) => "taken",
+        };
+
+        cx.render(rsx!("{status}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "checking");
+
+    run(&mut dom).await;
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "available");
+}
+
+#[tokio::test]
+async fn failed_tasks_keep_their_error() {
+    fn app(cx: Scope) -> Element {
+        let form = use_model(&cx, Form::default);
+
+        let task =
+            *cx.use_hook(|_| form.try_start(|_| async move { Err::<(), _>("the server is down") }));
+
+        let error = form.error().unwrap_or_default();
+        assert_eq!(form.tasks(), [(task, form.status(task).unwrap())]);
+
+        cx.render(rsx!("{error}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "");
+
+    run(&mut dom).await;
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "the server is down");
+}
+
+#[tokio::test]
+async fn statuses_can_be_cleared() {
+    thread_local! {
+        static STATUSES: Cell<Option<(Option<TaskStatus>, Option<TaskStatus>, usize)>> = Cell::new(None);
+    }
+
+    fn app(cx: Scope) -> Element {
+        let form = use_model(&cx, Form::default);
+
+        let (done, running) = *cx.use_hook(|_| {
+            let done = form.start(|_| ready(()));
+            let running = form.start(|_| pending());
+            (done, running)
+        });
+
+        let cleared = cx.use_hook(|_| false);
+        if form.status(done) == Some(TaskStatus::Finished) && !*cleared {
+            *cleared = true;
+            form.clear_finished();
+        }
+
+        STATUSES.with(|statuses| {
+            statuses.set(Some((
+                form.status(done),
+                form.status(running),
+                form.tasks().len(),
+            )))
+        });
+
+        None
+    }
+
+    let statuses = || STATUSES.with(|statuses| statuses.take()).unwrap();
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(
+        statuses(),
+        (Some(TaskStatus::Running), Some(TaskStatus::Running), 2)
+    );
+
+    // finished tasks are forgotten, running ones are kept
+    run(&mut dom).await;
+    assert_eq!(statuses(), (None, Some(TaskStatus::Running), 1));
+}
+
+#[tokio::test]
+async fn tasks_are_cancelled_on_unmount() {
+    thread_local! {
+        static DROPPED: Cell<bool> = Cell::new(false);
+    }
+
+    struct DropGuard;
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            DROPPED.with(|dropped| dropped.set(true));
+        }
+    }
+
+    fn app(cx: Scope) -> Element {
+        let show = cx.use_hook(|_| true);
+        let shown = *show;
+        *show = false;
+
+        cx.render(rsx!(shown.then(|| rsx!(Child {}))))
+    }
+
+    fn Child(cx: Scope) -> Element {
+        let model = use_model(&cx, || ());
+        cx.use_hook(|_| {
+            let guard = DropGuard;
+            model.start(|_| async move {
+                let _guard = guard;
+                pending::<()>().await;
+            })
+        });
+        None
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert!(!DROPPED.with(|dropped| dropped.get()));
+
+    rerender(&mut dom, ScopeId(0));
+    assert!(DROPPED.with(|dropped| dropped.get()));
+}
+
+#[tokio::test]
+async fn coroutines_receive_messages() {
+    fn app(cx: Scope) -> Element {
+        let form = use_model(&cx, Form::default);
+
+        let typing = use_model_coroutine(&cx, form, |form, mut rx| async move {
+            while let Some(key) = rx.next().await {
+                form.write().email.push(key);
+            }
+        });
+
+        cx.use_hook(|_| "me@".chars().for_each(|key| typing.send(key)));
+
+        let email = form.read().email.clone();
+        cx.render(rsx!("{email}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "");
+
+    run(&mut dom).await;
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "me@");
+}
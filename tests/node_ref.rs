@@ -0,0 +1,114 @@
+#![allow(non_snake_case)]
+
+//! Tests for attaching NodeRefs to elements.
+
+use dioxus::prelude::*;
+use dioxus_core::{ElementId, Mutations};
+
+mod test_utils;
+use test_utils::rerender;
+
+/// The refs a batch of mutations mounted
+fn mounted_refs(mutations: &[Mutations]) -> Vec<(NodeRef, ElementId)> {
+    mutations
+        .iter()
+        .flat_map(|m| &m.refs)
+        .map(|r| (r.node_ref.clone(), r.element_id))
+        .collect()
+}
+
+#[test]
+fn refs_are_listed_once_mounted() {
+    fn app(cx: Scope) -> Element {
+        let node_ref = use_node_ref(&cx);
+
+        cx.render(rsx!(
+            div {
+                input { node_ref: node_ref }
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+
+    assert_eq!(mutations.refs.len(), 1);
+    let node_ref = mutations.refs[0].node_ref.clone();
+    assert_eq!(mutations.refs[0].element_id, ElementId(2));
+    assert_eq!(node_ref.id(), Some(ElementId(2)));
+
+    // the renderer provides the handle
+    assert!(node_ref.get::<String>().is_none());
+    node_ref.set("input".to_string());
+    assert_eq!(*node_ref.get::<String>().unwrap(), "input");
+    assert!(node_ref.get::<u32>().is_none());
+
+    // re-rendering the same element keeps the handle
+    assert!(mounted_refs(&rerender(&mut dom, ScopeId(0))).is_empty());
+    assert_eq!(*node_ref.get::<String>().unwrap(), "input");
+}
+
+#[test]
+fn refs_are_cleared_when_the_element_is_removed() {
+    fn app(cx: Scope) -> Element {
+        let node_ref = use_node_ref(&cx);
+        let show = cx.use_hook(|_| true);
+        let showing = *show;
+        *show = false;
+
+        cx.render(rsx!(
+            div {
+                showing.then(|| rsx!(input { node_ref: node_ref }))
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+    let node_ref = mutations.refs[0].node_ref.clone();
+    node_ref.set(());
+
+    assert!(mounted_refs(&rerender(&mut dom, ScopeId(0))).is_empty());
+    assert_eq!(node_ref.id(), None);
+    assert!(node_ref.get::<()>().is_none());
+}
+
+#[test]
+fn refs_follow_the_element_they_are_attached_to() {
+    fn app(cx: Scope) -> Element {
+        let node_ref = use_node_ref(&cx);
+        let first = cx.use_hook(|_| true);
+        let on_first = *first;
+        *first = false;
+
+        match on_first {
+            true => cx.render(rsx!(
+                div {
+                    span { node_ref: node_ref, "first" }
+                    span { "second" }
+                }
+            )),
+            false => cx.render(rsx!(
+                div {
+                    span { "first" }
+                    span { node_ref: node_ref, "second" }
+                }
+            )),
+        }
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+    let node_ref = mutations.refs[0].node_ref.clone();
+    assert_eq!(node_ref.id(), Some(ElementId(2)));
+    node_ref.set("first");
+
+    let refs = mounted_refs(&rerender(&mut dom, ScopeId(0)));
+    assert_eq!(refs.len(), 1);
+    assert!(refs[0].0.ptr_eq(&node_ref));
+    assert_eq!(refs[0].1, ElementId(4));
+
+    // the handle of the old element is stale
+    assert_eq!(node_ref.id(), Some(ElementId(4)));
+    assert!(node_ref.get::<&str>().is_none());
+}
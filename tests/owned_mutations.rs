@@ -0,0 +1,208 @@
+#![allow(non_snake_case)]
+
+//! Tests for converting mutations into their owned, `'static` form.
+
+use dioxus::prelude::*;
+use dioxus_core::{
+    ArbitraryAttributeValue, AttributeValue, AttributeValueOwned, CustomValue, DomEdit,
+    DomEditOwned, EditInterner, MutationsOwned, SchedulerMsg, ToOwnedError,
+};
+use std::{sync::Arc, thread};
+
+fn app(cx: Scope) -> Element {
+    let count = cx.use_hook(|_| 0);
+    *count += 1;
+
+    cx.render(rsx!(
+        div { class: "counter", onclick: move |_| {},
+            "count: {count}"
+        }
+        div { width: "{count}" }
+    ))
+}
+
+#[test]
+fn owned_edits_match_borrowed_edits() {
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+    let owned = mutations.try_to_owned().unwrap();
+
+    assert_eq!(owned.edits.len(), mutations.edits.len());
+    assert_eq!(owned.dirty_scopes, mutations.dirty_scopes);
+    assert_eq!(
+        owned.edits[..3],
+        [
+            DomEditOwned::CreateElement { root: 1, tag: "div".into() },
+            DomEditOwned::NewEventListener {
+                event_name: "click".into(),
+                scope: ScopeId(0),
+                root: 1
+            },
+            DomEditOwned::SetAttribute {
+                root: 1,
+                field: "class".into(),
+                value: AttributeValueOwned::Text("counter".into()),
+                ns: None,
+            },
+        ]
+    );
+
+    // both forms serialize the same way, so interpreters can't tell them apart
+    assert_eq!(
+        serde_json::to_value(&owned.edits).unwrap(),
+        serde_json::to_value(&mutations.edits).unwrap()
+    );
+
+    let json = serde_json::to_string(&owned).unwrap();
+    assert_eq!(
+        serde_json::from_str::<MutationsOwned>(&json).unwrap(),
+        owned
+    );
+}
+
+#[test]
+fn owned_mutations_can_be_sent_to_another_thread() {
+    let mut dom = VirtualDom::new(app);
+    let mut interner = EditInterner::new();
+    let (tx, rx) = std::sync::mpsc::channel::<MutationsOwned>();
+
+    let renderer = thread::spawn(move || {
+        rx.into_iter()
+            .flat_map(|mutations| mutations.edits)
+            .filter_map(|edit| match edit {
+                DomEditOwned::SetText { text, .. } => Some(text.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    });
+
+    tx.send(interner.mutations(&dom.rebuild()).unwrap())
+        .unwrap();
+    for _ in 0..2 {
+        dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+        for mutations in dom.work_with_deadline(|| false) {
+            tx.send(interner.mutations(&mutations).unwrap()).unwrap();
+        }
+    }
+    drop(tx);
+
+    assert_eq!(renderer.join().unwrap(), ["count: 2", "count: 3"]);
+}
+
+#[test]
+fn names_are_interned_across_batches() {
+    let edits = [DomEdit::CreateElement { tag: "div", root: 1 }];
+
+    let mut interner = EditInterner::new();
+    let first = interner.edit(&edits[0]).unwrap();
+    let second = interner.edit(&edits[0]).unwrap();
+
+    match (first, second) {
+        (
+            DomEditOwned::CreateElement { tag: a, .. },
+            DomEditOwned::CreateElement { tag: b, .. },
+        ) => {
+            assert!(Arc::ptr_eq(&a, &b))
+        }
+        _ => unreachable!(),
+    }
+
+    // a reset interner allocates again
+    let interned = interner.intern("div");
+    interner.reset();
+    assert!(!Arc::ptr_eq(&interned, &interner.intern("div")));
+}
+
+#[test]
+fn arbitrary_values_cannot_be_owned() {
+    let value = 10;
+    let edit = DomEdit::SetAttribute {
+        root: 3,
+        field: "data",
+        value: AttributeValue::Any(ArbitraryAttributeValue {
+            value: &value,
+            cmp: |a, b| a.downcast_ref::<i32>() == b.downcast_ref::<i32>(),
+        }),
+        ns: None,
+    };
+
+    let err = EditInterner::new().edit(&edit).unwrap_err();
+    assert_eq!(
+        err,
+        ToOwnedError::UnsupportedValue { root: 3, field: "data" }
+    );
+    assert_eq!(
+        err.to_string(),
+        "attribute \"data\" on node 3 holds an arbitrary value, which can't be owned"
+    );
+}
+
+#[test]
+fn owned_values_borrow_back() {
+    let values = [
+        AttributeValue::Text("red"),
+        AttributeValue::Float64(-0.1),
+        AttributeValue::Vec4Uint(0, 1, 2, u32::MAX),
+        AttributeValue::Bytes(&[0, 1, 255]),
+    ];
+
+    let mut interner = EditInterner::new();
+    for value in values {
+        let owned = interner
+            .edit(&DomEdit::SetAttribute { root: 1, field: "data", value, ns: None })
+            .unwrap();
+
+        let value = match &owned {
+            DomEditOwned::SetAttribute { value, .. } => value.as_borrowed(),
+            _ => unreachable!(),
+        };
+        let edit = DomEdit::SetAttribute { root: 1, field: "data", value, ns: None };
+        assert_eq!(interner.edit(&edit).unwrap(), owned);
+    }
+}
+
+#[test]
+fn stored_batches_keep_attribute_types() {
+    let values = [
+        AttributeValueOwned::Text("5".into()),
+        AttributeValueOwned::Float32(5.0),
+        AttributeValueOwned::Float64(-0.1),
+        AttributeValueOwned::Int32(5),
+        AttributeValueOwned::Int64(i64::MIN),
+        AttributeValueOwned::Uint32(5),
+        AttributeValueOwned::Uint64(u64::MAX),
+        AttributeValueOwned::Bool(true),
+        AttributeValueOwned::Vec3Float(0.0, 1.5, 255.0),
+        AttributeValueOwned::Vec3Int(-1, 0, 1),
+        AttributeValueOwned::Vec3Uint(0, 1, 255),
+        AttributeValueOwned::Vec4Float(0.0, 1.5, 2.0, 255.0),
+        AttributeValueOwned::Vec4Int(-1, 0, 1, 2),
+        AttributeValueOwned::Vec4Uint(0, 1, 2, u32::MAX),
+        AttributeValueOwned::Bytes(Arc::from([0, 1, 255].as_slice())),
+        AttributeValueOwned::Custom(CustomValue { name: "point".into(), payload: "[1,2]".into() }),
+    ];
+
+    let mutations = MutationsOwned {
+        edits: values
+            .iter()
+            .map(|value| DomEditOwned::SetAttribute {
+                root: 1,
+                field: "data".into(),
+                value: value.clone(),
+                ns: None,
+            })
+            .collect(),
+        dirty_scopes: Default::default(),
+    };
+
+    let json = serde_json::to_string(&mutations).unwrap();
+    assert_eq!(
+        serde_json::from_str::<MutationsOwned>(&json).unwrap(),
+        mutations
+    );
+
+    // the edits themselves still serialize the way the interpreters read them
+    let edits = serde_json::to_value(&mutations.edits).unwrap();
+    assert_eq!(edits[6]["value"], serde_json::json!(u64::MAX));
+    assert_eq!(edits[14]["value"], serde_json::json!([0, 1, 255]));
+}
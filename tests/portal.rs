@@ -0,0 +1,191 @@
+#![allow(non_snake_case)]
+
+//! Tests for rendering children into a different mount point with portals.
+
+use dioxus::prelude::*;
+use dioxus_core::{DomEdit::*, ElementId, SchedulerMsg};
+use std::cell::RefCell;
+
+mod test_utils;
+use test_utils::input_event;
+
+#[test]
+fn children_are_appended_to_the_named_root() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(
+            div {
+                Portal { target: "modals",
+                    span { "hello" }
+                }
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+
+    assert_eq!(
+        mutations.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            CreatePlaceholder { root: 2 },
+            PushNamedRoot { name: "modals" },
+            CreateElement { tag: "span", root: 3 },
+            CreateTextNode { text: "hello", root: 4 },
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+            PopRoot {},
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+        ]
+    );
+}
+
+#[test]
+fn children_are_diffed_in_place() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(
+            Portal { target: "modals",
+                "count: {count}"
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let mutations = dom.work_with_deadline(|| false);
+
+    assert_eq!(mutations[0].edits, [SetText { root: 2, text: "count: 2" }]);
+}
+
+#[test]
+fn events_bubble_through_the_owner() {
+    thread_local! {
+        static INPUTS: RefCell<Vec<String>> = RefCell::new(Vec::new());
+    }
+
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(
+            div { oninput: move |evt| INPUTS.with(|inputs| inputs.borrow_mut().push(evt.value.clone())),
+                Modal {}
+            }
+        ))
+    }
+
+    fn Modal(cx: Scope) -> Element {
+        cx.render(rsx!(
+            Portal { target: "modals",
+                div {
+                    input {}
+                }
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    let mutations = dom.rebuild();
+    assert!(mutations
+        .edits
+        .contains(&CreateElement { tag: "input", root: 4 }));
+
+    // the input isn't a child of the div in the renderer, but it is in the VirtualDom
+    dom.handle_message(SchedulerMsg::Event(input_event(ElementId(4), "hello")));
+    dom.work_with_deadline(|| false);
+
+    INPUTS.with(|inputs| assert_eq!(*inputs.borrow(), ["hello"]));
+}
+
+#[test]
+fn removing_a_portal_removes_its_children() {
+    fn app(cx: Scope) -> Element {
+        let show = cx.use_hook(|_| true);
+        let showing = *show;
+        *show = false;
+
+        cx.render(rsx!(
+            div {
+                showing.then(|| rsx!(
+                    Portal { target: "modals",
+                        "first"
+                        "second"
+                    }
+                ))
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let mutations = dom.work_with_deadline(|| false);
+
+    // the children live in the named root, so replacing the placeholder doesn't remove them
+    assert_eq!(
+        mutations[0].edits,
+        [
+            CreatePlaceholder { root: 5 },
+            ReplaceWith { root: 2, m: 1 },
+            Remove { root: 3 },
+            Remove { root: 4 },
+        ]
+    );
+}
+
+#[test]
+fn removing_an_ancestor_removes_the_children() {
+    fn app(cx: Scope) -> Element {
+        let show = cx.use_hook(|_| true);
+        let showing = *show;
+        *show = false;
+
+        cx.render(rsx!(showing.then(|| rsx!(
+            div {
+                Portal { target: "modals",
+                    "hello"
+                }
+            }
+        ))))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let mutations = dom.work_with_deadline(|| false);
+
+    assert_eq!(
+        mutations[0].edits,
+        [
+            CreatePlaceholder { root: 4 },
+            ReplaceWith { root: 1, m: 1 },
+            Remove { root: 3 },
+        ]
+    );
+}
+
+#[test]
+fn portals_render_a_placeholder_on_the_server() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(
+            div {
+                Portal { target: "modals",
+                    span { "hello" }
+                }
+            }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<div><!--placeholder--></div>"
+    );
+}
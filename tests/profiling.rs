@@ -0,0 +1,122 @@
+#![allow(non_snake_case)]
+
+//! Tests for the per-component profiler enabled with the `profile` feature.
+
+use dioxus::prelude::*;
+use dioxus_core::{CycleKind, DirtyReason, ElementId, EventPriority, SchedulerMsg, UserEvent};
+
+mod test_utils;
+use test_utils::{input_event, rerender};
+
+fn app(cx: Scope) -> Element {
+    let count = use_state(&cx, || 0);
+
+    cx.render(rsx!(
+        input { oninput: move |_| count.set(count + 1) }
+        Child { count: **count }
+    ))
+}
+
+#[inline_props]
+fn Child(cx: Scope, count: i32) -> Element {
+    cx.render(rsx!("{count}"))
+}
+
+#[test]
+fn rebuild_records_mounts() {
+    let mut dom = VirtualDom::new(app);
+    let edits = dom.rebuild().edits.len();
+
+    let report = dom.take_profile();
+    assert_eq!(report.cycles.len(), 1);
+
+    let cycle = &report.cycles[0];
+    assert_eq!(cycle.kind, CycleKind::Rebuild);
+    assert_eq!(cycle.edits, edits);
+
+    let renders: Vec<_> = cycle
+        .renders
+        .iter()
+        .map(|r| (r.scope, r.name, r.reason))
+        .collect();
+    assert_eq!(
+        renders,
+        [
+            (ScopeId(0), None, DirtyReason::Mount),
+            (ScopeId(1), Some("Child"), DirtyReason::Mount),
+        ]
+    );
+
+    // the root's diff includes everything its child did
+    let (root, child) = (&cycle.renders[0], &cycle.renders[1]);
+    assert_eq!(root.edits + 1, edits);
+    assert_eq!(child.edits, 1);
+    assert!(child.render.start >= root.diff.start);
+    assert!(child.diff.start + child.diff.duration <= root.diff.start + root.diff.duration);
+
+    // the report was taken
+    assert!(dom.profile().cycles.is_empty());
+}
+
+#[test]
+fn reasons_are_tracked_across_cycles() {
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.take_profile();
+
+    // updates scheduled by a listener are attributed to the event, children to their parent
+    let event = input_event(ElementId(1), "hello");
+    dom.handle_message(SchedulerMsg::Event(UserEvent {
+        priority: EventPriority::Immediate,
+        ..event
+    }));
+    dom.work_with_deadline(|| false);
+
+    rerender(&mut dom, ScopeId(1));
+
+    // cycles without any renders are skipped
+    dom.work_with_deadline(|| false);
+
+    let report = dom.profile();
+    assert_eq!(report.cycles.len(), 2);
+    assert!(report.cycles.iter().all(|c| c.kind == CycleKind::Work));
+
+    let reasons: Vec<_> = report.renders().map(|r| (r.scope, r.reason)).collect();
+    assert_eq!(
+        reasons,
+        [
+            (ScopeId(0), DirtyReason::Event("input")),
+            (ScopeId(1), DirtyReason::ParentRender(ScopeId(0))),
+            (ScopeId(1), DirtyReason::Immediate),
+        ]
+    );
+
+    assert_eq!(report.renders_of(ScopeId(1)).count(), 2);
+    assert_eq!(report.slowest_renders(2).len(), 2);
+
+    // only the text changed
+    assert_eq!(report.cycles[0].edits, 1);
+    assert_eq!(report.total_edits(), 1);
+}
+
+#[test]
+fn chrome_trace_is_valid_json() {
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    dom.work_with_deadline(|| false);
+
+    let trace = dom.profile().to_chrome_trace();
+    let trace: serde_json::Value = serde_json::from_str(&trace).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+
+    // a cycle event plus a render and a diff for each scope that rendered: both of them, then only the child
+    assert_eq!(events.len(), 8);
+    assert!(events.iter().all(|e| e["ph"] == "X"));
+
+    assert_eq!(events[0]["name"], "rebuild");
+    assert_eq!(events[3]["name"], "render Child (ScopeId(1))");
+    assert_eq!(events[3]["args"]["reason"], "Mount");
+    assert_eq!(events[6]["name"], "render Child (ScopeId(1))");
+    assert_eq!(events[6]["args"]["reason"], "Immediate");
+}
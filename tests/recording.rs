@@ -0,0 +1,176 @@
+#![allow(non_snake_case)]
+
+//! Tests for recording the messages a VirtualDom processes and replaying them.
+
+use dioxus::prelude::*;
+use dioxus_core::{AttributeValue, ElementId, EventPriority, SchedulerMsg};
+use dioxus_html::{RecordedEntry, RecordedMessage, Recorder, Recording, ReplayError};
+
+mod test_utils;
+use test_utils::input_event;
+
+fn app(cx: Scope) -> Element {
+    let value = use_state(&cx, String::new);
+
+    cx.render(rsx!(
+        input { oninput: move |evt| value.set(evt.value.clone()) }
+        "{value}"
+        Child { len: value.len() }
+    ))
+}
+
+#[inline_props]
+fn Child(cx: Scope, len: usize) -> Element {
+    let len = *len;
+
+    // rsx only builds text attributes, so the numeric value is set by hand
+    cx.render(LazyNodes::new(move |f| {
+        let value = AttributeValue::Uint64(len as u64);
+        let attributes = f
+            .bump()
+            .alloc([f.custom_attr("value", value, None, false, false)]);
+        let children = f.bump().alloc([f.text(format_args!("{} characters", len))]);
+        f.raw_element("meter", None, &[], attributes, children, None)
+    }))
+}
+
+/// Type `value` into the app's input, replacing whatever was there
+fn type_into_input(dom: &mut VirtualDom, value: &str) {
+    dom.handle_message(SchedulerMsg::Event(input_event(ElementId(1), value)));
+}
+
+fn record_session() -> Recording {
+    let mut dom = VirtualDom::new(app);
+    let recorder = Recorder::attach(&mut dom);
+
+    recorder.rebuild(&mut dom);
+
+    type_into_input(&mut dom, "hello");
+    recorder.work_with_deadline(&mut dom, || false);
+
+    type_into_input(&mut dom, "hello world");
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    recorder.work_with_deadline(&mut dom, || false);
+
+    recorder.take_recording()
+}
+
+#[test]
+fn sessions_are_recorded_in_order() {
+    let recording = record_session();
+
+    let times: Vec<_> = recording
+        .entries
+        .iter()
+        .map(|entry| match entry {
+            RecordedEntry::Message { time, .. }
+            | RecordedEntry::Rebuild { time, .. }
+            | RecordedEntry::Work { time, .. } => *time,
+        })
+        .collect();
+    assert_eq!(times, [1, 2, 3, 4, 5, 6]);
+
+    match &recording.entries[1] {
+        RecordedEntry::Message { message: RecordedMessage::Event { name, data, .. }, .. } => {
+            assert_eq!(name, "input");
+            assert_eq!(data.as_ref().unwrap()["value"], "hello");
+        }
+        entry => panic!("expected an event, found {:?}", entry),
+    }
+
+    match &recording.entries[2] {
+        RecordedEntry::Work { deadline, mutations: Some(mutations), .. } => {
+            assert_eq!(*deadline, None);
+            assert_eq!(mutations.len(), 1);
+        }
+        entry => panic!("expected work, found {:?}", entry),
+    }
+}
+
+#[test]
+fn recordings_replay_against_a_fresh_dom() {
+    let recording = record_session();
+
+    // recordings survive being saved to disk
+    let json = serde_json::to_string(&recording).unwrap();
+    let recording: Recording = serde_json::from_str(&json).unwrap();
+
+    recording.replay(&mut VirtualDom::new(app)).unwrap();
+}
+
+#[test]
+fn replays_detect_divergence() {
+    fn buggy_app(cx: Scope) -> Element {
+        let value = use_state(&cx, String::new);
+
+        cx.render(rsx!(
+            input { oninput: move |evt| value.set(evt.value.to_uppercase()) }
+            "{value}"
+            Child { len: value.len() }
+        ))
+    }
+
+    let recording = record_session();
+
+    match recording.replay(&mut VirtualDom::new(buggy_app)) {
+        Err(ReplayError::Diverged { time, .. }) => assert_eq!(time, 3),
+        result => panic!("expected the replay to diverge, got {:?}", result),
+    }
+}
+
+#[test]
+fn deadlines_are_replayed() {
+    let mut dom = VirtualDom::new(app);
+    let recorder = Recorder::attach(&mut dom);
+    recorder.rebuild(&mut dom);
+
+    // the deadline passes once the text is diffed, so the child is left for the second call
+    type_into_input(&mut dom, "hi");
+    let mut checks = 0;
+    let deadline = || {
+        checks += 1;
+        checks > 1
+    };
+    assert_eq!(recorder.work_with_deadline(&mut dom, deadline).len(), 1);
+    assert_eq!(recorder.work_with_deadline(&mut dom, || false).len(), 1);
+
+    let recording = recorder.recording();
+    assert!(matches!(
+        recording.entries[2],
+        RecordedEntry::Work { deadline: Some(1), .. }
+    ));
+
+    recording.replay(&mut VirtualDom::new(app)).unwrap();
+}
+
+#[test]
+fn unknown_events_cannot_be_replayed() {
+    let message = RecordedMessage::Event {
+        name: "teleport".to_string(),
+        element: Some(ElementId(1)),
+        scope: None,
+        priority: EventPriority::High,
+        data: None,
+    };
+    let recording =
+        Recording { entries: vec![RecordedEntry::Message { time: 1, message: message.clone() }] };
+
+    let err = recording.replay(&mut VirtualDom::new(app)).unwrap_err();
+    assert_eq!(err, ReplayError::InvalidEvent { time: 1, message });
+}
+
+#[test]
+fn events_without_data_cannot_be_replayed() {
+    let message = RecordedMessage::Event {
+        name: "input".to_string(),
+        element: Some(ElementId(1)),
+        scope: None,
+        priority: EventPriority::High,
+        data: None,
+    };
+    let recording =
+        Recording { entries: vec![RecordedEntry::Message { time: 1, message: message.clone() }] };
+
+    let err = recording.replay(&mut VirtualDom::new(app)).unwrap_err();
+    assert_eq!(err, ReplayError::InvalidEvent { time: 1, message });
+}
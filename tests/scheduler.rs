@@ -1,6 +1,290 @@
+#![allow(non_snake_case)]
+
 //! Tests for the scheduler.
 //!
-//! TODO
 //! - priority lanes
-//! - periodic checking
-//!
+//! - periodic checking of the deadline
+
+use dioxus::prelude::*;
+use dioxus_core::{DomEdit::*, ElementId, EventPriority, SchedulerMsg, UserEvent};
+
+mod test_utils;
+use test_utils::{input_event, rerender, update};
+
+fn Counter(cx: Scope) -> Element {
+    let count = cx.use_hook(|_| 0);
+    *count += 1;
+
+    cx.render(rsx!("{count}"))
+}
+
+#[inline_props]
+fn Child(cx: Scope, count: i32) -> Element {
+    cx.render(rsx!("{count}"))
+}
+
+/// An event that isn't aimed at any element, so only its priority matters
+fn unhandled_event(priority: EventPriority) -> SchedulerMsg {
+    SchedulerMsg::Event(UserEvent { priority, element: None, ..input_event(ElementId(0), "") })
+}
+
+#[test]
+fn deadline_is_checked_between_components() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Counter {} Counter {}))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(2)));
+
+    // at least one component is diffed, even if the deadline has already passed
+    let edits = dom.work_with_deadline(|| true);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits, [SetText { root: 2, text: "2" }]);
+    assert!(dom.has_work());
+
+    // the next call picks up where the last one stopped
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits, [SetText { root: 1, text: "2" }]);
+    assert!(!dom.has_work());
+}
+
+#[test]
+fn children_are_deferred_once_out_of_time() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(
+            "{count}"
+            Child { count: *count }
+            Child { count: *count }
+        ))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+
+    // the parent's edits are complete, its children are left for later
+    let edits = dom.work_with_deadline(|| true);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits, [SetText { root: 1, text: "2" }]);
+    assert!(edits[0].dirty_scopes.contains(&ScopeId(0)));
+    assert!(!edits[0].dirty_scopes.contains(&ScopeId(1)));
+    assert!(dom.has_work());
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits.len(), 2);
+    assert_eq!(edits[0].edits, [SetText { root: 3, text: "2" }]);
+    assert_eq!(edits[1].edits, [SetText { root: 2, text: "2" }]);
+    assert!(!dom.has_work());
+}
+
+#[test]
+fn children_are_diffed_inline_with_time_left() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(Child { count: *count }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    let edits = rerender(&mut dom, ScopeId(0));
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits, [SetText { root: 1, text: "2" }]);
+}
+
+#[test]
+fn diffs_are_paused_between_siblings() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        let count = *count;
+        cx.render(rsx!(ul { (0..10).map(|i| rsx!(li { "{i}: {count}" })) }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+
+    // the first item is diffed and the rest are saved, holding back the component's edits
+    assert!(dom.work_with_deadline(|| true).is_empty());
+    assert!(dom.has_work());
+
+    // every call picks up where the last one stopped
+    for _ in 1..9 {
+        assert!(dom.work_with_deadline(|| true).is_empty());
+    }
+
+    let edits = dom.work_with_deadline(|| true);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits.len(), 10);
+    assert_eq!(edits[0].edits[0], SetText { root: 3, text: "0: 2" });
+    assert_eq!(edits[0].edits[9], SetText { root: 21, text: "9: 2" });
+    assert!(edits[0].dirty_scopes.contains(&ScopeId(0)));
+    drop(edits);
+
+    assert!(!dom.has_work());
+}
+
+#[test]
+fn keyed_diffs_are_paused_at_their_ends() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        // the middle of the list is reversed on the second render
+        let count = *count;
+        let items = match count {
+            1 => [0, 1, 2, 3, 4, 5, 6, 7],
+            _ => [0, 1, 2, 5, 4, 3, 6, 7],
+        };
+
+        cx.render(rsx!(ul {
+            items.iter().map(|i| rsx!(li { key: "{i}", "{i}: {count}" }))
+        }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+
+    let mut calls = 1;
+    while dom.work_with_deadline(|| true).is_empty() {
+        calls += 1;
+    }
+
+    // the items in the middle are moved in one go, the ends are diffed an item at a time
+    assert_eq!(calls, 5);
+    assert!(!dom.has_work());
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<ul><li>0: 2</li><li>1: 2</li><li>2: 2</li><li>5: 2</li><li>4: 2</li><li>3: 2</li><li>6: 2</li><li>7: 2</li></ul>"
+    );
+}
+
+#[test]
+fn higher_priority_events_pause_diffs() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        let count = *count;
+        cx.render(rsx!(ul { (0..10).map(|i| rsx!(li { "{i}: {count}" })) }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+
+    let channel = dom.get_scheduler_channel();
+    channel
+        .unbounded_send(unhandled_event(EventPriority::High))
+        .unwrap();
+
+    // the diff stops before it reaches the deadline
+    assert!(dom.work_with_deadline(|| false).is_empty());
+    assert!(dom.has_work());
+
+    // the event is handled and the diff is finished
+    let edits = update(&mut dom);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].edits.len(), 10);
+}
+
+#[test]
+fn updates_from_events_take_their_priority() {
+    fn Form(cx: Scope) -> Element {
+        let value = use_state(&cx, || 0);
+
+        cx.render(rsx!(
+            input { oninput: move |_| value.set(value + 1) }
+            "{value}"
+        ))
+    }
+
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Counter {} Form {}))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    // the counter was marked dirty first, but the form is updated by a user event
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    let event = input_event(ElementId(2), "hello");
+    dom.handle_message(SchedulerMsg::Event(UserEvent {
+        priority: EventPriority::High,
+        ..event
+    }));
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits[0].edits, [SetText { root: 3, text: "1" }]);
+    assert_eq!(edits[1].edits, [SetText { root: 1, text: "2" }]);
+}
+
+#[test]
+fn higher_priority_events_preempt_work() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Counter {} Counter {} Counter {}))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(2)));
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(3)));
+
+    // an event arrives while the background work is in progress
+    let channel = dom.get_scheduler_channel();
+    channel
+        .unbounded_send(unhandled_event(EventPriority::High))
+        .unwrap();
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits.len(), 1);
+    assert!(dom.has_work());
+
+    // the remaining work is resumed after the event is handled
+    let edits = update(&mut dom);
+    assert_eq!(edits.len(), 2);
+    assert!(!dom.has_work());
+}
+
+#[test]
+fn events_of_equal_priority_do_not_preempt_work() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Counter {} Counter {}))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(1)));
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(2)));
+
+    let channel = dom.get_scheduler_channel();
+    channel
+        .unbounded_send(unhandled_event(EventPriority::Low))
+        .unwrap();
+
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits.len(), 2);
+
+    // the event is still waiting to be handled
+    assert!(dom.has_work());
+}
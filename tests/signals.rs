@@ -0,0 +1,158 @@
+#![allow(non_snake_case)]
+
+//! Tests for signals: which components re-render when they change, memos, and writes from tasks.
+
+use dioxus::prelude::*;
+use std::cell::Cell;
+
+mod test_utils;
+use test_utils::{run, update};
+
+thread_local! {
+    static COUNT: Cell<Option<Signal<i32>>> = Cell::new(None);
+    static RENDERS: Cell<[usize; 3]> = Cell::new([0; 3]);
+    static COMPUTED: Cell<usize> = Cell::new(0);
+}
+
+fn count() -> Signal<i32> {
+    COUNT.with(|count| count.get()).unwrap()
+}
+
+fn rendered(idx: usize) {
+    RENDERS.with(|renders| {
+        let mut counts = renders.get();
+        counts[idx] += 1;
+        renders.set(counts);
+    });
+}
+
+fn renders() -> [usize; 3] {
+    RENDERS.with(|renders| renders.get())
+}
+
+#[test]
+fn writes_rerender_only_the_components_that_read() {
+    fn app(cx: Scope) -> Element {
+        rendered(0);
+        let count = use_signal(&cx, || 0);
+        COUNT.with(|cell| cell.set(Some(count)));
+
+        cx.render(rsx!(
+            div {
+                Reader { count: count }
+                Holder { count: count }
+            }
+        ))
+    }
+
+    #[inline_props]
+    fn Reader(cx: Scope, count: Signal<i32>) -> Element {
+        rendered(1);
+        cx.render(rsx!("{count}"))
+    }
+
+    #[inline_props]
+    fn Holder(cx: Scope, count: Signal<i32>) -> Element {
+        rendered(2);
+        let _ = count.peek();
+        cx.render(rsx!("holder"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(renders(), [1, 1, 1]);
+
+    count().set(5);
+    update(&mut dom);
+    assert_eq!(renders(), [1, 2, 1]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<div>5<!--spacer-->holder</div>"
+    );
+
+    // reads outside of a render don't subscribe anyone
+    assert_eq!(count().get(), 5);
+    *count().write() += 1;
+    update(&mut dom);
+    assert_eq!(renders(), [1, 3, 1]);
+    assert_eq!(
+        dioxus::ssr::render_vdom(&dom),
+        "<div>6<!--spacer-->holder</div>"
+    );
+}
+
+#[test]
+fn memos_recompute_lazily() {
+    fn app(cx: Scope) -> Element {
+        let count = use_signal(&cx, || 1);
+        COUNT.with(|cell| cell.set(Some(count)));
+
+        let doubled = use_memo(&cx, move || {
+            COMPUTED.with(|computed| computed.set(computed.get() + 1));
+            count.get() * 2
+        });
+
+        cx.render(rsx!(Doubled { doubled: doubled }))
+    }
+
+    #[inline_props]
+    fn Doubled(cx: Scope, doubled: ReadOnlySignal<i32>) -> Element {
+        rendered(0);
+        cx.render(rsx!("{doubled}"))
+    }
+
+    let computed = || COMPUTED.with(|computed| computed.get());
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(computed(), 1);
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "2");
+
+    // several writes only recompute once, when the memo is read again
+    count().set(2);
+    count().set(3);
+    assert_eq!(computed(), 1);
+
+    update(&mut dom);
+    assert_eq!(computed(), 2);
+    assert_eq!(renders()[0], 2);
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "6");
+}
+
+#[tokio::test]
+async fn tasks_can_write_to_signals() {
+    fn app(cx: Scope) -> Element {
+        let mut count = use_signal(&cx, || 0);
+
+        cx.use_hook(|_| {
+            cx.push_future(async move {
+                count += 10;
+            })
+        });
+
+        cx.render(rsx!("{count}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "0");
+
+    run(&mut dom).await;
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "10");
+}
+
+#[test]
+#[should_panic(expected = "after the component that owns it was dropped")]
+fn signals_are_dropped_with_their_owner() {
+    fn app(cx: Scope) -> Element {
+        let count = use_signal(&cx, || 0);
+        COUNT.with(|cell| cell.set(Some(count)));
+        None
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    drop(dom);
+
+    count().get();
+}
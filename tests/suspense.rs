@@ -0,0 +1,135 @@
+#![allow(non_snake_case)]
+
+//! Tests for suspense boundaries holding back their subtree until its tasks complete.
+
+use dioxus::prelude::*;
+use dioxus_core::{DomEdit::*, SchedulerMsg};
+use std::time::Duration;
+
+#[inline_props]
+fn Suspense<'a>(cx: Scope, children: Element<'a>) -> Element {
+    cx.provide_suspense_boundary(Loading);
+    cx.render(rsx!(children))
+}
+
+fn Loading(cx: Scope) -> Element {
+    cx.render(rsx!("loading"))
+}
+
+fn Delayed(cx: Scope) -> Element {
+    use_suspense(
+        &cx,
+        || async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            "done"
+        },
+        |value| cx.render(rsx!(h1 { "{value}" })),
+    )
+}
+
+#[tokio::test]
+async fn fallback_is_swapped_for_resolved_subtree() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(div { Suspense { Delayed {} } }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+
+    // only the fallback makes it to the renderer
+    let edits = dom.rebuild();
+    assert_eq!(
+        edits.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            CreateTextNode { text: "loading", root: 3 },
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+        ]
+    );
+
+    let boundary = dom
+        .get_scope(ScopeId(1))
+        .unwrap()
+        .provide_suspense_boundary(Loading);
+    assert!(boundary.is_suspended());
+    assert_eq!(boundary.pending_tasks(), 1);
+
+    // once the task completes, the subtree is created in its final state and swapped in
+    dom.wait_for_work().await;
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits.len(), 1);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateElement { tag: "h1", root: 4 },
+            CreateTextNode { text: "done", root: 5 },
+            AppendChildren { many: 1 },
+            InsertBefore { root: 3, n: 1 },
+            Remove { root: 3 },
+        ]
+    );
+
+    assert!(boundary.is_resolved());
+    assert!(dom.get_scope(ScopeId(3)).is_none());
+}
+
+#[test]
+fn boundary_without_tasks_renders_immediately() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(div { Suspense { "ready" } }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+
+    let edits = dom.rebuild();
+    assert_eq!(
+        edits.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            CreateTextNode { text: "ready", root: 2 },
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+        ]
+    );
+
+    let boundary = dom
+        .get_scope(ScopeId(1))
+        .unwrap()
+        .provide_suspense_boundary(Loading);
+    assert!(boundary.is_resolved());
+}
+
+#[tokio::test]
+async fn updates_while_suspended_are_held_back() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Suspense { Delayed {} Counter {} }))
+    }
+
+    fn Counter(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+        cx.render(rsx!("{count}"))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+
+    // the counter was never mounted, so nothing is sent for it
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(3)));
+    let edits = dom.work_with_deadline(|| false);
+    assert!(edits.iter().all(|edits| edits.edits.is_empty()));
+}
+
+#[tokio::test]
+async fn ssr_renders_fallback_or_resolved_content() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(div { Suspense { Delayed {} } }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert_eq!(dioxus::ssr::render_vdom(&dom), "<div>loading</div>");
+
+    let html = dioxus::ssr::render_vdom_resolved(&mut dom).await;
+    assert_eq!(html, "<div><h1>done</h1></div>");
+}
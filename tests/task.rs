@@ -1 +1,69 @@
-fn main() {}
+//! Tests for task handles: spawning and cancelling tasks from inside other tasks.
+
+use dioxus::prelude::*;
+use std::{
+    cell::Cell,
+    future::{pending, ready},
+};
+
+thread_local! {
+    static RAN: Cell<bool> = Cell::new(false);
+    static DROPPED: Cell<bool> = Cell::new(false);
+}
+
+#[tokio::test]
+async fn tasks_can_spawn_tasks() {
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|_| {
+            let (handle, update) = (cx.task_handle(), cx.schedule_update());
+            cx.task_handle().spawn(async move {
+                ready(()).await;
+                handle.spawn(async move {
+                    RAN.with(|ran| ran.set(true));
+                    update();
+                });
+            })
+        });
+        None
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    dom.wait_for_work().await;
+    assert!(RAN.with(|ran| ran.get()));
+}
+
+#[tokio::test]
+async fn tasks_can_cancel_tasks() {
+    struct DropGuard;
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            DROPPED.with(|dropped| dropped.set(true));
+        }
+    }
+
+    fn app(cx: Scope) -> Element {
+        cx.use_hook(|_| {
+            let handle = cx.task_handle();
+            let guard = DropGuard;
+            let forever = handle.spawn(async move {
+                let _guard = guard;
+                pending::<()>().await;
+            });
+
+            let update = cx.schedule_update();
+            handle.clone().spawn(async move {
+                handle.remove(forever);
+                update();
+            })
+        });
+        None
+    }
+
+    let mut dom = VirtualDom::new(app);
+    dom.rebuild();
+    assert!(!DROPPED.with(|dropped| dropped.get()));
+
+    dom.wait_for_work().await;
+    assert!(DROPPED.with(|dropped| dropped.get()));
+}
@@ -0,0 +1,198 @@
+#![allow(non_snake_case)]
+
+//! Tests for static subtrees being hoisted out of rsx! and cloned by the renderer.
+
+use dioxus::prelude::*;
+use dioxus_core::{AttributeValue, DomEdit::*, SchedulerMsg};
+use std::time::Duration;
+
+fn new_dom(app: Component) -> VirtualDom {
+    let mut dom = VirtualDom::new(app);
+    dom.enable_templates();
+    dom
+}
+
+#[test]
+fn static_subtree_is_saved_then_cloned() {
+    fn app(cx: Scope) -> Element {
+        let count = cx.use_hook(|_| 0);
+        *count += 1;
+
+        cx.render(rsx!(div {
+            "{count}"
+            h1 { class: "title", "hello" }
+        }))
+    }
+
+    let mut dom = new_dom(app);
+
+    let edits = dom.rebuild();
+    assert_eq!(
+        edits.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            CreateTextNode { text: "1", root: 2 },
+            CreateElement { tag: "h1", root: 4 },
+            SetAttribute {
+                root: 4,
+                field: "class",
+                value: AttributeValue::Text("title"),
+                ns: None
+            },
+            CreateTextNode { text: "hello", root: 5 },
+            AppendChildren { many: 1 },
+            SaveTemplate { id: 0 },
+            CloneTemplate { id: 0, root: 3 },
+            AppendChildren { many: 2 },
+            AppendChildren { many: 1 },
+        ]
+    );
+
+    // the template is untouched by the diff
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(edits[0].edits, [SetText { root: 2, text: "2" }]);
+}
+
+#[test]
+fn templates_are_only_saved_once() {
+    fn app(cx: Scope) -> Element {
+        let show = cx.use_hook(|_| true);
+        *show = !*show;
+
+        cx.render(rsx!(
+            ul {
+                (0..2).map(|_| rsx!(li { class: "item", "-" }))
+            }
+            show.then(|| rsx!(p { "toggled" }))
+        ))
+    }
+
+    let mut dom = new_dom(app);
+
+    let edits = dom.rebuild();
+    let saves = edits
+        .edits
+        .iter()
+        .filter(|edit| matches!(edit, SaveTemplate { .. }))
+        .count();
+    let clones = edits
+        .edits
+        .iter()
+        .filter(|edit| matches!(edit, CloneTemplate { id: 0, .. }))
+        .count();
+    assert_eq!(saves, 1);
+    assert_eq!(clones, 2);
+
+    // a template created after the first rebuild is saved when it's first needed
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let edits = dom.work_with_deadline(|| false);
+    assert_eq!(
+        edits[0].edits,
+        [
+            CreateElement { tag: "p", root: 6 },
+            CreateTextNode { text: "toggled", root: 7 },
+            AppendChildren { many: 1 },
+            SaveTemplate { id: 1 },
+            CloneTemplate { id: 1, root: 5 },
+            ReplaceWith { root: 3, m: 1 },
+        ]
+    );
+
+    // removing and recreating it only clones it again
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    dom.work_with_deadline(|| false);
+    dom.handle_message(SchedulerMsg::Immediate(ScopeId(0)));
+    let edits = dom.work_with_deadline(|| false);
+    assert!(matches!(
+        edits[0].edits[..],
+        [CloneTemplate { id: 1, .. }, ReplaceWith { .. }]
+    ));
+}
+
+#[test]
+fn templates_are_expanded_by_default() {
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(div { class: "static", "hello" }))
+    }
+
+    let mut dom = VirtualDom::new(app);
+
+    let edits = dom.rebuild();
+    assert_eq!(
+        edits.edits,
+        [
+            CreateElement { tag: "div", root: 1 },
+            SetAttribute {
+                root: 1,
+                field: "class",
+                value: AttributeValue::Text("static"),
+                ns: None
+            },
+            CreateTextNode { text: "hello", root: 2 },
+            AppendChildren { many: 1 },
+            AppendChildren { many: 1 },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn templates_created_while_suspended_are_saved_on_resolve() {
+    #[inline_props]
+    fn Suspense<'a>(cx: Scope, children: Element<'a>) -> Element {
+        cx.provide_suspense_boundary(Loading);
+        cx.render(rsx!(children))
+    }
+
+    fn Loading(cx: Scope) -> Element {
+        cx.render(rsx!("loading"))
+    }
+
+    fn Slow(cx: Scope) -> Element {
+        cx.use_hook(|_| cx.push_future(tokio::time::sleep(Duration::from_millis(10))));
+        cx.render(rsx!(p { class: "slow", "done" }))
+    }
+
+    fn app(cx: Scope) -> Element {
+        cx.render(rsx!(Suspense { Slow {} }))
+    }
+
+    let mut dom = new_dom(app);
+
+    let edits = dom.rebuild();
+    assert!(!edits
+        .edits
+        .iter()
+        .any(|edit| matches!(edit, SaveTemplate { .. })));
+
+    // the template was built while suspended, but the renderer never saw it
+    dom.wait_for_work().await;
+    let edits = dom.work_with_deadline(|| false);
+    let edits = &edits[0].edits;
+    assert!(edits.contains(&SaveTemplate { id: 0 }));
+    assert!(matches!(
+        edits[..],
+        [
+            ..,
+            CloneTemplate { id: 0, .. },
+            InsertBefore { .. },
+            Remove { .. }
+        ]
+    ));
+}
+
+#[test]
+fn ssr_renders_template_contents() {
+    fn app(cx: Scope) -> Element {
+        let name = "dynamic";
+        cx.render(rsx!(div { "{name}" span { class: "a", "static" } }))
+    }
+
+    let mut dom = new_dom(app);
+    dom.rebuild();
+
+    assert_eq!(
+        dioxus_ssr::render_vdom(&dom),
+        r#"<div>dynamic<span class="a">static</span></div>"#
+    );
+}
@@ -0,0 +1,25 @@
+//! Helpers shared by the integration tests. Include them with `mod test_utils;`.
+
+#![allow(dead_code, unused_imports)]
+
+use dioxus_core::{Mutations, SchedulerMsg, ScopeId, VirtualDom};
+
+pub use dioxus_testing::input_event;
+
+/// Handle every queued message, then render everything that's dirty
+pub fn update(dom: &mut VirtualDom) -> Vec<Mutations<'_>> {
+    dom.process_all_messages();
+    dom.work_with_deadline(|| false)
+}
+
+/// Mark a scope dirty and render it
+pub fn rerender(dom: &mut VirtualDom, scope: ScopeId) -> Vec<Mutations<'_>> {
+    dom.handle_message(SchedulerMsg::Immediate(scope));
+    dom.work_with_deadline(|| false)
+}
+
+/// Wait until a task wakes the dom up, then render what it changed
+pub async fn run(dom: &mut VirtualDom) -> Vec<Mutations<'_>> {
+    dom.wait_for_work().await;
+    dom.work_with_deadline(|| false)
+}